@@ -0,0 +1,93 @@
+//! Actions of a notification and how they are presented.
+
+/// Key of the action that is invoked by clicking the notification itself.
+pub(crate) const DEFAULT: &str = "default";
+
+/// Key of the action KDE Plasma shows as a text field to reply in.
+pub(crate) const INLINE_REPLY: &str = "inline-reply";
+
+/// Shown in the empty reply field by KDE Plasma, see [`Notification::inline_reply()`](crate::Notification::inline_reply).
+#[cfg(all(unix, not(target_os = "macos")))]
+pub(crate) const REPLY_PLACEHOLDER_HINT: &str = "x-kde-reply-placeholder-text";
+
+/// Text to prefill the reply field with, only sent to servers that advertise it as a capability.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub(crate) const REPLY_DEFAULT_HINT: &str = "x-kde-reply-default-text";
+
+/// How a server presents an action.
+///
+/// The specification gives the `"default"` key a meaning of its own,
+/// KDE Plasma does the same for `"inline-reply"`, every other action becomes a button.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ActionKind {
+    /// Invoked by clicking the notification itself, servers usually don't show a button for it.
+    Default,
+    /// A button showing the label of the action.
+    Button,
+    /// A text field to reply in (KDE only), other servers show a button instead.
+    InlineReply,
+}
+
+/// A single action of a notification, see [`Notification::action()`](crate::Notification::action).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Action {
+    /// Identifies the action when it is invoked.
+    pub key: String,
+    /// The localized string that is displayed to the user.
+    pub label: String,
+    /// How the action is presented, derived from the `key`.
+    pub kind: ActionKind,
+}
+
+impl Action {
+    /// Constructs a new action, its kind follows from the `key`.
+    pub fn new(key: &str, label: &str) -> Self {
+        Action {
+            key: key.to_owned(),
+            label: label.to_owned(),
+            kind: ActionKind::of(key),
+        }
+    }
+
+    /// Pairs up a flat list of alternating keys and labels, as it is sent over D-Bus.
+    ///
+    /// A trailing key without a label is left out.
+    ///
+    /// ```
+    /// # use notify_rust::*;
+    /// let actions = Action::from_flat(["default", "Open", "archive", "Archive", "delete"]);
+    /// assert_eq!(actions, [Action::new("default", "Open"), Action::new("archive", "Archive")]);
+    /// ```
+    pub fn from_flat<I, S>(flat: I) -> Vec<Action>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut flat = flat.into_iter();
+        let mut actions = Vec::new();
+        while let (Some(key), Some(label)) = (flat.next(), flat.next()) {
+            actions.push(Action::new(key.as_ref(), label.as_ref()));
+        }
+        actions
+    }
+
+    /// Flattens `actions` into alternating keys and labels, as they are sent over D-Bus.
+    pub fn to_flat(actions: &[Action]) -> Vec<&str> {
+        actions
+            .iter()
+            .flat_map(|action| [action.key.as_str(), action.label.as_str()])
+            .collect()
+    }
+}
+
+impl ActionKind {
+    /// The kind of an action registered under `key`.
+    pub(crate) fn of(key: &str) -> Self {
+        match key {
+            DEFAULT => ActionKind::Default,
+            INLINE_REPLY => ActionKind::InlineReply,
+            _ => ActionKind::Button,
+        }
+    }
+}
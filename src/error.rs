@@ -15,6 +15,13 @@ pub struct Error {
     kind: ErrorKind,
 }
 
+impl Error {
+    /// What went wrong.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
 /// The kind of an error.
 #[derive(Debug)]
 #[non_exhaustive]
@@ -40,6 +47,55 @@ pub enum ErrorKind {
     #[cfg(all(feature = "images", unix, not(target_os = "macos")))]
     Image(ImageError),
 
+    #[cfg(all(feature = "serde", unix, not(target_os = "macos")))]
+    Json(serde_json::Error),
+
+    Io(std::io::Error),
+
+    /// A request of the `http` feature failed, or the server answered with an error status.
+    #[cfg(all(feature = "http", unix, not(target_os = "macos")))]
+    Http(Box<ureq::Error>),
+
+    /// See [`Notification::validate_lengths()`](crate::Notification::validate_lengths), lengths are counted in characters.
+    FieldTooLong {
+        field: &'static str,
+        length: usize,
+        max: usize,
+    },
+
+    /// See [`Notification::build()`](crate::Notification::build), the summary is all most servers show of a notification.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    EmptySummary,
+
+    /// An action key without a label, see [`Notification::build()`](crate::Notification::build).
+    #[cfg(all(unix, not(target_os = "macos")))]
+    UnpairedAction {
+        key: String,
+    },
+
+    /// A sub bus that is no valid D-Bus name, see [`Notification::build()`](crate::Notification::build).
+    #[cfg(all(unix, not(target_os = "macos")))]
+    InvalidBus {
+        sub_bus: String,
+    },
+
+    /// The server doesn't advertise this capability, see [`set_strict_capabilities()`](crate::set_strict_capabilities).
+    #[cfg(all(unix, not(target_os = "macos")))]
+    UnsupportedFeature {
+        feature: &'static str,
+    },
+
+    /// The key of an invoked action didn't parse, see [`NotificationHandle::wait_for_action_typed()`](crate::NotificationHandle::wait_for_action_typed).
+    #[cfg(all(unix, not(target_os = "macos")))]
+    UnknownAction {
+        key: String,
+        message: String,
+    },
+
+    /// The server didn't reply within [`Notification::with_call_timeout()`](crate::Notification::with_call_timeout).
+    #[cfg(all(unix, not(target_os = "macos")))]
+    Timeout(std::time::Duration),
+
     ImplementationMissing,
 }
 
@@ -47,10 +103,10 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.kind {
             #[cfg(all(feature = "dbus", unix, not(target_os = "macos")))]
-            ErrorKind::Dbus(ref e) => write!(f, "{}", e),
+            ErrorKind::Dbus(ref e) => write!(f, "D-Bus error: {}", e),
 
             #[cfg(all(feature = "zbus", unix, not(target_os = "macos")))]
-            ErrorKind::Zbus(ref e) => write!(f, "{}", e),
+            ErrorKind::Zbus(ref e) => write!(f, "D-Bus error: {}", e),
 
             #[cfg(target_os = "macos")]
             ErrorKind::MacNotificationSys(ref e) => write!(f, "{}", e),
@@ -59,7 +115,42 @@ impl fmt::Display for Error {
             ErrorKind::Conversion(ref e) => write!(f, "Conversion Error: {}", e),
             ErrorKind::SpecVersion(ref e) | ErrorKind::Msg(ref e) => write!(f, "{}", e),
             #[cfg(all(feature = "images", unix, not(target_os = "macos")))]
-            ErrorKind::Image(ref e) => write!(f, "{}", e),
+            ErrorKind::Image(ref e) => write!(f, "image error: {}", e),
+            #[cfg(all(feature = "serde", unix, not(target_os = "macos")))]
+            ErrorKind::Json(ref e) => write!(f, "Json Error: {}", e),
+            ErrorKind::Io(ref e) => write!(f, "I/O error: {}", e),
+            #[cfg(all(feature = "http", unix, not(target_os = "macos")))]
+            ErrorKind::Http(ref e) => write!(f, "HTTP error: {}", e),
+            ErrorKind::FieldTooLong { field, length, max } => write!(
+                f,
+                "{} is too long: {} characters, at most {} allowed",
+                field, length, max
+            ),
+            #[cfg(all(unix, not(target_os = "macos")))]
+            ErrorKind::EmptySummary => write!(f, "the summary is empty"),
+            #[cfg(all(unix, not(target_os = "macos")))]
+            ErrorKind::UnpairedAction { ref key } => {
+                write!(f, "the action {:?} has no label", key)
+            }
+            #[cfg(all(unix, not(target_os = "macos")))]
+            ErrorKind::InvalidBus { ref sub_bus } => {
+                write!(f, "{:?} is not a valid bus name", sub_bus)
+            }
+            #[cfg(all(unix, not(target_os = "macos")))]
+            ErrorKind::UnsupportedFeature { feature } => {
+                write!(f, "the notification server does not support {:?}", feature)
+            }
+            #[cfg(all(unix, not(target_os = "macos")))]
+            ErrorKind::UnknownAction {
+                ref key,
+                ref message,
+            } => write!(f, "unknown action {:?}: {}", key, message),
+            #[cfg(all(unix, not(target_os = "macos")))]
+            ErrorKind::Timeout(timeout) => write!(
+                f,
+                "the notification server did not reply within {:?}",
+                timeout
+            ),
             ErrorKind::ImplementationMissing => write!(
                 f,
                 r#"No Dbus implementation available, please compile with either feature ="z" or feature="d""#
@@ -68,7 +159,25 @@ impl fmt::Display for Error {
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self.kind {
+            #[cfg(all(feature = "dbus", unix, not(target_os = "macos")))]
+            ErrorKind::Dbus(ref e) => Some(e),
+            #[cfg(all(feature = "zbus", unix, not(target_os = "macos")))]
+            ErrorKind::Zbus(ref e) => Some(e),
+            ErrorKind::Parse(ref e) => Some(e),
+            #[cfg(all(feature = "images", unix, not(target_os = "macos")))]
+            ErrorKind::Image(ref e) => Some(e),
+            #[cfg(all(feature = "serde", unix, not(target_os = "macos")))]
+            ErrorKind::Json(ref e) => Some(e),
+            ErrorKind::Io(ref e) => Some(e),
+            #[cfg(all(feature = "http", unix, not(target_os = "macos")))]
+            ErrorKind::Http(ref e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 impl From<&str> for Error {
     fn from(e: &str) -> Error {
@@ -114,6 +223,32 @@ impl From<ImageError> for Error {
     }
 }
 
+#[cfg(all(feature = "serde", unix, not(target_os = "macos")))]
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
+        Error {
+            kind: ErrorKind::Json(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Error {
+        Error {
+            kind: ErrorKind::Io(e),
+        }
+    }
+}
+
+#[cfg(all(feature = "http", unix, not(target_os = "macos")))]
+impl From<ureq::Error> for Error {
+    fn from(e: ureq::Error) -> Error {
+        Error {
+            kind: ErrorKind::Http(Box::new(e)),
+        }
+    }
+}
+
 impl From<num::ParseIntError> for Error {
     fn from(e: num::ParseIntError) -> Error {
         Error {
@@ -159,3 +294,33 @@ macro_rules! ensure {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn source_is_the_cause() {
+        let parse = "x".parse::<u32>().unwrap_err();
+        let error = Error::from(parse.clone());
+        assert_eq!(error.to_string(), format!("Parsing Error: {}", parse));
+        assert_eq!(error.source().unwrap().to_string(), parse.to_string());
+
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+        let error = Error::from(io);
+        assert_eq!(error.to_string(), "I/O error: no such file");
+        assert_eq!(error.source().unwrap().to_string(), "no such file");
+
+        assert!(Error::from("just a message").source().is_none());
+    }
+
+    #[cfg(all(feature = "zbus", unix, not(target_os = "macos")))]
+    #[test]
+    fn display_shows_the_cause() {
+        let zbus = zbus::Error::Failure("name has no owner".into());
+        let error = Error::from(zbus);
+        assert_eq!(error.to_string(), "D-Bus error: name has no owner");
+        assert_eq!(error.source().unwrap().to_string(), "name has no owner");
+    }
+}
@@ -37,7 +37,11 @@ pub(crate) enum CustomHintType {
 /// than is actually available.
 ///
 /// you pass these to [`Notification::hint`]
+///
+/// With the `serde` feature hints are (de)serialized under their specified names, e.g. `{"desktop-entry": "firefox"}`.
 #[derive(Eq, PartialEq, Hash, Clone, Debug)]
+#[cfg_attr(all(feature = "serde", unix, not(target_os = "macos")), derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(all(feature = "serde", unix, not(target_os = "macos")), serde(rename_all = "kebab-case"))]
 pub enum Hint {
     /// If true, server may interpret action identifiers as named icons and display those.
     ActionIcons(bool),
@@ -146,7 +150,32 @@ impl Hint {
 }
 
 #[cfg(all(unix, not(target_os = "macos")))]
-impl Hint {}
+impl Hint {
+    /// Whether this is sent as `image-data`.
+    pub(crate) fn is_image_data(&self) -> bool {
+        #[cfg(feature = "images")]
+        if matches!(self, Hint::ImageData(_)) {
+            return true;
+        }
+        false
+    }
+
+    /// Capabilities of which the server has to advertise at least one to make use of this hint.
+    ///
+    /// Empty if the spec doesn't tie the hint to any capability.
+    pub(crate) fn capabilities(&self) -> &'static [&'static str] {
+        match *self {
+            Hint::ActionIcons(_)                            => &["action-icons"],
+            #[cfg(feature = "images")]
+            Hint::ImageData(_)                              => &["icon-static", "icon-multi"],
+            Hint::ImagePath(_)                              => &["icon-static", "icon-multi"],
+            Hint::SoundFile(_)     | Hint::SoundName(_)     |
+            Hint::SuppressSound(_)                          => &["sound"],
+            Hint::Transient(_)                              => &["persistence"],
+            _                                               => &[]
+        }
+    }
+}
 
 #[cfg(all(feature = "zbus", unix, not(target_os = "macos")))]
 #[test]
@@ -166,12 +195,45 @@ fn test_hints_to_map() {
      });
 }
 
+#[cfg(all(feature = "zbus", unix, not(target_os = "macos")))]
+#[test]
+fn test_custom_int_is_an_int() {
+    let n1 = Notification::new()
+        .hint(Hint::CustomInt("x-size".into(), 32))
+        .finalize();
+
+     assert_eq!(hints_to_map(&n1), maplit::hashmap!{
+         "x-size" => zvariant::Value::I32(32)
+     });
+}
+
+#[cfg(all(feature = "zbus", feature = "images", unix, not(target_os = "macos")))]
+#[test]
+fn test_image_frames_are_a_vendor_hint() {
+    let frames = vec![
+        Image::from_rgb(1, 1, vec![0, 0, 0]).unwrap(),
+        Image::from_rgb(1, 1, vec![255, 255, 255]).unwrap(),
+    ];
+    let notification = Notification::new().animated_icon(frames).finalize();
+    let hints = hints_to_map(&notification);
+
+    assert_eq!(hints[constants::IMAGE_FRAMES].value_signature(), "a(iiibiiay)");
+    assert_eq!(hints[image_spec_str(*crate::SPEC_VERSION)].value_signature(), "(iiibiiay)");
+}
+
 #[cfg(all(feature = "zbus", unix, not(target_os = "macos")))]
 pub(crate) fn hints_to_map(notification: &Notification) -> HashMap::<&str, zvariant::Value<'_>> {
-    notification
-        .get_hints()
+    #[allow(unused_mut)]
+    let mut hints: HashMap<_, _> = notification
+        .hints_to_send()
         .map(Into::into)
-        .collect()
+        .collect();
+    #[cfg(feature = "images")]
+    if !notification.icon_frames.is_empty() {
+        let frames = notification.icon_frames.iter().map(Image::to_tuple).collect::<Vec<_>>();
+        hints.insert(constants::IMAGE_FRAMES, zvariant::Value::from(frames));
+    }
+    hints
 }
 
 #[cfg(all(feature = "zbus", unix, not(target_os = "macos")))]
@@ -210,6 +272,56 @@ impl<'a> From<&'a Hint> for (&'a str, zvariant::Value<'a>) {
 }
 
 
+/// Reads a hint as received from D-Bus, the inverse of the conversion above.
+///
+/// Unknown keys with string or integer values become [`Hint::Custom`] and [`Hint::CustomInt`].
+#[cfg(all(feature = "zbus", unix, not(target_os = "macos")))]
+impl TryFrom<(&str, &zvariant::Value<'_>)> for Hint {
+    type Error = crate::error::Error;
+
+    fn try_from((key, value): (&str, &zvariant::Value<'_>)) -> Result<Self, Self::Error> {
+        use self::constants::*;
+        use zvariant::Value;
+
+        let int = match value {
+            Value::U8(int)  => Some(i64::from(*int)),
+            Value::I32(int) => Some(i64::from(*int)),
+            Value::U32(int) => Some(i64::from(*int)),
+            _ => None,
+        };
+
+        Ok(match (key, value, int) {
+            (_,              Value::Value(inner), _   ) => return Hint::try_from((key, &**inner)),
+
+            (ACTION_ICONS,   Value::Bool(value),  _   ) => Hint::ActionIcons(*value),
+            (CATEGORY,       Value::Str(value),   _   ) => Hint::Category(value.as_str().to_owned()),
+            (DESKTOP_ENTRY,  Value::Str(value),   _   ) => Hint::DesktopEntry(value.as_str().to_owned()),
+            (IMAGE_PATH,     Value::Str(value),   _   ) => Hint::ImagePath(value.as_str().to_owned()),
+            (RESIDENT,       Value::Bool(value),  _   ) => Hint::Resident(*value),
+            (SOUND_FILE,     Value::Str(value),   _   ) => Hint::SoundFile(value.as_str().to_owned()),
+            (SOUND_NAME,     Value::Str(value),   _   ) => Hint::SoundName(value.as_str().to_owned()),
+            (SUPPRESS_SOUND, Value::Bool(value),  _   ) => Hint::SuppressSound(*value),
+            (TRANSIENT,      Value::Bool(value),  _   ) => Hint::Transient(*value),
+            (X,              Value::I32(value),   _   ) => Hint::X(*value),
+            (Y,              Value::I32(value),   _   ) => Hint::Y(*value),
+            (URGENCY,        _,              Some(int)) => Hint::Urgency(u64::try_from(int).ok().into()),
+
+            #[cfg(feature = "images")]
+            (crate::image::constants::IMAGE_DATA, Value::Structure(_), _) |
+            (crate::image::constants::IMAGE_DATA_1_1, Value::Structure(_), _) |
+            (crate::image::constants::IMAGE_DATA_1_0, Value::Structure(_), _) => {
+                let image = value.try_clone().and_then(TryInto::try_into).map_err(zbus::Error::from)?;
+                Hint::ImageData(Image::from_tuple(image)?)
+            }
+
+            (_,              Value::Str(value),   _   ) => Hint::Custom(key.to_owned(), value.as_str().to_owned()),
+            (_,              Value::I32(value),   _   ) => Hint::CustomInt(key.to_owned(), *value),
+
+            _ => return Err(crate::error::ErrorKind::Conversion(format!("unsupported hint {:?}: {:?}", key, value)).into()),
+        })
+    }
+}
+
 #[cfg(all(feature = "dbus", unix, not(target_os = "macos")))]
 impl<'a, A: dbus::arg::RefArg> From<(&'a String, &'a A)> for Hint {
     fn from(pair: (&String, &A)) -> Self {
@@ -237,7 +349,9 @@ impl<'a, A: dbus::arg::RefArg> From<(&'a String, &'a A)> for Hint {
             (constants::Y,              _,        Some(y), _          ) => Hint::Y(y as i32),
 
             other => {
-                eprintln!("Invalid Hint {:#?} ", other);
+                if !crate::xdg::quiet::is_quiet() {
+                    eprintln!("Invalid Hint {:#?} ", other);
+                }
                 Hint::Invalid
             }
         }
@@ -13,5 +13,8 @@ pub const X: &str               = "x";
 pub const Y: &str               = "y";
 pub const URGENCY: &str         = "urgency";
 
+/// Not in the specification, frames of `Notification::animated_icon()` for servers with `"icon-multi"`.
+pub const IMAGE_FRAMES: &str    = "x-notify-rust-image-frames";
+
 
 pub const INVALID: &str    = "invalid";
\ No newline at end of file
@@ -76,7 +76,9 @@ impl<'a, A: RefArg> From<(&'a String, &'a A)> for HintMessage {
             (Y,              _,        Some(y), _          ) => Hint::Y(y as i32),
 
             other => {
-                eprintln!("Invalid Hint{:#?} ", other);
+                if !crate::xdg::quiet::is_quiet() {
+                    eprintln!("Invalid Hint{:#?} ", other);
+                }
                 Hint::Invalid
             }
         }.into()
@@ -111,6 +113,13 @@ impl From<HintMessage> for (MessageItem, MessageItem) {
     }
 }
 
+/// The frames of `Notification::animated_icon()`, keyed like a hint.
+#[cfg(all(feature = "dbus", feature = "images", unix, not(target_os = "macos")))]
+pub(crate) fn image_frames(frames: &[Image]) -> (MessageItem, MessageItem) {
+    let frames = frames.iter().cloned().map(|frame| ImageMessage::from(frame).into()).collect();
+    let array = dbus::arg::messageitem::MessageItemArray::new(frames, "a(iiibiiay)".into()).unwrap();
+    (MessageItem::Str(IMAGE_FRAMES.to_owned()), MessageItem::Variant(Box::new(MessageItem::Array(array))))
+}
 
 // TODO: deprecated, Prefer the DBus Arg and RefArg APIs
 #[cfg(feature = "dbus")]
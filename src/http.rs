@@ -0,0 +1,82 @@
+//! The HTTP client behind the `http` feature,
+//! see [`Notification::image_url()`](crate::Notification::image_url) and [`Notification::show_webhook()`](crate::Notification::show_webhook).
+//!
+//! [`ureq`] blocks, so requests run on the thread pool of [`blocking`] to keep them off the caller's executor.
+
+use crate::error::*;
+
+/// Downloads `url`, reading no more than `max_bytes` of the response.
+///
+/// Fails on anything but a successful status.
+#[cfg(feature = "images")]
+pub(crate) async fn get(url: &str, max_bytes: usize) -> Result<Vec<u8>> {
+    use std::io::Read;
+
+    let url = url.to_owned();
+    blocking::unblock(move || {
+        let response = ureq::get(&url).call()?;
+        let mut bytes = Vec::new();
+        response
+            .into_reader()
+            .take(max_bytes as u64)
+            .read_to_end(&mut bytes)?;
+        Ok(bytes)
+    })
+    .await
+}
+
+/// Posts `json` to `url`.
+///
+/// Fails on anything but a successful status.
+#[cfg(feature = "serde")]
+pub(crate) async fn post_json(url: &str, json: String) -> Result<()> {
+    let url = url.to_owned();
+    blocking::unblock(move || {
+        ureq::post(&url)
+            .set("Content-Type", "application/json")
+            .send_string(&json)?;
+        Ok(())
+    })
+    .await
+}
+
+/// Answers one request on a local port with `status` and `body`.
+///
+/// Returns the url to request, and a thread that ends with the body of the request once it was answered.
+#[cfg(test)]
+pub(crate) fn serve_once(
+    status: &'static str,
+    body: Vec<u8>,
+) -> (String, std::thread::JoinHandle<Vec<u8>>) {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = BufReader::new(stream.try_clone().unwrap());
+        let mut content_length = 0;
+        let mut line = String::new();
+        while request.read_line(&mut line).unwrap() > 2 {
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            line.clear();
+        }
+        let mut request_body = vec![0; content_length];
+        request.read_exact(&mut request_body).unwrap();
+
+        let _ = write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            status,
+            body.len()
+        );
+        // the client hangs up early if the body is too large
+        let _ = stream.write_all(&body);
+        request_body
+    });
+    (url, server)
+}
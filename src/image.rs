@@ -10,14 +10,29 @@ use std::path::Path;
 
 use crate::miniver::Version;
 
-mod constants {
+pub(crate) mod constants {
     pub const IMAGE_DATA: &str = "image-data";
     pub const IMAGE_DATA_1_1: &str = "image_data";
     pub const IMAGE_DATA_1_0: &str = "icon_data";
 }
 
+/// The most bytes [`Image::from_encoded()`] decodes.
+pub(crate) const MAX_ENCODED_LEN: usize = 8 * 1024 * 1024;
+
+/// Width and height [`Image::from_encoded()`] scales down to.
+///
+/// Servers show images a lot smaller than this, and every pixel is sent as 3 or 4 bytes over D-Bus.
+pub(crate) const MAX_SIDE: u32 = 512;
+
 /// Image representation for images. Send via `Notification::image_data()`
+///
+/// With the `serde` feature the pixel data is (de)serialized as base64.
 #[derive(PartialEq, Eq, Debug, Clone, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "SerializedImage", try_from = "SerializedImage")
+)]
 pub struct Image {
     width: i32,
     height: i32,
@@ -42,7 +57,14 @@ impl Image {
             return Err(ImageError::TooBig);
         }
 
-        if data.len() != (width * height * channels) as usize {
+        let expected_len = usize::try_from(width)
+            .ok()
+            .zip(usize::try_from(height).ok())
+            .zip(usize::try_from(channels).ok())
+            .and_then(|((width, height), channels)| {
+                width.checked_mul(height)?.checked_mul(channels)
+            });
+        if expected_len != Some(data.len()) {
             Err(ImageError::WrongDataSize)
         } else {
             Ok(Self {
@@ -77,6 +99,26 @@ impl Image {
         Image::try_from(dyn_img)
     }
 
+    /// Decodes an encoded image (PNG, JPEG, ...) as downloaded, see `Notification::image_url()` of the `http` feature.
+    ///
+    /// Rejects more than 8 MiB
+    /// and scales images down to fit into 512x512 pixels, keeping the aspect ratio.
+    pub fn from_encoded(bytes: &[u8]) -> Result<Self, ImageError> {
+        if bytes.len() > MAX_ENCODED_LEN {
+            return Err(ImageError::TooBig);
+        }
+        Image::scaled_down(&image::load_from_memory(bytes)?)
+    }
+
+    /// Converts `dyn_img`, scaled down to fit into 512x512 pixels if it is larger, keeping the aspect ratio.
+    pub(crate) fn scaled_down(dyn_img: &DynamicImage) -> Result<Self, ImageError> {
+        if dyn_img.width() > MAX_SIDE || dyn_img.height() > MAX_SIDE {
+            Image::try_from(dyn_img.thumbnail(MAX_SIDE, MAX_SIDE))
+        } else {
+            Image::try_from(dyn_img)
+        }
+    }
+
     #[cfg(all(feature = "images", feature = "zbus"))]
     pub(crate) fn to_tuple(&self) -> (i32, i32, i32, bool, i32, i32, Vec<u8>) {
         (
@@ -89,8 +131,29 @@ impl Image {
             self.data.clone(),
         )
     }
+
+    /// Inverse of [`to_tuple()`](Self::to_tuple), for images received over D-Bus.
+    #[cfg(all(feature = "images", feature = "zbus"))]
+    pub(crate) fn from_tuple(
+        (width, height, rowstride, alpha, bits_per_sample, channels, data): (
+            i32,
+            i32,
+            i32,
+            bool,
+            i32,
+            i32,
+            Vec<u8>,
+        ),
+    ) -> Result<Self, ImageError> {
+        // padded rows are not supported
+        if width.checked_mul(channels) != Some(rowstride) {
+            return Err(ImageError::WrongDataSize);
+        }
+        Self::from_raw_data(width, height, data, channels, bits_per_sample, alpha)
+    }
 }
 
+/// Images of any other color type are converted to 8 bit RGB, or RGBA if they have an alpha channel.
 impl TryFrom<DynamicImage> for Image {
     type Error = ImageError;
 
@@ -98,18 +161,37 @@ impl TryFrom<DynamicImage> for Image {
         match dyn_img {
             DynamicImage::ImageRgb8(img) => Self::try_from(img),
             DynamicImage::ImageRgba8(img) => Self::try_from(img),
-            _ => Err(ImageError::CantConvert),
+            other => Self::try_from(&other),
+        }
+    }
+}
+
+/// Converts to 8 bit RGB, or RGBA if the image has an alpha channel.
+impl TryFrom<&DynamicImage> for Image {
+    type Error = ImageError;
+
+    fn try_from(dyn_img: &DynamicImage) -> Result<Self, Self::Error> {
+        if dyn_img.color().has_alpha() {
+            Self::try_from(dyn_img.to_rgba8())
+        } else {
+            Self::try_from(dyn_img.to_rgb8())
         }
     }
 }
 
+fn dimensions(width: u32, height: u32) -> Result<(i32, i32), ImageError> {
+    let width = i32::try_from(width).map_err(|_| ImageError::TooBig)?;
+    let height = i32::try_from(height).map_err(|_| ImageError::TooBig)?;
+    Ok((width, height))
+}
+
 impl TryFrom<image::RgbImage> for Image {
     type Error = ImageError;
 
     fn try_from(img: image::RgbImage) -> Result<Self, Self::Error> {
-        let (width, height) = img.dimensions();
+        let (width, height) = dimensions(img.width(), img.height())?;
         let image_data = img.into_raw();
-        Image::from_rgb(width as i32, height as i32, image_data)
+        Image::from_rgb(width, height, image_data)
     }
 }
 
@@ -117,16 +199,65 @@ impl TryFrom<image::RgbaImage> for Image {
     type Error = ImageError;
 
     fn try_from(img: image::RgbaImage) -> Result<Self, Self::Error> {
-        let (width, height) = img.dimensions();
+        let (width, height) = dimensions(img.width(), img.height())?;
         let image_data = img.into_raw();
-        Image::from_rgba(width as i32, height as i32, image_data)
+        Image::from_rgba(width, height, image_data)
+    }
+}
+
+/// Shape of an [`Image`] when (de)serialized.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerializedImage {
+    width: i32,
+    height: i32,
+    alpha: bool,
+    bits_per_sample: i32,
+    channels: i32,
+    data: String,
+}
+
+#[cfg(feature = "serde")]
+impl From<Image> for SerializedImage {
+    fn from(img: Image) -> Self {
+        use base64::Engine;
+        SerializedImage {
+            width: img.width,
+            height: img.height,
+            alpha: img.alpha,
+            bits_per_sample: img.bits_per_sample,
+            channels: img.channels,
+            data: base64::engine::general_purpose::STANDARD.encode(img.data),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SerializedImage> for Image {
+    type Error = String;
+
+    fn try_from(img: SerializedImage) -> Result<Self, Self::Error> {
+        use base64::Engine;
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(img.data)
+            .map_err(|e| e.to_string())?;
+        Image::from_raw_data(
+            img.width,
+            img.height,
+            data,
+            img.channels,
+            img.bits_per_sample,
+            img.alpha,
+        )
+        .map_err(|e| e.to_string())
     }
 }
 
 /// Errors that can occur when creating an Image
 #[derive(Debug)]
 pub enum ImageError {
-    /// The given image is too big. DBus only has 32 bits for width / height
+    /// The given image is too big. D-Bus only has 32 bits for width / height,
+    /// and [`Image::from_encoded()`] takes at most 8 MiB.
     TooBig,
     /// The given bytes don't match the width, height and channel count
     WrongDataSize,
@@ -150,16 +281,16 @@ impl fmt::Display for ImageError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use ImageError::*;
         match self {
-            TooBig => writeln!(
+            TooBig => write!(
                 f,
-                "The given image is too big. DBus only has 32 bits for width / height"
+                "The given image is too big. DBus only has 32 bits for width / height, encoded images may have at most 8 MiB"
             ),
-            WrongDataSize => writeln!(
+            WrongDataSize => write!(
                 f,
                 "The given bytes don't match the width, height and channel count"
             ),
-            CantOpen(e) => writeln!(f, "Can't open given path {}", e),
-            CantConvert => writeln!(f, "Can't convert from given input"),
+            CantOpen(e) => write!(f, "Can't open given path {}", e),
+            CantConvert => write!(f, "Can't convert from given input"),
         }
     }
 }
@@ -227,3 +358,67 @@ impl From<ImageMessage> for MessageItem {
         ])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgb_rowstride() {
+        let img = Image::try_from(DynamicImage::new_rgb8(3, 2)).unwrap();
+        assert_eq!((img.width, img.height, img.channels), (3, 2, 3));
+        assert_eq!(img.rowstride, 9);
+        assert!(!img.alpha);
+        assert_eq!(img.data.len(), 18);
+    }
+
+    #[test]
+    fn other_color_types_are_converted() {
+        let gray = Image::try_from(&DynamicImage::new_luma16(5, 1)).unwrap();
+        assert_eq!(
+            (gray.channels, gray.bits_per_sample, gray.rowstride),
+            (3, 8, 15)
+        );
+        assert!(!gray.alpha);
+
+        let gray_alpha = Image::try_from(DynamicImage::new_luma_a8(5, 1)).unwrap();
+        assert_eq!((gray_alpha.channels, gray_alpha.rowstride), (4, 20));
+        assert!(gray_alpha.alpha);
+    }
+
+    #[test]
+    fn encoded_images_are_scaled_down() {
+        let encode = |img: DynamicImage| {
+            let mut bytes = std::io::Cursor::new(Vec::new());
+            img.write_to(&mut bytes, image::ImageFormat::Png).unwrap();
+            bytes.into_inner()
+        };
+
+        let small = Image::from_encoded(&encode(DynamicImage::new_rgba8(16, 8))).unwrap();
+        assert_eq!((small.width, small.height, small.channels), (16, 8, 4));
+
+        let wide = Image::from_encoded(&encode(DynamicImage::new_rgb8(2048, 256))).unwrap();
+        assert_eq!((wide.width, wide.height), (512, 64));
+
+        assert!(matches!(
+            Image::from_encoded(b"<html>not found</html>"),
+            Err(ImageError::CantOpen(_))
+        ));
+        assert!(matches!(
+            Image::from_encoded(&vec![0; MAX_ENCODED_LEN + 1]),
+            Err(ImageError::TooBig)
+        ));
+    }
+
+    #[test]
+    fn wrong_data_size() {
+        assert!(matches!(
+            Image::from_rgb(2, 2, vec![0; 11]),
+            Err(ImageError::WrongDataSize)
+        ));
+        assert!(matches!(
+            Image::from_rgba(-1, 2, vec![]),
+            Err(ImageError::WrongDataSize)
+        ));
+    }
+}
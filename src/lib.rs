@@ -81,38 +81,107 @@
 //! ❌ = will not compile
 //!
 //! ## `Notification`
-//! | method              | XDG   | macOS | windows |
-//! |---------------------|-------|-------|---------|
-//! |  `fn appname(...)`  |  ✔︎    |       |        |
-//! |  `fn summary(...)`  |  ✔︎    | ✔︎     |  ✔︎    |
-//! |  `fn subtitle(...)` |       | ✔︎     |  ✔︎    |
-//! |  `fn body(...)`     |  ✔︎    | ✔︎     |  ✔︎    |
-//! |  `fn icon(...)`     |  ✔︎    |       |        |
-//! |  `fn auto_icon(...)`|  ✔︎    |       |        |
-//! |  `fn hint(...)`     |  ✔︎    | ❌    | ❌    |
-//! |  `fn timeout(...)`  |  ✔︎    |       |  ✔︎    |
-//! |  `fn urgency(...)`  |  ✔︎    | ❌    | ❌    |
-//! |  `fn action(...)`   |  ✔︎    |       |        |
-//! |  `fn id(...)`       |  ✔︎    |       |        |
-//! |  `fn finalize(...)` |  ✔︎    | ✔︎     |  ✔︎    |
-//! |  `fn show(...)`     |  ✔︎    | ✔︎     |  ✔︎    |
+//! | method                            | XDG   | macOS | windows |
+//! |-----------------------------------|-------|-------|---------|
+//! |  `fn appname(...)`                |  ✔︎    |       |        |
+//! |  `fn desktop_entry(...)`          |  ✔︎    |       |  ✔︎    |
+//! |  `fn identity(...)`               |  ✔︎    |       |  ✔︎    |
+//! |  `fn summary(...)`                |  ✔︎    | ✔︎     |  ✔︎    |
+//! |  `fn subtitle(...)`               |       | ✔︎     |  ✔︎    |
+//! |  `fn body(...)`                   |  ✔︎    | ✔︎     |  ✔︎    |
+//! |  `fn icon(...)`                   |  ✔︎    |       |        |
+//! |  `fn app_icon(...)`               |  ✔︎    |       |        |
+//! |  `fn auto_icon(...)`              |  ✔︎    |       |        |
+//! |  `fn icon_from_desktop_entry(...)` |  ✔︎    | ❌    | ❌    |
+//! |  `fn hero_image(...)`             |  ✔︎    | ❌    | ❌    |
+//! |  `fn effective_icon(...)`         |  ✔︎    | ❌    | ❌    |
+//! |  `fn error(...)`                  |  ✔︎    | ✔︎     |  ✔︎    |
+//! |  `fn warning(...)`                |  ✔︎    | ✔︎     |  ✔︎    |
+//! |  `fn info(...)`                   |  ✔︎    | ✔︎     |  ✔︎    |
+//! |  `fn hint(...)`                   |  ✔︎    | ✔︎     |  ✔︎    |
+//! |  `fn with_hints(...)`             |  ✔︎    | ✔︎     |  ✔︎    |
+//! |  `fn copy_hints_from(...)`        |  ✔︎    | ❌    | ❌    |
+//! |  `fn unsupported_hints(...)`      |  ✔︎    | ❌    | ❌    |
+//! |  `fn negotiate(...)`              |  ✔︎    | ❌    | ❌    |
+//! |  `fn predicted_presentation(...)` |  ✔︎    | ❌    | ❌    |
+//! |  `fn sound_with_fallback(...)`    |  ✔︎    | ❌    | ❌    |
+//! |  `fn timeout(...)`                |  ✔︎    |       |  ✔︎    |
+//! |  `fn timeout_ms(...)`             |  ✔︎    |       |  ✔︎    |
+//! |  `fn urgency(...)`                |  ✔︎    | ❌    | ❌    |
+//! |  `fn bypass_dnd(...)`             |  ✔︎    | ❌    | ❌    |
+//! |  `fn on_monitor(...)`             |  ✔︎    | ❌    | ❌    |
+//! |  `fn stack_tag(...)`              |  ✔︎    | ❌    | ❌    |
+//! |  `fn action(...)`                 |  ✔︎    |       |        |
+//! |  `fn action_kind(...)`            |  ✔︎    |       |        |
+//! |  `fn no_default_action(...)`      |  ✔︎    | ❌    | ❌    |
+//! |  `fn inline_reply(...)`           |  ✔︎    |       |        |
+//! |  `fn inline_reply_with_default(...)` |  ✔︎    |       |        |
+//! |  `fn id(...)`                     |  ✔︎    |       |        |
+//! |  `fn persist_id_to(...)`          |  ✔︎    | ❌    | ❌    |
+//! |  `fn replace_from_persisted(...)` |  ✔︎    | ❌    | ❌    |
+//! |  `fn merge_by_summary(...)`       |  ✔︎    | ❌    | ❌    |
+//! |  `fn merge_by_summary_prefix(...)` |  ✔︎    | ❌    | ❌    |
+//! |  `fn validate_lengths(...)`       |  ✔︎    | ❌    | ❌    |
+//! |  `fn with_call_timeout(...)`      |  ✔︎    | ❌    | ❌    |
+//! |  `fn with_action_fallback(...)`   |  ✔︎    | ❌    | ❌    |
+//! |  `fn with_timestamp(...)`         |  ✔︎    | ❌    | ❌    |
+//! |  `fn with_timestamp_format(...)`  |  ✔︎    | ❌    | ❌    |
+//! |  `fn finalize(...)`               |  ✔︎    | ✔︎     |  ✔︎    |
+//! |  `fn build(...)`                  |  ✔︎    | ❌    | ❌    |
+//! |  `fn from_notify_args(...)`       |  ✔︎    | ❌    | ❌    |
+//! |  `fn show(...)`                   |  ✔︎    | ✔︎     |  ✔︎    |
+//! |  `fn show_and_wait(...)`          |  ✔︎    | ❌    | ❌    |
+//! |  `fn show_scoped(...)`            |  ✔︎    | ❌    | ❌    |
+//! |  `fn show_light(...)`             |  ✔︎    | ❌    | ❌    |
+//! |  `fn show_reconnecting(...)`      |  ✔︎    | ❌    | ❌    |
+//! |  `fn show_after(...)`             |  ✔︎    | ❌    | ❌    |
+//! |  `fn show_and_wait_for_action_typed(...)` |  ✔︎    | ❌    | ❌    |
+//! |  `fn show_webhook(...)`           |  ✔︎    | ❌    | ❌    |
+//! |  `fn send_id(...)`                |  ✔︎    | ❌    | ❌    |
 //!
 //! ## `NotificationHandle`
 //!
-//! | method                   | XDG | macOS | windows |
-//! |--------------------------|-----|-------|---------|
-//! | `fn wait_for_action(...)`|  ✔︎  |  ❌  |   ❌   |
-//! | `fn close(...)`          |  ✔︎  |  ❌  |   ❌   |
-//! | `fn on_close(...)`       |  ✔︎  |  ❌  |   ❌   |
-//! | `fn update(...)`         |  ✔︎  |  ❌  |   ❌   |
-//! | `fn id(...)`             |  ✔︎  |  ❌  |   ❌   |
+//! | method                           | XDG | macOS | windows |
+//! |----------------------------------|-----|-------|---------|
+//! | `fn wait_for_action(...)`        |  ✔︎  |  ❌  |   ❌   |
+//! | `fn wait_for_action_ref(...)`    |  ✔︎  |  ❌  |   ❌   |
+//! | `fn wait_for_action_typed(...)`  |  ✔︎  |  ❌  |   ❌   |
+//! | `fn close(...)`                  |  ✔︎  |  ❌  |   ❌   |
+//! | `fn on_close(...)`               |  ✔︎  |  ❌  |   ❌   |
+//! | `fn wait_for_close_timeout(...)` |  ✔︎  |  ❌  |   ❌   |
+//! | `fn update(...)`                 |  ✔︎  |  ❌  |   ❌   |
+//! | `fn modify(...)`                 |  ✔︎  |  ❌  |   ❌   |
+//! | `fn update_icon(...)`            |  ✔︎  |  ❌  |   ❌   |
+//! | `fn update_actions(...)`         |  ✔︎  |  ❌  |   ❌   |
+//! | `fn complete(...)`               |  ✔︎  |  ❌  |   ❌   |
+//! | `fn id(...)`                     |  ✔︎  |  ❌  |   ❌   |
+//! | `fn last_send_latency(...)`      |  ✔︎  |  ❌  |   ❌   |
+//! | `fn with_injected_action(...)`   |  ✔︎  |  ❌  |   ❌   |
 //!
 //! ## Functions
 //!
 //! |                                            | XDG | macOS | windows |
 //! |--------------------------------------------|-----|-------|---------|
+//! | `fn actions_supported(...)`                | ✔︎   |   ❌ |  ❌    |
+//! | `fn close_by_tag(...)`                     | ✔︎   |   ❌ |  ❌    |
+//! | `fn close_notification(...)`               | ✔︎   |   ❌ |  ❌    |
 //! | `fn get_capabilities(...)`                 | ✔︎   |   ❌ |  ❌    |
 //! | `fn get_server_information(...)`           | ✔︎   |   ❌ |  ❌    |
+//! | `fn introspect_server(...)`                | ✔︎   |   ❌ |  ❌    |
+//! | `fn introspect_server_methods(...)`        | ✔︎   |   ❌ |  ❌    |
+//! | `fn get_server_limits(...)`                | ✔︎   |   ❌ |  ❌    |
+//! | `fn get_icon_support(...)`                 | ✔︎   |   ❌ |  ❌    |
+//! | `fn watch_server(...)`                     | ✔︎   |   ❌ |  ❌    |
+//! | `fn signal_stream(...)`                    | ✔︎   |   ❌ |  ❌    |
+//! | `fn reset_connection(...)`                 | ✔︎   |   ❌ |  ❌    |
+//! | `fn select_action(...)`                    | ✔︎   |   ❌ |  ❌    |
+//! | `fn show_many_ids(...)`                    | ✔︎   |   ❌ |  ❌    |
+//! | `fn set_send_observer(...)`                | ✔︎   |   ❌ |  ❌    |
+//! | `fn set_panic_hook(...)`                   | ✔︎   |   ❌ |  ❌    |
+//! | `fn record_to(...)`                        | ✔︎   |   ❌ |  ❌    |
+//! | `fn replay(...)`                           | ✔︎   |   ❌ |  ❌    |
+//! | `fn set_quiet(...)`                        | ✔︎   |   ❌ |  ❌    |
+//! | `fn set_strict_capabilities(...)`          | ✔︎   |   ❌ |  ❌    |
 //! | `fn set_application(...)`                  | ❌  |   ✔︎  |  ❌    |
 //! | `fn get_bundle_identifier_or_default(...)` | ❌  |   ✔︎  |  ❌    |
 //!
@@ -160,10 +229,12 @@ extern crate winrt_notification;
 #[cfg(all(feature = "images", unix, not(target_os = "macos")))]
 extern crate lazy_static;
 
+mod action;
 pub mod error;
 mod hints;
 mod miniver;
 mod notification;
+mod replace_id;
 mod timeout;
 pub(crate) mod urgency;
 
@@ -179,6 +250,14 @@ mod xdg;
 #[cfg(all(feature = "images", unix, not(target_os = "macos")))]
 mod image;
 
+#[cfg(all(
+    feature = "http",
+    any(feature = "images", feature = "serde"),
+    unix,
+    not(target_os = "macos")
+))]
+mod http;
+
 #[cfg(all(feature = "server", feature = "dbus", unix, not(target_os = "macos")))]
 pub mod server;
 
@@ -194,14 +273,39 @@ pub use macos::NotificationHandle;
     not(target_os = "macos")
 ))]
 pub use crate::xdg::{
-    dbus_stack, get_capabilities, get_server_information, handle_action, ActionResponse,
-    CloseHandler, CloseReason, DbusStack, NotificationHandle,
+    actions_supported, close_by_tag, close_notification, close_notification_at_bus, dbus_stack,
+    get_capabilities, get_icon_support, get_server_information, get_server_limits, handle_action,
+    introspect_server, introspect_server_methods, select_action, set_panic_hook, set_quiet,
+    set_send_observer, set_strict_capabilities, show_many_ids, ActionResponse, BoxedActionHandler,
+    CloseHandler, CloseReason, DbusStack, IconSource, IconSupport, LightHandle,
+    NotificationAggregator, NotificationHandle, OwnedActionResponse, Presentation,
+    ReconnectingHandle, ScheduledNotification, ScopedNotification, ServerInformation, ServerLimits,
+    TypedActionResponse,
 };
 
+#[cfg(all(feature = "async", feature = "zbus", unix, not(target_os = "macos")))]
+pub use crate::xdg::{
+    close_notification_async, signal_stream, watch_server, watch_server_at_bus, ServerEvent,
+};
+
+#[cfg(all(feature = "zbus", unix, not(target_os = "macos")))]
+pub use crate::xdg::{reset_connection, NotificationSignal};
+
+#[cfg(all(
+    feature = "serde",
+    any(feature = "dbus", feature = "zbus"),
+    unix,
+    not(target_os = "macos")
+))]
+pub use crate::xdg::{record_to, replay};
+
 #[cfg(all(feature = "server", unix, not(target_os = "macos")))]
 pub use crate::xdg::stop_server;
 
-pub use crate::hints::Hint;
+pub use crate::{
+    action::{Action, ActionKind},
+    hints::Hint,
+};
 
 #[cfg(all(feature = "images", unix, not(target_os = "macos")))]
 pub use crate::image::{Image, ImageError};
@@ -212,7 +316,7 @@ pub use crate::image::{Image, ImageError};
 )]
 pub use crate::urgency::Urgency;
 
-pub use crate::{notification::Notification, timeout::Timeout};
+pub use crate::{notification::Notification, replace_id::ReplaceId, timeout::Timeout};
 
 #[cfg(all(feature = "images", unix, not(target_os = "macos")))]
 lazy_static! {
@@ -223,6 +327,11 @@ lazy_static! {
         .unwrap_or_else(|_| miniver::Version::new(1,1));
 }
 /// Return value of `get_server_information()`.
+#[cfg(not(all(
+    any(feature = "dbus", feature = "zbus"),
+    unix,
+    not(target_os = "macos")
+)))]
 #[derive(Debug)]
 pub struct ServerInformation {
     /// The product name of the server.
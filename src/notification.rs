@@ -5,6 +5,9 @@ use crate::{
     xdg,
 };
 
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::{hints::Hint, urgency::Urgency};
+
 #[cfg(all(unix, not(target_os = "macos"), feature = "images"))]
 use crate::image::Image;
 
@@ -13,10 +16,20 @@ use crate::macos;
 #[cfg(target_os = "windows")]
 use crate::windows;
 
-use crate::{error::*, timeout::Timeout};
+use crate::{
+    action::{self, Action, ActionKind},
+    error::*,
+    replace_id::ReplaceId,
+    timeout::Timeout,
+};
 
 #[cfg(all(unix, not(target_os = "macos")))]
-use std::collections::{HashMap, HashSet};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 // Returns the name of the current executable, used as a default for `Notification.appname`.
 fn exe_name() -> String {
@@ -29,6 +42,47 @@ fn exe_name() -> String {
         .to_owned()
 }
 
+/// Removes control characters, the body may keep its line breaks and tabs.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn strip_control_chars(text: &str, multiline: bool) -> Cow<'_, str> {
+    let is_stripped = |c: char| c.is_control() && !(multiline && (c == '\n' || c == '\t'));
+    if text.chars().any(is_stripped) {
+        Cow::Owned(text.chars().filter(|&c| !is_stripped(c)).collect())
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn read_persisted_id(path: &Path) -> Option<u32> {
+    let id = std::fs::read_to_string(path).ok()?.trim().parse().ok()?;
+    // 0 never is a valid id
+    Some(id).filter(|&id| id != 0)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn write_persisted_id(path: &Path, id: u32) -> std::io::Result<()> {
+    std::fs::write(path, format!("{}\n", id))
+}
+
+/// Where the default text of `inline_reply_with_default()` is kept.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn reply_default_key() -> (String, CustomHintType) {
+    (
+        action::REPLY_DEFAULT_HINT.to_owned(),
+        CustomHintType::String,
+    )
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn check_length(field: &'static str, text: &str, max: usize) -> Result<()> {
+    let length = text.chars().count();
+    if length > max {
+        return Err(ErrorKind::FieldTooLong { field, length, max }.into());
+    }
+    Ok(())
+}
+
 /// Desktop notification.
 ///
 /// A desktop notification is configured via builder pattern, before it is launched with `show()`.
@@ -74,7 +128,7 @@ pub struct Notification {
     pub(crate) hints_unique: HashMap<(String, CustomHintType), Hint>,
 
     /// See `Notification::actions()` and `Notification::action()`
-    pub actions: Vec<String>,
+    pub actions: Vec<Action>,
 
     #[cfg(target_os = "macos")]
     pub(crate) sound_name: Option<String>,
@@ -88,14 +142,71 @@ pub struct Notification {
     #[cfg(target_os = "windows")]
     pub(crate) app_id: Option<String>,
 
+    /// Used as AUMID if there is no `app_id`, see `hint()`
+    #[cfg(target_os = "windows")]
+    pub(crate) desktop_entry: Option<String>,
+
     #[cfg(all(unix, not(target_os = "macos")))]
     pub(crate) bus: xdg::NotificationBus,
 
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) merge_window: Option<Duration>,
+
+    /// Merged by this prefix of the summary instead of the whole summary, see `merge_by_summary_prefix()`
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) merge_prefix: Option<String>,
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) sound_fallback: bool,
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) persist_path: Option<PathBuf>,
+
+    /// Maximum length of summary and body, see `validate_lengths()`
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) length_limits: Option<(usize, usize)>,
+
+    /// How long to wait for the server to reply, see `with_call_timeout()`
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) call_timeout: Option<Duration>,
+
+    /// Appended to the body if the server can't show actions, see `with_action_fallback()`
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) action_fallback: Option<String>,
+
+    /// The `"default"` action is never sent, see `no_default_action()`
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) no_default_action: bool,
+
+    /// The current time is put in front of the body in this format when sending, see `with_timestamp()`
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) timestamp_format: Option<String>,
+
+    /// The body without the timestamp, once `prepare()` put it in front
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) unstamped_body: Option<String>,
+
+    /// A trailing key `actions()` got without a label, reported by `build()` and `show()`
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) unpaired_action: Option<String>,
+
+    /// A sub bus `at_bus()` got that is no valid bus name, reported by `build()` and `show()`
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) invalid_bus: Option<String>,
+
+    /// Frames of an animated image, see `animated_icon()`
+    #[cfg(all(feature = "images", unix, not(target_os = "macos")))]
+    pub(crate) icon_frames: Vec<Image>,
+
+    /// `image_url()` fails instead of leaving out the image, see `with_image_url_required()`
+    #[cfg(all(feature = "http", feature = "images", unix, not(target_os = "macos")))]
+    pub(crate) image_url_required: bool,
+
     /// Lifetime of the Notification in ms. Often not respected by server, sorry.
     pub timeout: Timeout, // both gnome and galago want allow for -1
 
     /// Only to be used on the receive end. Use Notification hand for updating.
-    pub(crate) id: Option<u32>,
+    pub(crate) id: ReplaceId,
 }
 
 impl Notification {
@@ -108,22 +219,130 @@ impl Notification {
         Notification::default()
     }
 
+    /// Constructs a Notification about an error.
+    ///
+    /// Sets the themed icon `dialog-error`, [`Urgency::Critical`],
+    /// which many servers don't let expire until it is dismissed, and the category `im.error`.
+    /// Everything can be overridden like on any other notification,
+    /// e.g. with a category that fits better such as `network.error`.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// Notification::error("Sync failed", "The server could not be reached")
+    ///     .hint(Hint::Category("network.error".into()))
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    pub fn error(summary: &str, body: &str) -> Notification {
+        Notification::preset(summary, body, "dialog-error", Urgency::Critical, "im.error")
+    }
+
+    /// Constructs a Notification with a warning.
+    ///
+    /// Sets the themed icon `dialog-warning`, [`Urgency::Normal`] and the category `x-notify-rust.warning`,
+    /// see [`error()`](Self::error).
+    /// The specification has no category for warnings, this is one in its form for vendor categories.
+    pub fn warning(summary: &str, body: &str) -> Notification {
+        Notification::preset(
+            summary,
+            body,
+            "dialog-warning",
+            Urgency::Normal,
+            "x-notify-rust.warning",
+        )
+    }
+
+    /// Constructs a Notification that is merely informative.
+    ///
+    /// Sets the themed icon `dialog-information`, [`Urgency::Low`] and the category `x-notify-rust.info`,
+    /// see [`warning()`](Self::warning).
+    pub fn info(summary: &str, body: &str) -> Notification {
+        Notification::preset(
+            summary,
+            body,
+            "dialog-information",
+            Urgency::Low,
+            "x-notify-rust.info",
+        )
+    }
+
+    fn preset(
+        summary: &str,
+        body: &str,
+        icon: &str,
+        urgency: Urgency,
+        category: &str,
+    ) -> Notification {
+        Notification::new()
+            .summary(summary)
+            .body(body)
+            .icon(icon)
+            .hint(Hint::Urgency(urgency))
+            .hint(Hint::Category(category.into()))
+            .finalize()
+    }
+
     /// This is for testing purposes only and will not work with actual implementations.
     #[cfg(all(unix, not(target_os = "macos")))]
     #[doc(hidden)]
     #[deprecated(note = "this is a test only feature")]
     pub fn at_bus(sub_bus: &str) -> Notification {
-        let bus = xdg::NotificationBus::custom(sub_bus)
-            .ok_or("invalid subpath")
-            .unwrap();
-        Notification {
-            bus,
+        match xdg::NotificationBus::custom(sub_bus) {
+            Some(bus) => Notification {
+                bus,
+                ..Notification::default()
+            },
+            None => Notification {
+                invalid_bus: Some(sub_bus.to_owned()),
+                ..Notification::default()
+            },
+        }
+    }
+
+    /// Reconstructs a notification from the arguments of a `Notify` call.
+    ///
+    /// This is the inverse of what [`show()`](Self::show) sends,
+    /// for notification servers and for tools that proxy or replay notifications.
+    /// A `replaces_id` of `0` stands for a new notification.
+    /// Hints that can't be represented as a [`Hint`] are left out.
+    ///
+    /// (xdg only, zbus only)
+    #[cfg(all(feature = "zbus", unix, not(target_os = "macos")))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_notify_args<K: AsRef<str>>(
+        appname: &str,
+        replaces_id: u32,
+        icon: &str,
+        summary: &str,
+        body: &str,
+        actions: Vec<String>,
+        hints: &HashMap<K, zbus::zvariant::OwnedValue>,
+        timeout: i32,
+    ) -> Notification {
+        let mut notification = Notification {
+            appname: appname.to_owned(),
+            icon: icon.to_owned(),
+            summary: summary.to_owned(),
+            body: body.to_owned(),
+            actions: Action::from_flat(actions),
+            timeout: Timeout::from(timeout),
+            id: ReplaceId::existing(replaces_id),
             ..Notification::default()
+        };
+        for (key, value) in hints {
+            if let Ok(hint) = Hint::try_from((key.as_ref(), &**value)) {
+                notification.hint(hint);
+            }
         }
+        notification
     }
 
     /// Overwrite the appname field used for Notification.
     ///
+    /// This is the `app_name` the server may display as the sender, it defaults to the name of the binary.
+    /// Servers like GNOME group notifications and pick their icon by [`desktop_entry()`](Self::desktop_entry) instead,
+    /// use [`identity()`](Self::identity) to set both.
+    ///
     /// # Platform Support
     /// Please note that this method has no effect on macOS. Here you can only set the application via [`set_application()`](fn.set_application.html)
     pub fn appname(&mut self, appname: &str) -> &mut Notification {
@@ -131,6 +350,29 @@ impl Notification {
         self
     }
 
+    /// Wrapper for [`Hint::DesktopEntry`], the name of your `.desktop` file without the extension.
+    ///
+    /// This identifies your application to the server: GNOME groups notifications by it
+    /// and takes their icon and displayed name from the desktop file, regardless of the [`appname()`](Self::appname).
+    /// On Windows it is used as the AUMID, see [`hint()`](Self::hint).
+    pub fn desktop_entry(&mut self, desktop_entry: &str) -> &mut Notification {
+        self.hint(Hint::DesktopEntry(desktop_entry.to_owned()))
+    }
+
+    /// Sets both the displayed [`appname()`](Self::appname) and the grouping [`desktop_entry()`](Self::desktop_entry).
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// Notification::new()
+    ///     .identity("Firefox", "firefox")
+    ///     .summary("Download finished")
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    pub fn identity(&mut self, app_name: &str, desktop_entry: &str) -> &mut Notification {
+        self.appname(app_name).desktop_entry(desktop_entry)
+    }
+
     /// Set the `summary`.
     ///
     /// Often acts as title of the notification. For more elaborate content use the `body` field.
@@ -154,6 +396,68 @@ impl Notification {
         self
     }
 
+    /// Sets the large content image of the notification, e.g. a photo or the cover of the song playing.
+    ///
+    /// Notifications have two pictures: the small icon of the app, see [`app_icon()`](Self::app_icon),
+    /// and an image that belongs to the content and is shown larger, often next to the body.
+    /// This sends the image as `image-data`, which takes precedence over an `image-path` set before,
+    /// while the app icon stays what it is.
+    /// Passing a content image as the icon gets it shrunk into the icon slot, or shown instead of the app icon.
+    ///
+    /// How large the image is shown is up to the server, some show only one of the two pictures.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// # fn _doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// Notification::new()
+    ///     .app_icon("music-player")
+    ///     .hero_image(Image::open("cover.png")?)
+    ///     .summary("Now playing")
+    ///     .body("Song 2")
+    ///     .show()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// (xdg only)
+    #[cfg(all(feature = "images", unix, not(target_os = "macos")))]
+    pub fn hero_image(&mut self, image: Image) -> &mut Notification {
+        self.hints
+            .retain(|hint| !matches!(hint, Hint::ImagePath(_)));
+        self.hint(Hint::ImageData(image));
+        self
+    }
+
+    /// An animated image such as a spinner.
+    ///
+    /// The first frame is sent as the image, as if it was set via [`image_data()`](Self::image_data).
+    /// Servers advertising `"icon-multi"` (see [`get_icon_support()`](crate::get_icon_support)) are sent all `frames`
+    /// as well, under the vendor hint `x-notify-rust-image-frames` since the specification doesn't say how to send them.
+    /// Replaces any image set before, an empty list of `frames` removes it.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// # fn _doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// let frames = (0..8)
+    ///     .map(|i| Image::open(format!("spinner/{}.png", i)))
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// Notification::new()
+    ///     .summary("Uploading")
+    ///     .animated_icon(frames)
+    ///     .show()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(feature = "images", unix, not(target_os = "macos")))]
+    pub fn animated_icon(&mut self, frames: Vec<Image>) -> &mut Notification {
+        self.hints.retain(|hint| !hint.is_image_data());
+        if let Some(first) = frames.first() {
+            self.hint(Hint::ImageData(first.clone()));
+        }
+        self.icon_frames = frames;
+        self
+    }
+
     /// Wrapper for `Hint::ImagePath`
     #[cfg(all(unix, not(target_os = "macos")))]
     pub fn image_path(&mut self, path: &str) -> &mut Notification {
@@ -161,6 +465,42 @@ impl Notification {
         self
     }
 
+    /// Which of the images and icons set on the notification the server is going to show.
+    ///
+    /// The specification gives `image-data` precedence over `image-path`,
+    /// which in turn takes precedence over the [`icon()`](Self::icon) (`app_icon`).
+    /// Helps finding out why a notification shows no icon, or not the expected one.
+    ///
+    /// ```
+    /// # use notify_rust::*;
+    /// let mut notification = Notification::new();
+    /// assert_eq!(notification.effective_icon(), IconSource::None);
+    /// notification.icon("firefox");
+    /// assert_eq!(notification.effective_icon(), IconSource::AppIcon("firefox"));
+    /// notification.image_path("/tmp/screenshot.png");
+    /// assert_eq!(notification.effective_icon(), IconSource::ImagePath("/tmp/screenshot.png"));
+    /// ```
+    ///
+    /// Whether the server shows images at all is up to its [`IconSupport`](crate::IconSupport).
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn effective_icon(&self) -> xdg::IconSource<'_> {
+        let mut path = None;
+        for hint in self.hints_to_send() {
+            match hint {
+                hint if hint.is_image_data() => return xdg::IconSource::ImageData,
+                Hint::ImagePath(image_path) if !image_path.is_empty() => path = Some(image_path),
+                _ => {}
+            }
+        }
+        match path {
+            Some(path) => xdg::IconSource::ImagePath(path),
+            None if !self.icon.is_empty() => xdg::IconSource::AppIcon(&self.icon),
+            None => xdg::IconSource::None,
+        }
+    }
+
     /// Wrapper for `NotificationHint::ImagePath`
     #[cfg(target_os = "windows")]
     pub fn image_path(&mut self, path: &str) -> &mut Notification {
@@ -177,15 +517,134 @@ impl Notification {
 
     /// Wrapper for `Hint::ImageData`
     #[cfg(all(feature = "images", unix, not(target_os = "macos")))]
-    pub fn image<T: AsRef<std::path::Path> + Sized>(
+    pub fn image<T: AsRef<Path> + Sized>(&mut self, path: T) -> Result<&mut Notification> {
+        let img = Image::open(&path)?;
+        self.hint(Hint::ImageData(img));
+        Ok(self)
+    }
+
+    /// Wrapper for `Hint::ImageData` that takes an already decoded image.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// # fn _doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// let screenshot = image::DynamicImage::new_rgb8(640, 480);
+    /// Notification::new()
+    ///     .summary("Screenshot taken")
+    ///     .dynamic_image(&screenshot)?
+    ///     .show()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Images without an alpha channel are sent as RGB, all others as RGBA, both with 8 bits per sample.
+    #[cfg(all(feature = "images", unix, not(target_os = "macos")))]
+    pub fn dynamic_image(&mut self, image: &image::DynamicImage) -> Result<&mut Notification> {
+        let img = Image::try_from(image)?;
+        self.hint(Hint::ImageData(img));
+        Ok(self)
+    }
+
+    /// Shows a preview of `image` with an action to open it, what screenshot tools usually show after a capture.
+    ///
+    /// The image is scaled down to at most 512x512 pixels and sent as `Hint::ImageData`,
+    /// replacing any image or `Hint::ImagePath` set before, so every server shows the thumbnail.
+    /// `open_label` becomes the default action, invoked when the notification is clicked,
+    /// which arrives as `"default"` in [`wait_for_action()`](crate::NotificationHandle::wait_for_action).
+    /// The notification stays for 10 seconds, call [`timeout()`](Self::timeout) afterwards to change that.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// # fn _doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let screenshot = image::DynamicImage::new_rgb8(1920, 1080);
+    /// Notification::new()
+    ///     .summary("Screenshot saved")
+    ///     .body("~/Pictures/Screenshot.png")
+    ///     .with_thumbnail(&screenshot, "Open")?
+    ///     .show()?
+    ///     .wait_for_action(|action| {
+    ///         if action == "default" {
+    ///             // open the file
+    ///         }
+    ///     });
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(feature = "images", unix, not(target_os = "macos")))]
+    pub fn with_thumbnail(
         &mut self,
-        path: T,
+        image: &image::DynamicImage,
+        open_label: &str,
     ) -> Result<&mut Notification> {
-        let img = Image::open(&path)?;
+        let thumbnail = Image::scaled_down(image)?;
+        self.hints
+            .retain(|hint| !matches!(hint, Hint::ImagePath(_)));
+        self.hint(Hint::ImageData(thumbnail));
+        self.actions.retain(|action| action.key != "default");
+        self.action("default", open_label);
+        self.timeout(Timeout::Milliseconds(10_000));
+        Ok(self)
+    }
+
+    /// Wrapper for `Hint::ImageData` that takes an encoded image, PNG, JPEG or anything else the image crate decodes.
+    ///
+    /// Images larger than 512x512 pixels are scaled down, and more than 8 MiB of `bytes` are rejected,
+    /// so the notification stays a reasonable D-Bus message.
+    #[cfg(all(feature = "images", unix, not(target_os = "macos")))]
+    pub fn image_bytes(&mut self, bytes: &[u8]) -> Result<&mut Notification> {
+        let img = Image::from_encoded(bytes)?;
         self.hint(Hint::ImageData(img));
         Ok(self)
     }
 
+    /// Downloads the image at `url` and sends it as `Hint::ImageData`, e.g. album art of a media player.
+    ///
+    /// At most 8 MiB are downloaded, then the image is decoded and scaled like in [`image_bytes()`](Self::image_bytes).
+    /// The download runs on a thread pool, it doesn't block the executor this is awaited on.
+    /// If downloading or decoding fails the image is left out and the notification falls back to its [icon](Self::icon),
+    /// [`with_image_url_required()`](Self::with_image_url_required) makes this fail instead.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// # async fn _doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// Notification::new()
+    ///     .summary("Now playing")
+    ///     .icon("media-playback-start")
+    ///     .image_url("https://example.com/cover.jpg")
+    ///     .await?
+    ///     .show()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// (xdg only, needs the `http` and `images` features)
+    #[cfg(all(feature = "http", feature = "images", unix, not(target_os = "macos")))]
+    pub async fn image_url(&mut self, url: &str) -> Result<&mut Notification> {
+        let downloaded = match crate::http::get(url, crate::image::MAX_ENCODED_LEN + 1).await {
+            Ok(bytes) => Image::from_encoded(&bytes).map_err(Error::from),
+            Err(error) => Err(error),
+        };
+        match downloaded {
+            Ok(image) => {
+                self.hint(Hint::ImageData(image));
+            }
+            Err(error) if self.image_url_required => return Err(error),
+            Err(error) if !xdg::quiet::is_quiet() => {
+                log::warn!("leaving out the image at {}, {}", url, error);
+            }
+            Err(_) => {}
+        }
+        Ok(self)
+    }
+
+    /// Makes [`image_url()`](Self::image_url) fail if the image can't be downloaded or decoded,
+    /// instead of leaving it out.
+    #[cfg(all(feature = "http", feature = "images", unix, not(target_os = "macos")))]
+    pub fn with_image_url_required(&mut self, required: bool) -> &mut Notification {
+        self.image_url_required = required;
+        self
+    }
+
     /// Wrapper for `Hint::SoundName`
     #[cfg(all(unix, not(target_os = "macos")))]
     pub fn sound_name(&mut self, name: &str) -> &mut Notification {
@@ -193,6 +652,28 @@ impl Notification {
         self
     }
 
+    /// Play the sound at `file`, or the themed sound `name` if the server can't play files.
+    ///
+    /// Usually only one of `sound-file` and `sound-name` is sent, `sound-file` taking precedence.
+    /// This sends both, so servers that support both can fall back to the name.
+    /// [`Hint::SuppressSound`] still silences either of them.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// Notification::new()
+    ///     .summary("Message")
+    ///     .sound_with_fallback("message-new-instant", "/usr/share/sounds/custom/message.oga")
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn sound_with_fallback(&mut self, name: &str, file: &str) -> &mut Notification {
+        self.hint(Hint::SoundName(name.to_owned()));
+        self.hint(Hint::SoundFile(file.to_owned()));
+        self.sound_fallback = true;
+        self
+    }
+
     /// Set the `sound_name` for the `NSUserNotification`
     #[cfg(any(target_os = "macos", target_os = "windows"))]
     pub fn sound_name(&mut self, name: &str) -> &mut Notification {
@@ -223,6 +704,18 @@ impl Notification {
         self
     }
 
+    /// Sets the small icon of the app, shown in the header of the notification or in front of it.
+    ///
+    /// This is the `app_icon` of the specification, set just like [`icon()`](Self::icon),
+    /// with an icon name or the path of a file.
+    /// For an image that belongs to the content, use [`hero_image()`](Self::hero_image) instead.
+    ///
+    /// # Platform support
+    /// macOS does not support manually setting the icon. However you can pretend to be another app using [`set_application()`](fn.set_application.html)
+    pub fn app_icon(&mut self, name: &str) -> &mut Notification {
+        self.icon(name)
+    }
+
     /// Set the `icon` field automatically.
     ///
     /// This looks at your binary's name and uses it to set the icon.
@@ -234,6 +727,36 @@ impl Notification {
         self
     }
 
+    /// Set the `icon` field to the `Icon` of the installed desktop entry given by [`Hint::DesktopEntry`].
+    ///
+    /// The `.desktop` file is looked up in the `applications` directories of `$XDG_DATA_HOME` and `$XDG_DATA_DIRS`.
+    /// GNOME already takes the icon from the desktop entry, this makes other servers show the same one.
+    /// Leaves the icon alone if there is no desktop entry hint or the entry has no icon,
+    /// so set the hint first.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// Notification::new()
+    ///     .summary("Download finished")
+    ///     .hint(Hint::DesktopEntry("firefox".into()))
+    ///     .icon_from_desktop_entry()
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn icon_from_desktop_entry(&mut self) -> &mut Notification {
+        let icon = self.get_hints().find_map(|hint| match hint {
+            Hint::DesktopEntry(entry) => xdg::desktop_entry::icon(entry),
+            _ => None,
+        });
+        if let Some(icon) = icon {
+            self.icon = icon;
+        }
+        self
+    }
+
     /// Adds a hint.
     ///
     /// This method will add a hint to the internal hint [`HashSet`].
@@ -259,8 +782,12 @@ impl Notification {
     ///                    .show();
     /// ```
     ///
+    /// Each hint is sent only once, so adding a hint replaces an earlier one of the same kind,
+    /// or with the same key for [`Hint::Custom`] and [`Hint::CustomInt`].
+    ///
     /// # Platform support
-    /// Most of these hints don't even have an effect on the big XDG Desktops, they are completely tossed on macOS.
+    /// Most of these hints don't even have an effect on the big XDG Desktops.
+    /// On macOS and Windows only those with a counterpart there are applied, see below.
     #[cfg(all(unix, not(target_os = "macos")))]
     pub fn hint(&mut self, hint: Hint) -> &mut Notification {
         match hint {
@@ -273,108 +800,826 @@ impl Notification {
                     .insert((k.clone(), CustomHintType::String), Hint::Custom(k, v));
             }
             _ => {
+                // another image replaces the frames of `animated_icon()`
+                #[cfg(feature = "images")]
+                if hint.is_image_data() {
+                    self.icon_frames.clear();
+                }
+                let kind = std::mem::discriminant(&hint);
+                self.hints
+                    .retain(|existing| std::mem::discriminant(existing) != kind);
                 self.hints.insert(hint);
             }
         }
         self
     }
 
-    #[cfg(all(unix, not(target_os = "macos")))]
-    pub(crate) fn get_hints(&self) -> impl Iterator<Item = &Hint> {
-        self.hints.iter().chain(self.hints_unique.values())
-    }
-
-    /// Set the `timeout`.
-    ///
-    /// Accepts multiple types that implement `Into<Timeout>`.
-    ///
-    /// ## `i31`
+    /// Adds several hints at once, same as calling [`hint()`](Self::hint) for each of them.
     ///
-    /// This sets the time (in milliseconds) from the time the notification is displayed until it is
-    /// closed again by the Notification Server.
-    /// According to [specification](https://developer.gnome.org/notification-spec/)
-    /// -1 will leave the timeout to be set by the server and
-    /// 0 will cause the notification never to expire.
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// # let important = true;
+    /// let mut hints = vec![Hint::Category("transfer.complete".into())];
+    /// if important {
+    ///     hints.push(Hint::Resident(true));
+    /// }
+    /// Notification::new()
+    ///     .summary("Download complete")
+    ///     .with_hints(hints)
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    pub fn with_hints<I: IntoIterator<Item = Hint>>(&mut self, hints: I) -> &mut Notification {
+        for hint in hints {
+            self.hint(hint);
+        }
+        self
+    }
 
-    /// ## [Duration](`std::time::Duration`)
-    ///
-    /// When passing a [`Duration`](`std::time::Duration`) we will try convert it into milliseconds.
+    /// Copies all hints of `other`, e.g. to give a family of notifications the same urgency, category and desktop entry.
     ///
+    /// Same as passing each of them to [`hint()`](Self::hint): a hint that can only be sent once
+    /// (anything but custom hints with different names) replaces the one set before.
     ///
-    /// ```
-    /// # use std::time::Duration;
-    /// # use notify_rust::Timeout;
-    /// assert_eq!(Timeout::from(Duration::from_millis(2000)), Timeout::Milliseconds(2000));
-    /// ```
-    /// ### Caveats!
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// let template = Notification::new()
+    ///     .urgency(Urgency::Critical)
+    ///     .hint(Hint::Category("device.error".into()))
+    ///     .hint(Hint::DesktopEntry("org.example.Backup".into()))
+    ///     .finalize();
     ///
-    /// 1. If the duration is zero milliseconds then the original behavior will apply and the notification will **Never** timeout.
-    /// 2. Should the number of milliseconds not fit within an [`i32`] then we will fall back to the default timeout.
-    /// ```
-    /// # use std::time::Duration;
-    /// # use notify_rust::Timeout;
-    /// assert_eq!(Timeout::from(Duration::from_millis(0)), Timeout::Never);
-    /// assert_eq!(Timeout::from(Duration::from_millis(u64::MAX)), Timeout::Default);
+    /// Notification::new()
+    ///     .summary("Backup failed")
+    ///     .copy_hints_from(&template)
+    ///     .show()
+    ///     .unwrap();
     /// ```
     ///
-    /// # Platform support
-    /// This only works on XDG Desktops, macOS does not support manually setting the timeout.
-    pub fn timeout<T: Into<Timeout>>(&mut self, timeout: T) -> &mut Notification {
-        self.timeout = timeout.into();
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn copy_hints_from(&mut self, other: &Notification) -> &mut Notification {
+        self.with_hints(other.get_hints().cloned());
+        self.sound_fallback |= other.sound_fallback;
         self
     }
 
-    /// Set the `urgency`.
+    /// Applies the hints that have a counterpart on macOS.
     ///
-    /// Pick between Medium, Low and High.
+    /// * [`Hint::SoundName`] becomes the sound, like [`sound_name()`](Self::sound_name)
     ///
-    /// # Platform support
-    /// Most Desktops on linux and bsd are far too relaxed to pay any attention to this.
-    /// In macOS this does not exist
-    #[cfg(all(unix, not(target_os = "macos")))]
-    pub fn urgency(&mut self, urgency: Urgency) -> &mut Notification {
-        self.hint(Hint::Urgency(urgency)); // TODO impl as T where T: Into<Urgency>
+    /// All others are ignored, including [`Hint::Category`]:
+    /// the underlying `NSUserNotification` has no way of grouping notifications.
+    #[cfg(target_os = "macos")]
+    pub fn hint(&mut self, hint: Hint) -> &mut Notification {
+        if let Hint::SoundName(name) = hint {
+            self.sound_name = Some(name);
+        }
         self
     }
 
-    /// Set `actions`.
-    ///
-    /// To quote <http://www.galago-project.org/specs/notification/0.9/x408.html#command-notify>
-    ///
-    /// >  Actions are sent over as a list of pairs.
-    /// >  Each even element in the list (starting at index 0) represents the identifier for the action.
-    /// >  Each odd element in the list is the localized string that will be displayed to the user.y
+    /// Applies the hints that have a counterpart on Windows.
     ///
-    /// There is nothing fancy going on here yet.
-    /// **Careful! This replaces the internal list of actions!**
+    /// * [`Hint::DesktopEntry`] is used as the AUMID of the toast, unless [`app_id()`](Self::app_id) is set.
+    ///   Toasts only appear for registered AUMIDs, so this has to be the id your installer registered.
+    /// * [`Hint::SoundName`] becomes the sound, like [`sound_name()`](Self::sound_name)
+    /// * [`Hint::ImagePath`] becomes the image, like [`image_path()`](Self::image_path)
     ///
-    /// (xdg only)
-    #[deprecated(note = "please use .action() only")]
-    pub fn actions(&mut self, actions: Vec<String>) -> &mut Notification {
-        self.actions = actions;
+    /// All others are ignored, including [`Hint::Category`]:
+    /// toasts are grouped by their AUMID only.
+    #[cfg(target_os = "windows")]
+    pub fn hint(&mut self, hint: Hint) -> &mut Notification {
+        match hint {
+            Hint::DesktopEntry(entry) => self.desktop_entry = Some(entry),
+            Hint::SoundName(name) => self.sound_name = Some(name),
+            Hint::ImagePath(path) => self.path_to_image = Some(path),
+            _ => {}
+        }
         self
     }
 
-    /// Add an action.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) fn get_hints(&self) -> impl Iterator<Item = &Hint> {
+        self.hints.iter().chain(self.hints_unique.values())
+    }
+
+    /// Returns the hints that a server with these `capabilities` is likely to ignore.
+    ///
+    /// Pass in what [`get_capabilities()`](crate::get_capabilities) returned, e.g. to warn users or fall back.
+    /// Sound hints need `"sound"`, [`Hint::ActionIcons`] needs `"action-icons"`,
+    /// [`Hint::Transient`] needs `"persistence"` and image hints need `"icon-static"` or `"icon-multi"`.
+    /// All other hints are not covered by any capability and are assumed to be used.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// let capabilities = get_capabilities().unwrap();
+    /// let notification = Notification::new()
+    ///     .summary("Beep")
+    ///     .sound_name("message-new-instant")
+    ///     .finalize();
+    ///
+    /// for hint in notification.unsupported_hints(&capabilities) {
+    ///     eprintln!("the server will probably ignore {:?}", hint);
+    /// }
+    /// ```
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn unsupported_hints<S: AsRef<str>>(&self, capabilities: &[S]) -> Vec<&Hint> {
+        self.get_hints()
+            .filter(|hint| {
+                let required = hint.capabilities();
+                !required.is_empty()
+                    && !capabilities
+                        .iter()
+                        .any(|capability| required.contains(&capability.as_ref()))
+            })
+            .collect()
+    }
+
+    /// Returns a copy that only uses what a server with these `capabilities` understands.
+    ///
+    /// Pass in what [`get_capabilities()`](crate::get_capabilities) returned.
+    /// Where [`unsupported_hints()`](Self::unsupported_hints) and [`set_strict_capabilities()`](crate::set_strict_capabilities)
+    /// only tell, this takes out everything the server would ignore or show garbled:
+    ///
+    /// * without `"body"` the body is left out
+    /// * without `"body-markup"` the body is turned into plain text,
+    ///   without `"body-hyperlinks"` or `"body-images"` just links or images are taken out of it
+    /// * without `"actions"` the actions are left out, or replaced as in [`with_action_fallback()`](Self::with_action_fallback)
+    /// * [`animated_icon()`](Self::animated_icon) is reduced to its first frame without `"icon-multi"`
+    /// * every hint in [`unsupported_hints()`](Self::unsupported_hints) is removed,
+    ///   as well as the default text of [`inline_reply_with_default()`](Self::inline_reply_with_default)
+    ///
+    /// ```
+    /// # use notify_rust::*;
+    /// let notification = Notification::new()
+    ///     .summary("Alice")
+    ///     .body("<b>lunch</b> at <a href=\"https://example.com/menu\">Luigi's</a>?")
+    ///     .action("reply", "Reply")
+    ///     .sound_name("message-new-instant")
+    ///     .finalize();
+    ///
+    /// let negotiated = notification.negotiate(&["body", "body-markup"]);
+    /// assert_eq!(negotiated.body, "<b>lunch</b> at Luigi's?");
+    /// assert!(negotiated.actions.is_empty());
+    /// assert!(negotiated.hints.is_empty());
+    /// ```
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn negotiate<S: AsRef<str>>(&self, capabilities: &[S]) -> Notification {
+        let supports = |capability: &str| capabilities.iter().any(|c| c.as_ref() == capability);
+
+        let mut negotiated = self.clone();
+        #[cfg(feature = "images")]
+        if !supports("icon-multi") {
+            negotiated.icon_frames.clear();
+        }
+
+        let unsupported = negotiated
+            .unsupported_hints(capabilities)
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        negotiated.hints.retain(|hint| !unsupported.contains(hint));
+        if !supports(action::REPLY_DEFAULT_HINT) {
+            negotiated.hints_unique.remove(&reply_default_key());
+        }
+
+        if !supports("actions") && !negotiated.actions.is_empty() {
+            match negotiated.action_fallback.clone() {
+                Some(suffix) => negotiated = negotiated.without_actions(&suffix),
+                None => negotiated.actions.clear(),
+            }
+        }
+
+        if !supports("body") {
+            negotiated.body.clear();
+        } else if !supports("body-markup") {
+            if xdg::strict::uses_markup(&negotiated.body) {
+                negotiated.body = xdg::strict::plain_text(&negotiated.body);
+            }
+        } else {
+            let links = supports("body-hyperlinks");
+            let images = supports("body-images");
+            negotiated.body = xdg::strict::strip_tags(&negotiated.body, |tag| {
+                (tag == "a" && !links) || (tag == "img" && !images)
+            });
+        }
+        negotiated
+    }
+
+    /// Guesses whether the notification pops up as a banner, is kept in the notification center, or both.
+    ///
+    /// Pass in what [`get_capabilities()`](crate::get_capabilities) returned.
+    /// This is best-effort: the specification leaves presentation to the server,
+    /// and users can change it per application or with do-not-disturb.
+    /// The rules are the ones most servers follow:
+    ///
+    /// * without the `"persistence"` capability notifications are only ever banners
+    /// * [`Hint::Transient`] bypasses persistence, it's a banner only
+    /// * [`Urgency::Low`] is not popped up by GNOME Shell and others, it goes to the notification center only
+    /// * everything else is shown as a banner and then kept
+    ///
+    /// ```
+    /// # use notify_rust::*;
+    /// let capabilities = ["body", "persistence"];
+    /// let mut notification = Notification::new();
+    /// assert_eq!(notification.predicted_presentation(&capabilities), Presentation::Both);
+    /// notification.hint(Hint::Transient(true));
+    /// assert_eq!(notification.predicted_presentation(&capabilities), Presentation::Banner);
+    /// ```
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn predicted_presentation<S: AsRef<str>>(&self, capabilities: &[S]) -> xdg::Presentation {
+        let persistence = capabilities.iter().any(|c| c.as_ref() == "persistence");
+        let transient = self.hints.contains(&Hint::Transient(true));
+        let low = self.hints.contains(&Hint::Urgency(Urgency::Low));
+        match (persistence && !transient, low) {
+            (false, _) => xdg::Presentation::Banner,
+            (true, true) => xdg::Presentation::Persistent,
+            (true, false) => xdg::Presentation::Both,
+        }
+    }
+
+    /// The hints as they are sent to the server.
+    ///
+    /// `suppress-sound` silences `sound-file`, which in turn takes precedence over `sound-name`,
+    /// unless both were set via [`sound_with_fallback()`](Self::sound_with_fallback).
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) fn hints_to_send(&self) -> impl Iterator<Item = &Hint> {
+        let suppressed = self.hints.contains(&Hint::SuppressSound(true));
+        let has_file = self
+            .hints
+            .iter()
+            .any(|hint| matches!(hint, Hint::SoundFile(_)));
+        let send_name = !has_file || self.sound_fallback;
+        self.get_hints().filter(move |hint| match hint {
+            Hint::SoundFile(_) => !suppressed,
+            Hint::SoundName(_) => !suppressed && send_name,
+            _ => true,
+        })
+    }
+
+    /// Set the `timeout`.
+    ///
+    /// Accepts multiple types that implement `Into<Timeout>`.
+    ///
+    /// ## `i31`
+    ///
+    /// This sets the time (in milliseconds) from the time the notification is displayed until it is
+    /// closed again by the Notification Server.
+    /// According to [specification](https://developer.gnome.org/notification-spec/)
+    /// -1 will leave the timeout to be set by the server and
+    /// 0 will cause the notification never to expire.
+    ///
+    /// ## [Duration](`std::time::Duration`)
+    ///
+    /// When passing a [`Duration`](`std::time::Duration`) we will try convert it into milliseconds.
+    ///
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use notify_rust::Timeout;
+    /// assert_eq!(Timeout::from(Duration::from_millis(2000)), Timeout::Milliseconds(2000));
+    /// ```
+    /// ### Caveats!
+    ///
+    /// 1. If the duration is zero milliseconds then the original behavior will apply and the notification will **Never** timeout.
+    /// 2. Should the number of milliseconds not fit within an [`i32`] then we will fall back to the default timeout.
+    /// ```
+    /// # use std::time::Duration;
+    /// # use notify_rust::Timeout;
+    /// assert_eq!(Timeout::from(Duration::from_millis(0)), Timeout::Never);
+    /// assert_eq!(Timeout::from(Duration::from_millis(u64::MAX)), Timeout::Default);
+    /// ```
+    ///
+    /// # Platform support
+    /// This only works on XDG Desktops, macOS does not support manually setting the timeout.
+    pub fn timeout<T: Into<Timeout>>(&mut self, timeout: T) -> &mut Notification {
+        self.timeout = timeout.into();
+        self
+    }
+
+    /// The `expire_timeout` as it is sent to the server.
+    ///
+    /// That is `-1` for [`Timeout::Default`], `0` for [`Timeout::Never`] and the milliseconds otherwise,
+    /// at most [`i32::MAX`].
+    /// ```
+    /// # use notify_rust::*;
+    /// assert_eq!(Notification::new().timeout_ms(), -1);
+    /// assert_eq!(Notification::new().timeout(Timeout::Never).timeout_ms(), 0);
+    /// assert_eq!(Notification::new().timeout(6000).timeout_ms(), 6000);
+    /// ```
+    pub fn timeout_ms(&self) -> i32 {
+        self.timeout.into()
+    }
+
+    /// Set the `urgency`.
+    ///
+    /// Pick between Medium, Low and High.
+    ///
+    /// # Platform support
+    /// Most Desktops on linux and bsd are far too relaxed to pay any attention to this.
+    /// In macOS this does not exist
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn urgency(&mut self, urgency: Urgency) -> &mut Notification {
+        self.hint(Hint::Urgency(urgency)); // TODO impl as T where T: Into<Urgency>
+        self
+    }
+
+    /// Makes sure the notification gets through while the user turned on do not disturb, for alerts that can't wait.
+    ///
+    /// This sets critical [`urgency`](Self::urgency), the one thing desktops let through while do not disturb is on.
+    /// There is no hint of its own for this, neither in the specification nor with any of the common servers.
+    /// As a side effect, most servers keep critical notifications until they are dismissed.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// Notification::new()
+    ///     .summary("Battery critical")
+    ///     .body("Shutting down in 1 minute")
+    ///     .bypass_dnd()
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// (xdg only, `mac-notification-sys` can't set an interruption level
+    /// and `tauri-winrt-notification` has no `urgent` scenario)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn bypass_dnd(&mut self) -> &mut Notification {
+        self.urgency(Urgency::Critical)
+    }
+
+    /// Asks for the notification to be shown on the monitor at `x`, `y` that is `width` by `height` pixels large.
+    ///
+    /// The specification has no notion of monitors or outputs,
+    /// this points the [`Hint::X`] and [`Hint::Y`] at the centre of the monitor, in coordinates of the whole desktop,
+    /// which servers that honour them (e.g. notify-osd and xfce4-notifyd) use to place the notification on that monitor.
+    /// Most others ignore them and stick to their own configuration, dunst and mako e.g. have a `monitor`/`output` setting.
+    /// So this is best effort only.
+    ///
+    /// There is no portable way of querying monitor geometry on X11 and Wayland,
+    /// take it from your toolkit or windowing library.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// // the second of two 1920x1080 monitors side by side
+    /// Notification::new()
+    ///     .summary("Presentation starts in 5 minutes")
+    ///     .on_monitor(1920, 0, 1920, 1080)
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn on_monitor(&mut self, x: i32, y: i32, width: u32, height: u32) -> &mut Notification {
+        let centre = |origin: i32, length: u32| {
+            (i64::from(origin) + i64::from(length / 2)).clamp(i32::MIN.into(), i32::MAX.into())
+                as i32
+        };
+        self.hint(Hint::X(centre(x, width)));
+        self.hint(Hint::Y(centre(y, height)));
+        self
+    }
+
+    /// Only keep the latest notification with this `tag` on screen.
+    ///
+    /// Sends `x-dunst-stack-tag`, which dunst uses to replace notifications with the same tag,
+    /// and `x-canonical-private-synchronous`, which notify-osd (and dunst as well) treats the same way.
+    /// Other servers ignore them, use [`id()`](Self::id) or [`merge_by_summary()`](Self::merge_by_summary) to replace notifications everywhere.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// Notification::new()
+    ///     .summary("Volume: 40%")
+    ///     .stack_tag("volume")
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn stack_tag(&mut self, tag: &str) -> &mut Notification {
+        self.hint(Hint::Custom(xdg::tags::STACK_TAG_HINT.into(), tag.into()));
+        self.hint(Hint::Custom(
+            "x-canonical-private-synchronous".into(),
+            tag.into(),
+        ));
+        self
+    }
+
+    /// Set `actions`.
+    ///
+    /// To quote <http://www.galago-project.org/specs/notification/0.9/x408.html#command-notify>
+    ///
+    /// >  Actions are sent over as a list of pairs.
+    /// >  Each even element in the list (starting at index 0) represents the identifier for the action.
+    /// >  Each odd element in the list is the localized string that will be displayed to the user.y
+    ///
+    /// The list is paired up with [`Action::from_flat()`],
+    /// a trailing key without a label is left out and makes [`build()`](Self::build) and `show()` fail.
+    /// **Careful! This replaces the internal list of actions!**
+    ///
+    /// (xdg only)
+    #[deprecated(note = "please use .action() only")]
+    pub fn actions(&mut self, actions: Vec<String>) -> &mut Notification {
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            self.unpaired_action = if actions.len() % 2 == 1 {
+                actions.last().cloned()
+            } else {
+                None
+            };
+        }
+        self.actions = Action::from_flat(actions);
+        self
+    }
+
+    /// Add an action.
     ///
     /// This adds a single action to the internal list of actions.
     ///
     /// (xdg only)
     pub fn action(&mut self, identifier: &str, label: &str) -> &mut Notification {
-        self.actions.push(identifier.to_owned());
-        self.actions.push(label.to_owned());
+        self.actions.push(Action::new(identifier, label));
+        self
+    }
+
+    /// Never declares the `"default"` action, so clicking the notification itself invokes nothing.
+    ///
+    /// The specification only invokes `"default"` on a click if the notification declared it,
+    /// what a click does otherwise is up to the server: most just close the notification,
+    /// GNOME Shell activates the app of the [`desktop_entry()`](Self::desktop_entry) as well.
+    /// This leaves out the `"default"` action when sending, including one added later e.g. by [`action()`](Self::action),
+    /// while all other actions stay buttons.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// Notification::new()
+    ///     .summary("Disk almost full")
+    ///     .action("clean", "Clean up")
+    ///     .no_default_action()
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn no_default_action(&mut self) -> &mut Notification {
+        self.no_default_action = true;
+        self
+    }
+
+    /// The actions as they are sent to the server, flattened into alternating keys and labels.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) fn actions_to_send(&self) -> Vec<&str> {
+        self.actions
+            .iter()
+            .filter(|action| !(self.no_default_action && action.kind == ActionKind::Default))
+            .flat_map(|action| [action.key.as_str(), action.label.as_str()])
+            .collect()
+    }
+
+    /// Adds a text field to reply in, labelled `label` and showing `placeholder` while it is empty.
+    ///
+    /// This is the `"inline-reply"` action of KDE Plasma, which sends the text in a `NotificationReplied` signal,
+    /// see [`NotificationSignal::NotificationReplied`](crate::NotificationSignal::NotificationReplied).
+    /// Other servers show a button invoking `"inline-reply"` instead. Replaces the reply field added before.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// Notification::new()
+    ///     .summary("Alice")
+    ///     .body("lunch?")
+    ///     .inline_reply("Reply", "Write a reply…")
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// (xdg only)
+    pub fn inline_reply(&mut self, label: &str, placeholder: &str) -> &mut Notification {
+        self.actions
+            .retain(|action| action.kind != ActionKind::InlineReply);
+        self.action(action::INLINE_REPLY, label);
+        #[cfg(all(unix, not(target_os = "macos")))]
+        self.hint(Hint::Custom(
+            action::REPLY_PLACEHOLDER_HINT.into(),
+            placeholder.into(),
+        ));
+        #[cfg(any(target_os = "macos", target_os = "windows"))]
+        let _ = placeholder;
         self
     }
 
+    /// Like [`inline_reply()`](Self::inline_reply), with the reply field already holding `default_text`, e.g. a canned response.
+    ///
+    /// No specification covers a default text, it is sent as `x-kde-reply-default-text`
+    /// and only to servers that advertise a capability of the same name.
+    /// All others just show the `placeholder`.
+    ///
+    /// (xdg only)
+    pub fn inline_reply_with_default(
+        &mut self,
+        label: &str,
+        placeholder: &str,
+        default_text: &str,
+    ) -> &mut Notification {
+        self.inline_reply(label, placeholder);
+        #[cfg(all(unix, not(target_os = "macos")))]
+        self.hint(Hint::Custom(
+            action::REPLY_DEFAULT_HINT.into(),
+            default_text.into(),
+        ));
+        #[cfg(any(target_os = "macos", target_os = "windows"))]
+        let _ = default_text;
+        self
+    }
+
+    /// How the action added as `identifier` will be presented, `None` if there is no such action.
+    ///
+    /// ```
+    /// # use notify_rust::*;
+    /// let notification = Notification::new()
+    ///     .action("default", "Open")
+    ///     .action("archive", "Archive")
+    ///     .finalize();
+    /// assert_eq!(notification.action_kind("default"), Some(ActionKind::Default));
+    /// assert_eq!(notification.action_kind("archive"), Some(ActionKind::Button));
+    /// assert_eq!(notification.action_kind("delete"), None);
+    /// ```
+    ///
+    /// (xdg only)
+    pub fn action_kind(&self, identifier: &str) -> Option<ActionKind> {
+        self.actions
+            .iter()
+            .find(|action| action.key == identifier)
+            .map(|action| action.kind)
+    }
+
     /// Set an Id ahead of time
     ///
     /// Setting the id ahead of time allows overriding a known other notification.
     /// Though if you want to update a notification, it is easier to use the `update()` method of
     /// the `NotificationHandle` object that `show()` returns.
     ///
+    /// Takes a [`ReplaceId`], a `u32` or an `Option<u32>`, an id of `0` shows a new notification.
+    ///
+    /// (xdg only)
+    pub fn id(&mut self, id: impl Into<ReplaceId>) -> &mut Notification {
+        self.id = id.into();
+        self
+    }
+
+    /// After showing, write the id of the notification to the file at `path`.
+    ///
+    /// Together with [`replace_from_persisted()`](Self::replace_from_persisted) a daemon can replace
+    /// the notification of its previous run instead of piling up new ones.
+    /// Failing to write the file doesn't fail `show()`.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// let state = "/tmp/backup-daemon.notification";
+    /// Notification::new()
+    ///     .summary("Backup running")
+    ///     .replace_from_persisted(state)
+    ///     .persist_id_to(state)
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn persist_id_to<P: AsRef<Path>>(&mut self, path: P) -> &mut Notification {
+        self.persist_path = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Replace the notification whose id was written to `path` by [`persist_id_to()`](Self::persist_id_to).
+    ///
+    /// If the file is missing or doesn't contain an id this is shown as a new notification.
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn replace_from_persisted<P: AsRef<Path>>(&mut self, path: P) -> &mut Notification {
+        if let Some(id) = read_persisted_id(path.as_ref()) {
+            self.id = ReplaceId::existing(id);
+        }
+        self
+    }
+
+    /// Replace a recently shown notification with the same summary.
+    ///
+    /// If a notification with exactly the same summary was shown on the same bus from this process within `window`,
+    /// `show()` replaces it instead of opening a new bubble, so you don't have to keep track of ids
+    /// for simple status updates. Keep the summary stable and put the changing part into the body,
+    /// or see [`merge_by_summary_prefix()`](Self::merge_by_summary_prefix).
+    /// An id set via [`id()`](Self::id) always takes precedence.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// # use std::time::Duration;
+    /// Notification::new()
+    ///     .summary("Sync")
+    ///     .body("Syncing…")
+    ///     .merge_by_summary(Duration::from_secs(60))
+    ///     .show()
+    ///     .unwrap();
+    ///
+    /// // later, this replaces the first one
+    /// Notification::new()
+    ///     .summary("Sync")
+    ///     .body("Sync complete")
+    ///     .merge_by_summary(Duration::from_secs(60))
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn merge_by_summary(&mut self, window: Duration) -> &mut Notification {
+        self.merge_window = Some(window);
+        self.merge_prefix = None;
+        self
+    }
+
+    /// Like [`merge_by_summary()`](Self::merge_by_summary), but replaces a recent notification whose summary started with `prefix` as well.
+    ///
+    /// That way "Syncing…" turns into "Sync complete" in the same bubble.
+    /// A notification whose summary doesn't start with `prefix` is neither merged nor remembered.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// # use std::time::Duration;
+    /// Notification::new()
+    ///     .summary("Syncing…")
+    ///     .merge_by_summary_prefix("Sync", Duration::from_secs(60))
+    ///     .show()
+    ///     .unwrap();
+    ///
+    /// // later, this replaces the first one
+    /// Notification::new()
+    ///     .summary("Sync complete")
+    ///     .merge_by_summary_prefix("Sync", Duration::from_secs(60))
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn merge_by_summary_prefix(&mut self, prefix: &str, window: Duration) -> &mut Notification {
+        self.merge_window = Some(window);
+        self.merge_prefix = Some(prefix.to_owned());
+        self
+    }
+
+    /// Check summary and body before sending and strip control characters from them.
+    ///
+    /// `show()` fails with [`ErrorKind::FieldTooLong`] if the summary has more than `max_summary`
+    /// or the body more than `max_body` characters.
+    /// Control characters are removed first, except for line breaks and tabs in the body.
+    /// Every [`update()`](xdg::NotificationHandle::update) of the handle is checked the same way.
+    /// Use this when the content comes from untrusted sources like log lines or filenames.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// # let line_from_log = "";
+    /// Notification::new()
+    ///     .summary("New log entry")
+    ///     .body(line_from_log)
+    ///     .validate_lengths(64, 1024)
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn validate_lengths(&mut self, max_summary: usize, max_body: usize) -> &mut Notification {
+        self.length_limits = Some((max_summary, max_body));
+        self
+    }
+
+    /// Give up on the server if it doesn't reply to `Notify` or `CloseNotification` within `timeout`.
+    ///
+    /// Sending (and updating or closing through the handle) then fails with [`ErrorKind::Timeout`]
+    /// instead of hanging on a stuck server.
+    /// This has nothing to do with how long the notification is displayed, see [`timeout()`](Self::timeout) for that.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// # use std::time::Duration;
+    /// let shown = Notification::new()
+    ///     .summary("Copied to clipboard")
+    ///     .with_call_timeout(Duration::from_millis(250))
+    ///     .show();
+    /// ```
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn with_call_timeout(&mut self, timeout: Duration) -> &mut Notification {
+        self.call_timeout = Some(timeout);
+        self
+    }
+
+    /// Put the time of sending in front of the body, as `%H:%M:%S`.
+    ///
+    /// See [`with_timestamp_format()`](Self::with_timestamp_format).
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn with_timestamp(&mut self) -> &mut Notification {
+        self.with_timestamp_format("%H:%M:%S")
+    }
+
+    /// Put the time of sending in front of the body, in a `strftime` like `format`.
+    ///
+    /// The time is rendered every time the notification goes out, so a [scheduled](Self::show_after)
+    /// notification shows when it fired and an [update](crate::NotificationHandle::update) shows the time of the update.
+    ///
+    /// With the `chrono` feature this is the local time and all of chrono's specifiers are supported.
+    /// Without it the time is in UTC and only `%Y`, `%m`, `%d`, `%H`, `%M`, `%S` and `%%` are replaced.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// Notification::new()
+    ///     .summary("Disk almost full")
+    ///     .body("/var has 2% left")
+    ///     .with_timestamp_format("[%H:%M]")
+    ///     .show()
+    ///     .unwrap();
+    /// // body: "[14:03] /var has 2% left"
+    /// ```
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn with_timestamp_format(&mut self, format: &str) -> &mut Notification {
+        self.timestamp_format = Some(format.to_owned());
+        self
+    }
+
+    /// The body as it is sent right now, with the timestamp of [`with_timestamp()`](Self::with_timestamp).
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) fn body_at_send(&self) -> Cow<'_, str> {
+        let Some(format) = &self.timestamp_format else {
+            return Cow::Borrowed(&self.body);
+        };
+        if self.unstamped_body.is_some() {
+            // already stamped for this send
+            return Cow::Borrowed(&self.body);
+        }
+        let now = xdg::timestamp::now(format);
+        if self.body.is_empty() {
+            Cow::Owned(now)
+        } else {
+            Cow::Owned(format!("{} {}", now, self.body))
+        }
+    }
+
+    /// Puts the timestamp in front of the body for this send, so the length limits count it as well.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn applied_timestamp(&self) -> Cow<'_, Notification> {
+        if self.timestamp_format.is_none() || self.unstamped_body.is_some() {
+            return Cow::Borrowed(self);
+        }
+        let mut notification = self.clone();
+        notification.body = self.body_at_send().into_owned();
+        notification.unstamped_body = Some(self.body.clone());
+        Cow::Owned(notification)
+    }
+
+    /// What a handle keeps of this, without the timestamp of this send so updates render the time anew.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) fn for_handle(&self) -> Notification {
+        let mut notification = self.clone();
+        if let Some(body) = notification.unstamped_body.take() {
+            notification.body = body;
+        }
+        notification
+    }
+
+    /// Explain in the body what to do if the server can't show the actions.
+    ///
+    /// If the server doesn't advertise the `"actions"` capability, `body_suffix` is appended to the body
+    /// and the actions are left out, instead of sending buttons that silently vanish.
+    /// The capabilities are fetched once per server and kept for a minute.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// Notification::new()
+    ///     .summary("Deploy to production?")
+    ///     .action("accept", "Deploy")
+    ///     .with_action_fallback("Run `myapp accept` to confirm.")
+    ///     .show()
+    ///     .unwrap();
+    /// ```
+    ///
     /// (xdg only)
-    pub fn id(&mut self, id: u32) -> &mut Notification {
-        self.id = Some(id);
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn with_action_fallback(&mut self, body_suffix: &str) -> &mut Notification {
+        self.action_fallback = Some(body_suffix.to_owned());
         self
     }
 
@@ -385,6 +1630,132 @@ impl Notification {
         self.clone()
     }
 
+    /// Finalizes a Notification, checking everything that can be checked without talking to the server.
+    ///
+    /// Fails with [`ErrorKind::EmptySummary`], [`ErrorKind::UnpairedAction`] for an action key without a label,
+    /// [`ErrorKind::InvalidBus`], or [`ErrorKind::FieldTooLong`] if [`validate_lengths()`](Self::validate_lengths) was set,
+    /// so errors in what was built show up here and not only once it is sent.
+    /// The returned notification has control characters stripped just like `show()` would.
+    /// `show()` runs the same checks, except that it keeps sending notifications without a summary.
+    ///
+    /// ```
+    /// # use notify_rust::*;
+    /// let queued = Notification::new()
+    ///     .summary("Build finished")
+    ///     .validate_lengths(64, 1024)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let error = Notification::new().body("no summary").build().unwrap_err();
+    /// assert!(matches!(error.kind(), error::ErrorKind::EmptySummary));
+    /// ```
+    ///
+    /// (xdg only)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn build(&self) -> Result<Notification> {
+        if self.summary.trim().is_empty() {
+            return Err(ErrorKind::EmptySummary.into());
+        }
+        self.check_built()?;
+        Ok(self.checked_lengths()?.into_owned())
+    }
+
+    /// The checks of [`build()`](Self::build) that `show()` runs as well.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn check_built(&self) -> Result<()> {
+        if let Some(key) = &self.unpaired_action {
+            return Err(ErrorKind::UnpairedAction { key: key.clone() }.into());
+        }
+        if let Some(sub_bus) = &self.invalid_bus {
+            return Err(ErrorKind::InvalidBus {
+                sub_bus: sub_bus.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Parses a Notification from JSON.
+    ///
+    /// Every field is optional, missing ones keep the defaults of [`Notification::new()`].
+    /// Hints are given under their specified names.
+    ///
+    /// ```
+    /// # use notify_rust::*;
+    /// let notification = Notification::from_json(r#"{
+    ///     "summary": "Category:email",
+    ///     "hints": [{"category": "email"}, {"urgency": "critical"}],
+    ///     "timeout": {"milliseconds": 6000}
+    /// }"#).unwrap();
+    ///
+    /// assert_eq!(notification.summary, "Category:email");
+    /// assert!(notification.hints.contains(&Hint::Urgency(Urgency::Critical)));
+    /// assert_eq!(notification.timeout, Timeout::Milliseconds(6000));
+    /// ```
+    #[cfg(all(feature = "serde", unix, not(target_os = "macos")))]
+    pub fn from_json(json: &str) -> Result<Notification> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Serializes the Notification to JSON, the counterpart to [`Notification::from_json()`].
+    #[cfg(all(feature = "serde", unix, not(target_os = "macos")))]
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Posts the notification to a chat webhook instead of showing it, e.g. on a server without a desktop.
+    ///
+    /// The JSON body has the `appname`, `summary`, `body`, `urgency` and `icon`,
+    /// and a `text` of summary and body, which is what Slack, Mattermost and Rocket.Chat show.
+    /// Fails with [`ErrorKind::Http`] if the webhook can't be reached or answers with an error status.
+    /// The request runs on a thread pool, it doesn't block the executor this is awaited on.
+    /// Call [`show()`](Self::show) as well to notify both.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// # async fn _doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// Notification::new()
+    ///     .summary("Backup failed")
+    ///     .body("disk full")
+    ///     .urgency(Urgency::Critical)
+    ///     .show_webhook("https://chat.example.com/hooks/backup")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// (xdg only, needs the `http` and `serde` features)
+    #[cfg(all(feature = "http", feature = "serde", unix, not(target_os = "macos")))]
+    pub async fn show_webhook(&self, url: &str) -> Result<()> {
+        crate::http::post_json(url, self.webhook_json()?).await
+    }
+
+    #[cfg(all(feature = "http", feature = "serde", unix, not(target_os = "macos")))]
+    fn webhook_json(&self) -> Result<String> {
+        let body = self.body_at_send();
+        let text = if body.is_empty() {
+            self.summary.clone()
+        } else {
+            format!("{}\n{}", self.summary, body)
+        };
+        let urgency = self
+            .hints
+            .iter()
+            .find_map(|hint| match hint {
+                Hint::Urgency(urgency) => Some(*urgency),
+                _ => None,
+            })
+            .unwrap_or(Urgency::Normal);
+        Ok(serde_json::to_string(&WebhookPayload {
+            text: &text,
+            appname: &self.appname,
+            summary: &self.summary,
+            body: &body,
+            urgency,
+            icon: &self.icon,
+        })?)
+    }
+
     /// Schedules a Notification
     ///
     /// Sends a Notification at the specified date.
@@ -409,9 +1780,150 @@ impl Notification {
     /// Sends Notification to D-Bus.
     ///
     /// Returns a handle to a notification
+    ///
+    /// With the environment variable `NOTIFY_RUST_DRY_RUN` set (to anything but `0`) nothing is sent,
+    /// every send of this crate logs the notification at info level instead, e.g. for CI.
+    /// The returned handle gets made up ids and waiting on it returns right away with the reserved close reason `4`, [`CloseReason::Other(4)`](crate::CloseReason::Other).
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn show(&self) -> Result<xdg::NotificationHandle> {
+        let notification = self.prepare()?;
+        let handle = if xdg::dry_run::enabled() {
+            let id = xdg::dry_run::send(&notification);
+            xdg::NotificationHandle::dry_run(id, notification.for_handle())
+        } else {
+            xdg::show_notification(&notification)?
+        };
+        notification.shown(handle.id());
+        Ok(handle)
+    }
+
+    /// Shows the notification and blocks until the user picks an action or it is closed, a confirm dialog in one call.
+    ///
+    /// Meant for scripts and command line tools that have nothing else to do meanwhile.
+    /// Nothing is kept around once this returns, the connection is dropped
+    /// and a notification that is still on screen after an action is left to the server.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// # fn _doc() -> error::Result<()> {
+    /// let response = Notification::new()
+    ///     .summary("Delete 3 files?")
+    ///     .action("delete", "Delete")
+    ///     .action("keep", "Keep")
+    ///     .show_and_wait()?;
+    /// if response == OwnedActionResponse::Custom("delete".into()) {
+    ///     // delete them
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn show_and_wait(&self) -> Result<xdg::OwnedActionResponse> {
+        self.show()?.wait_for_response()
+    }
+
+    /// Shows the notification and waits for an action, parsed into your own type.
+    ///
+    /// See [`NotificationHandle::wait_for_action_typed()`](xdg::NotificationHandle::wait_for_action_typed).
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn show_and_wait_for_action_typed<A>(&self) -> Result<xdg::TypedActionResponse<A>>
+    where
+        A: std::str::FromStr,
+        A::Err: std::fmt::Display,
+    {
+        self.show()?.wait_for_action_typed()
+    }
+
+    /// Sends Notification to D-Bus without keeping a connection around.
+    ///
+    /// Returns a [`LightHandle`](xdg::LightHandle) that can only close the notification,
+    /// for fire-and-forget notifications that occasionally need to be taken back.
+    /// Use [`show()`](Self::show) if you want to wait for actions.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// let handle = Notification::new().summary("Uploading").show_light().unwrap();
+    /// // ... upload got cancelled
+    /// handle.close().unwrap();
+    /// ```
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn show_light(&self) -> Result<xdg::LightHandle> {
+        let notification = self.prepare()?;
+        let handle = if xdg::dry_run::enabled() {
+            xdg::LightHandle::dry_run(xdg::dry_run::send(&notification), &notification)
+        } else {
+            xdg::show_light(&notification)?
+        };
+        notification.shown(handle.id());
+        Ok(handle)
+    }
+
+    /// Sends the notification like [`show_light()`](Self::show_light), but only once `delay` has passed.
+    ///
+    /// Until then it can still be [cancelled](xdg::ScheduledNotification::cancel),
+    /// e.g. to only notify about a condition that persists for a while.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// # use std::time::Duration;
+    /// # let still_offline = || true;
+    /// let scheduled = Notification::new()
+    ///     .summary("Connection lost")
+    ///     .show_after(Duration::from_secs(3));
+    /// // ... connection came back
+    /// if !still_offline() {
+    ///     scheduled.cancel();
+    /// }
+    /// ```
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn show_after(&self, delay: Duration) -> xdg::ScheduledNotification {
+        xdg::ScheduledNotification::start(self.clone(), delay)
+    }
+
+    /// Sends Notification to D-Bus and only returns the id the server assigned to it.
+    ///
+    /// No connection is kept around, store the id to replace the notification later on with [`id()`](Self::id).
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// let id = Notification::new().summary("Downloading").send_id().unwrap();
+    /// // ... later, maybe from another process
+    /// Notification::new().summary("Download complete").id(id).send_id().unwrap();
+    /// ```
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn send_id(&self) -> Result<u32> {
+        self.show_light().map(|handle| handle.id())
+    }
+
+    /// Sends Notification to D-Bus and returns a handle that survives the connection, for notifications that stay for hours.
+    ///
+    /// See [`ReconnectingHandle`](xdg::ReconnectingHandle).
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn show_reconnecting(&self) -> Result<xdg::ReconnectingHandle> {
+        let id = self.send_id()?;
+        Ok(xdg::ReconnectingHandle::new(id, self.clone()))
+    }
+
+    /// Sends Notification to D-Bus and returns a handle that closes it again once dropped.
+    ///
+    /// See [`ScopedNotification`](xdg::ScopedNotification).
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn show_scoped(&self) -> Result<xdg::ScopedNotification> {
+        self.show().map(xdg::ScopedNotification::new)
+    }
+
+    /// Async version of [`send_id()`](Self::send_id).
     #[cfg(all(unix, not(target_os = "macos")))]
-    pub fn show(&self) -> Result<xdg::NotificationHandle> {
-        xdg::show_notification(self)
+    #[cfg(all(feature = "async", feature = "zbus"))]
+    pub async fn send_id_async(&self) -> Result<u32> {
+        let notification = self.prepare_async().await?;
+        let id = if xdg::dry_run::enabled() {
+            xdg::dry_run::send(&notification)
+        } else {
+            xdg::send_notification_detached_async(&notification).await?
+        };
+        notification.shown(id);
+        Ok(id)
     }
 
     /// Sends Notification to D-Bus.
@@ -420,7 +1932,15 @@ impl Notification {
     #[cfg(all(unix, not(target_os = "macos")))]
     #[cfg(all(feature = "async", feature = "zbus"))]
     pub async fn show_async(&self) -> Result<xdg::NotificationHandle> {
-        xdg::show_notification_async(self).await
+        let notification = self.prepare_async().await?;
+        let handle = if xdg::dry_run::enabled() {
+            let id = xdg::dry_run::send(&notification);
+            xdg::NotificationHandle::dry_run(id, notification.for_handle())
+        } else {
+            xdg::show_notification_async(&notification).await?
+        };
+        notification.shown(handle.id());
+        Ok(handle)
     }
 
     /// Sends Notification to D-Bus.
@@ -452,6 +1972,185 @@ impl Notification {
         windows::show_notification(self)
     }
 
+    /// Applies validation and merging to what is about to be sent.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) fn prepare(&self) -> Result<Cow<'_, Notification>> {
+        let capabilities = if self.needs_capabilities() {
+            xdg::strict::fetched(xdg::strict::capabilities(&self.bus, self.call_timeout))?
+        } else {
+            None
+        };
+        self.prepare_with(capabilities.as_deref().map(Vec::as_slice))
+    }
+
+    /// Async version of [`prepare()`](Self::prepare), fetching the capabilities without blocking the executor.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[cfg(all(feature = "async", feature = "zbus"))]
+    async fn prepare_async(&self) -> Result<Cow<'_, Notification>> {
+        let capabilities = if self.needs_capabilities() {
+            xdg::strict::fetched(
+                xdg::strict::capabilities_async(&self.bus, self.call_timeout).await,
+            )?
+        } else {
+            None
+        };
+        self.prepare_with(capabilities.as_deref().map(Vec::as_slice))
+    }
+
+    /// Whether preparing needs the capabilities of the server, never in a dry run since nothing is sent.
+    ///
+    /// They are fetched at most once per send and shared by every step of [`prepare_with()`](Self::prepare_with).
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn needs_capabilities(&self) -> bool {
+        let fallback = self.action_fallback.is_some() && !self.actions_to_send().is_empty();
+        !xdg::dry_run::enabled()
+            && (fallback
+                || self.has_icon_frames()
+                || self.hints_unique.contains_key(&reply_default_key())
+                || xdg::strict::enabled())
+    }
+
+    /// Whether [`animated_icon()`](Self::animated_icon) was set.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn has_icon_frames(&self) -> bool {
+        #[cfg(feature = "images")]
+        if !self.icon_frames.is_empty() {
+            return true;
+        }
+        false
+    }
+
+    /// [`prepare()`](Self::prepare) with the `capabilities` of the server, `None` if they are unknown.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn prepare_with(&self, capabilities: Option<&[String]>) -> Result<Cow<'_, Notification>> {
+        self.check_built()?;
+        let prepared = self.applied_action_fallback(capabilities);
+        let prepared = match prepared.applied_icon_frames(capabilities) {
+            Cow::Borrowed(_) => prepared,
+            Cow::Owned(reduced) => Cow::Owned(reduced),
+        };
+        let prepared = match prepared.applied_reply_default(capabilities) {
+            Cow::Borrowed(_) => prepared,
+            Cow::Owned(reduced) => Cow::Owned(reduced),
+        };
+        xdg::strict::check(&prepared, capabilities)?;
+        let prepared = match prepared.applied_timestamp() {
+            Cow::Borrowed(_) => prepared,
+            Cow::Owned(stamped) => Cow::Owned(stamped),
+        };
+        let checked = match prepared.checked_lengths()? {
+            Cow::Borrowed(_) => prepared,
+            Cow::Owned(checked) => Cow::Owned(checked),
+        };
+        Ok(match xdg::merge::prepare(&checked) {
+            Cow::Borrowed(_) => checked,
+            Cow::Owned(merged) => Cow::Owned(merged),
+        })
+    }
+
+    /// Applies [`with_action_fallback()`](Self::with_action_fallback) if the server can't show actions.
+    ///
+    /// If the capabilities are unknown the notification is left as it is.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn applied_action_fallback(&self, capabilities: Option<&[String]>) -> Cow<'_, Notification> {
+        let Some(suffix) = &self.action_fallback else {
+            return Cow::Borrowed(self);
+        };
+        if self.actions_to_send().is_empty() {
+            return Cow::Borrowed(self);
+        }
+        match capabilities {
+            Some(capabilities) if !capabilities.iter().any(|c| c == "actions") => {
+                Cow::Owned(self.without_actions(suffix))
+            }
+            _ => Cow::Borrowed(self),
+        }
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn without_actions(&self, body_suffix: &str) -> Notification {
+        let mut notification = self.clone();
+        notification.actions.clear();
+        if !notification.body.is_empty() {
+            notification.body.push('\n');
+        }
+        notification.body.push_str(body_suffix);
+        notification
+    }
+
+    /// Leaves out the frames of [`animated_icon()`](Self::animated_icon) unless the server can show `"icon-multi"`.
+    ///
+    /// If the capabilities are unknown only the first frame is sent, every server showing images understands that.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[cfg_attr(not(feature = "images"), allow(unused_variables))]
+    fn applied_icon_frames(&self, capabilities: Option<&[String]>) -> Cow<'_, Notification> {
+        #[cfg(feature = "images")]
+        if !self.icon_frames.is_empty()
+            && !capabilities.is_some_and(|capabilities| {
+                capabilities.iter().any(|c| c == "icon-multi")
+            })
+        {
+            let mut notification = self.clone();
+            notification.icon_frames.clear();
+            return Cow::Owned(notification);
+        }
+        Cow::Borrowed(self)
+    }
+
+    /// Leaves out the default text of [`inline_reply_with_default()`](Self::inline_reply_with_default)
+    /// unless the server advertises it, or if the capabilities are unknown.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn applied_reply_default(&self, capabilities: Option<&[String]>) -> Cow<'_, Notification> {
+        let key = reply_default_key();
+        if !self.hints_unique.contains_key(&key) {
+            return Cow::Borrowed(self);
+        }
+        match capabilities {
+            Some(capabilities) if capabilities.iter().any(|c| c == action::REPLY_DEFAULT_HINT) => {
+                Cow::Borrowed(self)
+            }
+            _ => {
+                let mut notification = self.clone();
+                notification.hints_unique.remove(&key);
+                Cow::Owned(notification)
+            }
+        }
+    }
+
+    /// Applies [`validate_lengths()`](Self::validate_lengths), if set.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn checked_lengths(&self) -> Result<Cow<'_, Notification>> {
+        let Some((max_summary, max_body)) = self.length_limits else {
+            return Ok(Cow::Borrowed(self));
+        };
+        let summary = strip_control_chars(&self.summary, false);
+        let body = strip_control_chars(&self.body, true);
+        check_length("summary", &summary, max_summary)?;
+        check_length("body", &body, max_body)?;
+        if let (Cow::Borrowed(_), Cow::Borrowed(_)) = (&summary, &body) {
+            return Ok(Cow::Borrowed(self));
+        }
+        let mut notification = self.clone();
+        notification.summary = summary.into_owned();
+        notification.body = body.into_owned();
+        Ok(Cow::Owned(notification))
+    }
+
+    /// Remembers the id this was shown with, for merging, closing by tag and a persisted id.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub(crate) fn shown(&self, id: u32) {
+        xdg::merge::record(self, id);
+        xdg::tags::record(self, id);
+        if let Some(path) = &self.persist_path {
+            match write_persisted_id(path, id) {
+                Err(error) if !xdg::quiet::is_quiet() => {
+                    log::warn!("failed to persist notification id to {:?}: {}", path, error);
+                }
+                _ => {}
+            }
+        }
+    }
+
     /// Wraps [`show()`] but prints notification to stdout.
     #[cfg(all(unix, not(target_os = "macos")))]
     #[deprecated = "this was never meant to be public API"]
@@ -482,7 +2181,23 @@ impl Default for Notification {
             actions: Vec::new(),
             timeout: Timeout::Default,
             bus: Default::default(),
-            id: None,
+            merge_window: None,
+            merge_prefix: None,
+            sound_fallback: false,
+            persist_path: None,
+            length_limits: None,
+            call_timeout: None,
+            action_fallback: None,
+            no_default_action: false,
+            timestamp_format: None,
+            unstamped_body: None,
+            unpaired_action: None,
+            invalid_bus: None,
+            #[cfg(feature = "images")]
+            icon_frames: Vec::new(),
+            #[cfg(all(feature = "http", feature = "images"))]
+            image_url_required: false,
+            id: ReplaceId::new(),
         }
     }
 
@@ -497,7 +2212,7 @@ impl Default for Notification {
             actions: Vec::new(),
             timeout: Timeout::Default,
             sound_name: Default::default(),
-            id: None,
+            id: ReplaceId::new(),
         }
     }
 
@@ -512,9 +2227,1086 @@ impl Default for Notification {
             actions: Vec::new(),
             timeout: Timeout::Default,
             sound_name: Default::default(),
-            id: None,
+            id: ReplaceId::new(),
             path_to_image: None,
             app_id: None,
+            desktop_entry: None,
+        }
+    }
+}
+
+/// Borrowed shape of a [`Notification`] when serialized.
+#[cfg(all(feature = "serde", unix, not(target_os = "macos")))]
+#[derive(serde::Serialize)]
+struct SerializedNotification<'a> {
+    appname: &'a str,
+    summary: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subtitle: Option<&'a str>,
+    body: &'a str,
+    icon: &'a str,
+    hints: Vec<&'a Hint>,
+    actions: Vec<&'a str>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    no_default_action: bool,
+    timeout: Timeout,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<u32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    sound_fallback: bool,
+}
+
+/// What [`Notification::show_webhook()`] posts.
+#[cfg(all(feature = "http", feature = "serde", unix, not(target_os = "macos")))]
+#[derive(serde::Serialize)]
+struct WebhookPayload<'a> {
+    text: &'a str,
+    appname: &'a str,
+    summary: &'a str,
+    body: &'a str,
+    urgency: Urgency,
+    icon: &'a str,
+}
+
+/// Owned shape of a [`Notification`] when deserialized, every field is optional.
+#[cfg(all(feature = "serde", unix, not(target_os = "macos")))]
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+struct DeserializedNotification {
+    appname: Option<String>,
+    summary: String,
+    subtitle: Option<String>,
+    body: String,
+    icon: String,
+    hints: Vec<Hint>,
+    actions: Vec<String>,
+    no_default_action: bool,
+    timeout: Timeout,
+    id: Option<u32>,
+    sound_fallback: bool,
+}
+
+#[cfg(all(feature = "serde", unix, not(target_os = "macos")))]
+impl serde::Serialize for Notification {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        SerializedNotification {
+            appname: &self.appname,
+            summary: &self.summary,
+            subtitle: self.subtitle.as_deref(),
+            body: &self.body,
+            icon: &self.icon,
+            hints: self.get_hints().collect(),
+            actions: Action::to_flat(&self.actions),
+            no_default_action: self.no_default_action,
+            timeout: self.timeout,
+            id: self.id.get(),
+            sound_fallback: self.sound_fallback,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(all(feature = "serde", unix, not(target_os = "macos")))]
+impl<'de> serde::Deserialize<'de> for Notification {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let de = DeserializedNotification::deserialize(deserializer)?;
+        let mut notification = Notification::new();
+        if let Some(appname) = de.appname {
+            notification.appname = appname;
+        }
+        notification.summary = de.summary;
+        notification.subtitle = de.subtitle;
+        notification.body = de.body;
+        notification.icon = de.icon;
+        for hint in de.hints {
+            notification.hint(hint);
+        }
+        notification.actions = Action::from_flat(de.actions);
+        notification.no_default_action = de.no_default_action;
+        notification.timeout = de.timeout;
+        notification.id = de.id.into();
+        notification.sound_fallback = de.sound_fallback;
+        Ok(notification)
+    }
+}
+
+#[cfg(all(test, unix, not(target_os = "macos")))]
+mod tests {
+    use super::*;
+
+    fn sent_sounds(notification: &Notification) -> Vec<Hint> {
+        let mut sounds = notification
+            .hints_to_send()
+            .filter(|hint| matches!(hint, Hint::SoundFile(_) | Hint::SoundName(_)))
+            .cloned()
+            .collect::<Vec<_>>();
+        sounds.sort_by_key(|hint| matches!(hint, Hint::SoundName(_)));
+        sounds
+    }
+
+    fn file() -> Hint {
+        Hint::SoundFile("/tmp/sound.oga".into())
+    }
+
+    fn name() -> Hint {
+        Hint::SoundName("message-new-instant".into())
+    }
+
+    #[test]
+    fn control_chars_are_stripped() {
+        let notification = Notification::new()
+            .summary("new\nfile\u{1b}[31m")
+            .body("line 1\n\tline 2\r\u{7}")
+            .validate_lengths(64, 64)
+            .finalize();
+        let prepared = notification.prepare().unwrap();
+        assert_eq!(prepared.summary, "newfile[31m");
+        assert_eq!(prepared.body, "line 1\n\tline 2");
+
+        let clean = Notification::new()
+            .summary("clean")
+            .validate_lengths(64, 64)
+            .finalize();
+        assert!(matches!(clean.prepare().unwrap(), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn lengths_are_counted_in_characters() {
+        let notification = Notification::new()
+            .summary("äöü")
+            .body("ß".repeat(10).as_str())
+            .validate_lengths(3, 10)
+            .finalize();
+        assert!(notification.prepare().is_ok());
+
+        let error = Notification::new()
+            .summary("äöüx")
+            .validate_lengths(3, 10)
+            .prepare()
+            .unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::FieldTooLong {
+                field: "summary",
+                length: 4,
+                max: 3
+            }
+        ));
+
+        let error = Notification::new()
+            .body(&"x".repeat(11))
+            .validate_lengths(3, 10)
+            .prepare()
+            .unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::FieldTooLong {
+                field: "body",
+                length: 11,
+                max: 10
+            }
+        ));
+    }
+
+    #[test]
+    fn timestamp_counts_towards_the_body_length() {
+        let error = Notification::new()
+            .body("123456")
+            .with_timestamp_format("%%%%")
+            .validate_lengths(64, 8)
+            .prepare()
+            .unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::FieldTooLong {
+                field: "body",
+                length: 9,
+                max: 8
+            }
+        ));
+
+        let notification = Notification::new()
+            .body("1234")
+            .with_timestamp_format("%%")
+            .finalize();
+        let prepared = notification.prepare().unwrap();
+        assert_eq!(prepared.body, "% 1234");
+        assert_eq!(prepared.body_at_send(), "% 1234");
+        assert_eq!(prepared.for_handle().body, "1234");
+    }
+
+    #[test]
+    fn identity_sets_appname_and_desktop_entry() {
+        let mut notification = Notification::new();
+        notification
+            .desktop_entry("org.mozilla.firefox")
+            .identity("Firefox", "firefox");
+
+        assert_eq!(notification.appname, "Firefox");
+        assert_eq!(
+            notification.get_hints().collect::<Vec<_>>(),
+            [&Hint::DesktopEntry("firefox".into())]
+        );
+    }
+
+    #[test]
+    fn stack_tag_sets_both_hints() {
+        let notification = Notification::new()
+            .stack_tag("volume")
+            .stack_tag("brightness")
+            .finalize();
+
+        assert_eq!(
+            notification.get_hints().cloned().collect::<HashSet<_>>(),
+            HashSet::from([
+                Hint::Custom("x-dunst-stack-tag".into(), "brightness".into()),
+                Hint::Custom(
+                    "x-canonical-private-synchronous".into(),
+                    "brightness".into()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn presentation_rules() {
+        use xdg::Presentation::*;
+        let keeping = ["body", "persistence"];
+        let banners = ["body"];
+        let predict = |notification: &mut Notification| {
+            (
+                notification.predicted_presentation(&keeping),
+                notification.predicted_presentation(&banners),
+            )
+        };
+
+        assert_eq!(predict(&mut Notification::new()), (Both, Banner));
+        assert_eq!(
+            predict(Notification::new().urgency(Urgency::Critical)),
+            (Both, Banner)
+        );
+        assert_eq!(
+            predict(Notification::new().urgency(Urgency::Low)),
+            (Persistent, Banner)
+        );
+        assert_eq!(
+            predict(Notification::new().hint(Hint::Transient(true))),
+            (Banner, Banner)
+        );
+        assert_eq!(
+            predict(
+                Notification::new()
+                    .urgency(Urgency::Low)
+                    .hint(Hint::Transient(true))
+            ),
+            (Banner, Banner)
+        );
+        assert_eq!(
+            predict(Notification::new().hint(Hint::Transient(false))),
+            (Both, Banner)
+        );
+    }
+
+    #[test]
+    fn presets_can_be_overridden() {
+        let error = Notification::error("Sync failed", "offline");
+        assert_eq!(error.summary, "Sync failed");
+        assert_eq!(error.body, "offline");
+        assert_eq!(error.icon, "dialog-error");
+        assert!(error.hints.contains(&Hint::Urgency(Urgency::Critical)));
+        assert!(error.hints.contains(&Hint::Category("im.error".into())));
+
+        let calmer = Notification::error("Sync failed", "offline")
+            .urgency(Urgency::Normal)
+            .icon("network-offline")
+            .hint(Hint::Category("network.error".into()))
+            .finalize();
+        assert_eq!(calmer.icon, "network-offline");
+        let mut hints = calmer.get_hints().collect::<Vec<_>>();
+        hints.sort_by_key(|hint| format!("{:?}", hint));
+        assert_eq!(
+            hints,
+            [
+                &Hint::Category("network.error".into()),
+                &Hint::Urgency(Urgency::Normal)
+            ]
+        );
+
+        let warning = Notification::warning("", "");
+        assert_eq!(warning.icon, "dialog-warning");
+        assert!(warning
+            .hints
+            .contains(&Hint::Category("x-notify-rust.warning".into())));
+        let info = Notification::info("", "");
+        assert!(info.hints.contains(&Hint::Urgency(Urgency::Low)));
+        assert!(info
+            .hints
+            .contains(&Hint::Category("x-notify-rust.info".into())));
+    }
+
+    #[test]
+    fn build_checks_without_sending() {
+        let built = Notification::new()
+            .summary("Line\u{7}")
+            .body("a\tb\u{1b}")
+            .validate_lengths(4, 3)
+            .build()
+            .unwrap();
+        assert_eq!(built.summary, "Line");
+        assert_eq!(built.body, "a\tb");
+
+        let error = Notification::new()
+            .summary("too long")
+            .validate_lengths(3, 10)
+            .build()
+            .unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::FieldTooLong { .. }));
+
+        let error = Notification::new().summary(" ").build().unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::EmptySummary));
+        // show() keeps accepting it
+        assert!(Notification::new().prepare().is_ok());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn build_checks_actions_and_bus() {
+        let mut unpaired = Notification::new();
+        unpaired
+            .summary("Delete?")
+            .actions(vec!["delete".into(), "Delete".into(), "keep".into()]);
+        let error = unpaired.build().unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::UnpairedAction { key } if key == "keep"));
+        assert!(unpaired.prepare().is_err());
+
+        unpaired.actions(vec!["keep".into(), "Keep".into()]);
+        assert!(unpaired.build().is_ok());
+
+        let error = Notification::at_bus("not a bus name")
+            .summary("Hi")
+            .build()
+            .unwrap_err();
+        assert!(
+            matches!(error.kind(), ErrorKind::InvalidBus { sub_bus } if sub_bus == "not a bus name")
+        );
+        assert!(Notification::at_bus("not a bus name").prepare().is_err());
+    }
+
+    #[test]
+    fn unlimited_by_default() {
+        let notification = Notification::new().summary("\u{7}").finalize();
+        assert_eq!(notification.prepare().unwrap().summary, "\u{7}");
+    }
+
+    #[test]
+    fn negotiate_strips_what_is_not_understood() {
+        let mut notification = Notification::new();
+        notification
+            .summary("Alice")
+            .body("<b>lunch</b> at <a href=\"https://example.com\">Luigi's</a> <img src=\"pizza.png\"/>")
+            .action("reply", "Reply")
+            .inline_reply_with_default("Reply", "Write a reply…", "yes!")
+            .sound_name("message-new-instant")
+            .hint(Hint::Transient(true))
+            .hint(Hint::Category("im.received".into()));
+
+        let everything = [
+            "actions",
+            "body",
+            "body-markup",
+            "body-hyperlinks",
+            "body-images",
+            "sound",
+            "persistence",
+            action::REPLY_DEFAULT_HINT,
+        ];
+        let negotiated = notification.negotiate(&everything);
+        assert_eq!(negotiated.body, notification.body);
+        assert_eq!(negotiated.actions, notification.actions);
+        assert_eq!(
+            negotiated.get_hints().collect::<HashSet<_>>(),
+            notification.get_hints().collect::<HashSet<_>>()
+        );
+
+        let negotiated = notification.negotiate(&["actions", "body", "body-markup", "sound"]);
+        assert_eq!(negotiated.body, "<b>lunch</b> at Luigi's ");
+        assert_eq!(
+            negotiated.get_hints().collect::<HashSet<_>>(),
+            HashSet::from([
+                &Hint::SoundName("message-new-instant".into()),
+                &Hint::Category("im.received".into()),
+                &Hint::Custom(
+                    action::REPLY_PLACEHOLDER_HINT.into(),
+                    "Write a reply…".into()
+                ),
+            ])
+        );
+
+        let negotiated = notification.negotiate(&["body"]);
+        assert_eq!(negotiated.summary, "Alice");
+        assert_eq!(negotiated.body, "lunch at Luigi's ");
+        assert!(negotiated.actions.is_empty());
+
+        notification.with_action_fallback("reply in the app");
+        let negotiated = notification.negotiate(&["body"]);
+        assert_eq!(negotiated.body, "lunch at Luigi's \nreply in the app");
+        assert!(negotiated.actions.is_empty());
+    }
+
+    #[test]
+    fn unsupported_hints_by_capability() {
+        let notification = Notification::new()
+            .hint(Hint::ActionIcons(true))
+            .hint(Hint::ImagePath("/tmp/image.png".into()))
+            .hint(name())
+            .hint(Hint::Transient(true))
+            .hint(Hint::Category("email".into()))
+            .hint(Hint::Custom("x-vendor".into(), "value".into()))
+            .finalize();
+
+        let mut unsupported = notification.unsupported_hints(&["body", "icon-multi"]);
+        unsupported.sort_by_key(|hint| format!("{:?}", hint));
+        assert_eq!(
+            unsupported,
+            [&Hint::ActionIcons(true), &name(), &Hint::Transient(true)]
+        );
+
+        let everything = ["action-icons", "icon-static", "sound", "persistence"];
+        assert!(notification.unsupported_hints(&everything).is_empty());
+        assert_eq!(
+            notification.unsupported_hints(&Vec::<String>::new()).len(),
+            4
+        );
+    }
+
+    #[cfg(feature = "zbus")]
+    #[test]
+    fn notify_args_round_trip() {
+        let mut sent = Notification::new();
+        sent.appname("round-trip")
+            .id(7)
+            .icon("dialog-information")
+            .summary("summary")
+            .body("body")
+            .action("default", "Open")
+            .timeout(Timeout::Milliseconds(1500))
+            .urgency(Urgency::Critical)
+            .hint(Hint::ActionIcons(true))
+            .hint(Hint::Category("im.received".into()))
+            .hint(Hint::DesktopEntry("firefox".into()))
+            .hint(Hint::ImagePath("/tmp/image.png".into()))
+            .hint(Hint::Resident(true))
+            .hint(Hint::SoundName("message-new-instant".into()))
+            .hint(Hint::SuppressSound(false))
+            .hint(Hint::Transient(false))
+            .hint(Hint::X(-10))
+            .hint(Hint::Y(20))
+            .hint(Hint::Custom("x-custom".into(), "value".into()))
+            .hint(Hint::CustomInt("x-custom-int".into(), 42));
+        #[cfg(feature = "images")]
+        sent.hint(Hint::ImageData(
+            Image::from_rgb(2, 1, vec![0, 1, 2, 3, 4, 5]).unwrap(),
+        ));
+
+        let hints = crate::hints::hints_to_map(&sent)
+            .into_iter()
+            .map(|(key, value)| (key, zbus::zvariant::OwnedValue::try_from(value).unwrap()))
+            .collect::<HashMap<_, _>>();
+        let received = Notification::from_notify_args(
+            &sent.appname,
+            sent.id.raw(),
+            &sent.icon,
+            &sent.summary,
+            &sent.body,
+            Action::to_flat(&sent.actions)
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            &hints,
+            i32::from(sent.timeout),
+        );
+
+        assert_eq!(received.appname, sent.appname);
+        assert_eq!(received.id, ReplaceId::existing(7));
+        assert_eq!(received.icon, sent.icon);
+        assert_eq!(received.summary, sent.summary);
+        assert_eq!(received.body, sent.body);
+        assert_eq!(received.actions, sent.actions);
+        assert_eq!(received.timeout, sent.timeout);
+        assert_eq!(
+            received.get_hints().collect::<HashSet<_>>(),
+            sent.get_hints().collect::<HashSet<_>>()
+        );
+    }
+
+    #[cfg(feature = "zbus")]
+    #[test]
+    fn unsupported_notify_hints_are_left_out() {
+        use zbus::zvariant::{OwnedValue, Value};
+        let hints = HashMap::from([
+            ("category", OwnedValue::from(7u32)),
+            ("x-float", OwnedValue::from(0.5f64)),
+            ("resident", Value::Bool(true).try_into().unwrap()),
+        ]);
+
+        let received = Notification::from_notify_args("", 0, "", "", "", vec![], &hints, -1);
+        assert_eq!(received.id, ReplaceId::new());
+        assert_eq!(
+            received.get_hints().collect::<Vec<_>>(),
+            [&Hint::Resident(true)]
+        );
+    }
+
+    #[test]
+    fn inline_reply_replaces_reply_field() {
+        let notification = Notification::new()
+            .action("default", "Open")
+            .inline_reply("Reply", "Write a reply…")
+            .inline_reply_with_default("Answer", "Answer…", "On my way!")
+            .finalize();
+
+        assert_eq!(
+            notification.actions,
+            [
+                Action::new("default", "Open"),
+                Action::new("inline-reply", "Answer")
+            ]
+        );
+        let hints = notification.get_hints().collect::<HashSet<_>>();
+        assert!(hints.contains(&Hint::Custom(
+            "x-kde-reply-placeholder-text".into(),
+            "Answer…".into()
+        )));
+        assert!(hints.contains(&Hint::Custom(
+            "x-kde-reply-default-text".into(),
+            "On my way!".into()
+        )));
+    }
+
+    #[test]
+    fn copied_hints_replace_singletons() {
+        let template = Notification::new()
+            .urgency(Urgency::Critical)
+            .hint(Hint::Category("device.error".into()))
+            .hint(Hint::Custom("x-shared".into(), "template".into()))
+            .sound_with_fallback("bell", "/tmp/bell.oga")
+            .finalize();
+        let mut notification = Notification::new();
+        notification
+            .urgency(Urgency::Low)
+            .hint(Hint::Resident(true))
+            .hint(Hint::Custom("x-own".into(), "own".into()))
+            .hint(Hint::Custom("x-shared".into(), "own".into()))
+            .copy_hints_from(&template);
+
+        let hints = notification.get_hints().cloned().collect::<HashSet<_>>();
+        assert_eq!(
+            hints,
+            HashSet::from([
+                Hint::Urgency(Urgency::Critical),
+                Hint::Category("device.error".into()),
+                Hint::Resident(true),
+                Hint::SoundName("bell".into()),
+                Hint::SoundFile("/tmp/bell.oga".into()),
+                Hint::Custom("x-own".into(), "own".into()),
+                Hint::Custom("x-shared".into(), "template".into()),
+            ])
+        );
+        assert!(notification.sound_fallback);
+    }
+
+    #[test]
+    fn on_monitor_points_at_centre() {
+        let mut notification = Notification::new();
+        notification
+            .hint(Hint::X(5))
+            .on_monitor(1920, -1080, 2560, 1440);
+        let position = notification
+            .get_hints()
+            .filter(|hint| matches!(hint, Hint::X(_) | Hint::Y(_)))
+            .cloned()
+            .collect::<HashSet<_>>();
+        assert_eq!(position, HashSet::from([Hint::X(3200), Hint::Y(-360)]));
+
+        notification.on_monitor(i32::MAX, 0, u32::MAX, 0);
+        assert!(notification
+            .get_hints()
+            .any(|hint| *hint == Hint::X(i32::MAX)));
+    }
+
+    #[test]
+    fn no_default_action_is_never_sent() {
+        let mut notification = Notification::new();
+        notification
+            .action("default", "Open")
+            .action("clean", "Clean up");
+        assert_eq!(
+            notification.actions_to_send(),
+            ["default", "Open", "clean", "Clean up"]
+        );
+
+        notification.no_default_action().action("default", "Open");
+        assert_eq!(notification.actions_to_send(), ["clean", "Clean up"]);
+        assert_eq!(
+            notification.action_kind("default"),
+            Some(ActionKind::Default)
+        );
+    }
+
+    #[test]
+    fn bypass_dnd_is_critical() {
+        let notification = Notification::new()
+            .urgency(Urgency::Low)
+            .bypass_dnd()
+            .finalize();
+        assert_eq!(
+            notification.get_hints().collect::<Vec<_>>(),
+            [&Hint::Urgency(Urgency::Critical)]
+        );
+    }
+
+    #[test]
+    fn action_kinds() {
+        let notification = Notification::new()
+            .action("default", "Open")
+            .action("inline-reply", "Reply")
+            .action("archive", "default")
+            .finalize();
+
+        assert_eq!(
+            notification.action_kind("default"),
+            Some(ActionKind::Default)
+        );
+        assert_eq!(
+            notification.action_kind("inline-reply"),
+            Some(ActionKind::InlineReply)
+        );
+        assert_eq!(
+            notification.action_kind("archive"),
+            Some(ActionKind::Button)
+        );
+        // labels are not keys
+        assert_eq!(notification.action_kind("Open"), None);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn flat_actions_are_paired_up() {
+        let notification = Notification::new()
+            .actions(vec!["default".into(), "Open".into(), "dangling".into()])
+            .finalize();
+
+        assert_eq!(notification.actions, [Action::new("default", "Open")]);
+        assert_eq!(Action::to_flat(&notification.actions), ["default", "Open"]);
+    }
+
+    #[test]
+    fn hints_replace_their_kind() {
+        let mut notification = Notification::new();
+        notification
+            .urgency(Urgency::Low)
+            .hint(Hint::Category("a".into()))
+            .with_hints([
+                Hint::Urgency(Urgency::Critical),
+                Hint::Custom("key".into(), "first".into()),
+                Hint::Custom("key".into(), "second".into()),
+                Hint::CustomInt("key".into(), 1),
+                Hint::Category("b".into()),
+            ]);
+
+        let hints = notification.get_hints().cloned().collect::<HashSet<_>>();
+        assert_eq!(
+            hints,
+            HashSet::from([
+                Hint::Urgency(Urgency::Critical),
+                Hint::Category("b".into()),
+                Hint::Custom("key".into(), "second".into()),
+                Hint::CustomInt("key".into(), 1),
+            ])
+        );
+    }
+
+    #[test]
+    fn persisted_ids() {
+        let path = std::env::temp_dir().join(format!("notify-rust-{}.id", std::process::id()));
+
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(
+            Notification::new().replace_from_persisted(&path).id.get(),
+            None
+        );
+
+        write_persisted_id(&path, 42).unwrap();
+        assert_eq!(
+            Notification::new().replace_from_persisted(&path).id.get(),
+            Some(42)
+        );
+
+        for corrupt in ["", "0", "-1", "forty two", "4294967296"] {
+            std::fs::write(&path, corrupt).unwrap();
+            assert_eq!(
+                Notification::new().replace_from_persisted(&path).id.get(),
+                None
+            );
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn sound_name_alone() {
+        assert_eq!(sent_sounds(Notification::new().hint(name())), [name()]);
+    }
+
+    #[test]
+    fn sound_file_alone() {
+        assert_eq!(sent_sounds(Notification::new().hint(file())), [file()]);
+    }
+
+    #[test]
+    fn sound_file_beats_sound_name() {
+        let notification = Notification::new().hint(name()).hint(file()).finalize();
+        assert_eq!(sent_sounds(&notification), [file()]);
+    }
+
+    #[test]
+    fn fallback_sends_both() {
+        let notification = Notification::new()
+            .sound_with_fallback("message-new-instant", "/tmp/sound.oga")
+            .finalize();
+        assert_eq!(sent_sounds(&notification), [file(), name()]);
+    }
+
+    #[test]
+    fn suppress_sound_beats_everything() {
+        let notification = Notification::new()
+            .sound_with_fallback("message-new-instant", "/tmp/sound.oga")
+            .hint(Hint::SuppressSound(true))
+            .finalize();
+        assert_eq!(sent_sounds(&notification), []);
+        assert!(notification
+            .hints_to_send()
+            .any(|hint| hint == &Hint::SuppressSound(true)));
+
+        let notification = Notification::new()
+            .hint(file())
+            .hint(Hint::SuppressSound(false))
+            .finalize();
+        assert_eq!(sent_sounds(&notification), [file()]);
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn hero_image_leaves_app_icon() {
+        let hero = Image::from_rgb(2, 2, vec![0; 2 * 2 * 3]).unwrap();
+        let mut notification = Notification::new();
+        notification
+            .image_path("/tmp/old.png")
+            .app_icon("music-player")
+            .hero_image(hero.clone());
+
+        assert_eq!(notification.icon, "music-player");
+        let images = notification
+            .hints_to_send()
+            .filter(|hint| hint.is_image_data() || matches!(hint, Hint::ImagePath(_)))
+            .collect::<Vec<_>>();
+        assert_eq!(images, [&Hint::ImageData(hero)]);
+    }
+
+    #[test]
+    fn effective_icon_follows_precedence() {
+        let mut notification = Notification::new();
+        notification.icon("").image_path("");
+        assert_eq!(notification.effective_icon(), xdg::IconSource::None);
+
+        notification.app_icon("music-player");
+        assert_eq!(
+            notification.effective_icon(),
+            xdg::IconSource::AppIcon("music-player")
+        );
+        notification.image_path("/tmp/cover.png");
+        assert_eq!(
+            notification.effective_icon(),
+            xdg::IconSource::ImagePath("/tmp/cover.png")
+        );
+
+        #[cfg(feature = "images")]
+        {
+            notification.image_data(Image::from_rgb(1, 1, vec![0; 3]).unwrap());
+            assert_eq!(notification.effective_icon(), xdg::IconSource::ImageData);
         }
     }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn thumbnail_takes_precedence() {
+        let mut notification = Notification::new();
+        notification
+            .image_path("/tmp/old.png")
+            .action("default", "Show")
+            .action("delete", "Delete")
+            .with_thumbnail(&image::DynamicImage::new_rgb8(1920, 1080), "Open")
+            .unwrap();
+
+        let images = notification
+            .hints_to_send()
+            .filter(|hint| hint.is_image_data() || matches!(hint, Hint::ImagePath(_)))
+            .collect::<Vec<_>>();
+        let expected = Image::from_rgb(512, 288, vec![0; 512 * 288 * 3]).unwrap();
+        assert_eq!(images, [&Hint::ImageData(expected)]);
+        assert_eq!(
+            notification
+                .actions
+                .iter()
+                .map(|action| (action.key.as_str(), action.label.as_str()))
+                .collect::<Vec<_>>(),
+            [("delete", "Delete"), ("default", "Open")]
+        );
+        assert_eq!(notification.timeout, Timeout::Milliseconds(10_000));
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn animated_icon_replaces_image() {
+        let frame = |value| Image::from_rgb(1, 1, vec![value; 3]).unwrap();
+        let images = |notification: &Notification| {
+            notification
+                .hints_to_send()
+                .filter(|hint| hint.is_image_data())
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        let mut notification = Notification::new();
+        notification
+            .image_data(frame(0))
+            .animated_icon(vec![frame(1), frame(2)]);
+        assert_eq!(images(&notification), [Hint::ImageData(frame(1))]);
+        assert_eq!(notification.icon_frames, [frame(1), frame(2)]);
+
+        let multi = ["icon-multi".to_owned()];
+        let stat = ["icon-static".to_owned()];
+        assert!(matches!(
+            notification.applied_icon_frames(Some(&multi)),
+            Cow::Borrowed(_)
+        ));
+        let reduced = notification.applied_icon_frames(Some(&stat));
+        assert_eq!(images(&reduced), [Hint::ImageData(frame(1))]);
+        assert!(reduced.icon_frames.is_empty());
+        assert!(notification
+            .applied_icon_frames(None)
+            .icon_frames
+            .is_empty());
+
+        notification.image_data(frame(3));
+        assert_eq!(images(&notification), [Hint::ImageData(frame(3))]);
+        assert!(notification.icon_frames.is_empty());
+        notification.animated_icon(vec![]);
+        assert!(images(&notification).is_empty());
+    }
+
+    #[cfg(all(feature = "http", feature = "images"))]
+    #[async_std::test]
+    async fn image_url_falls_back_to_icon() {
+        let png = {
+            let mut bytes = std::io::Cursor::new(Vec::new());
+            image::DynamicImage::new_rgb8(4, 4)
+                .write_to(&mut bytes, image::ImageFormat::Png)
+                .unwrap();
+            bytes.into_inner()
+        };
+        let has_image = |notification: &Notification| {
+            notification
+                .hints_to_send()
+                .any(|hint| matches!(hint, Hint::ImageData(_)))
+        };
+
+        let mut notification = Notification::new();
+        notification
+            .icon("audio-x-generic")
+            .image_url(&crate::http::serve_once("404 Not Found", vec![]).0)
+            .await
+            .unwrap();
+        assert!(!has_image(&notification));
+        assert_eq!(notification.icon, "audio-x-generic");
+
+        notification.with_image_url_required(true);
+        let error = notification
+            .image_url(&crate::http::serve_once("404 Not Found", vec![]).0)
+            .await
+            .unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Http(_)));
+        let error = notification
+            .image_url(&crate::http::serve_once("200 OK", b"<html>".to_vec()).0)
+            .await
+            .unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Image(_)));
+        let huge = vec![0; crate::image::MAX_ENCODED_LEN * 2];
+        let error = notification
+            .image_url(&crate::http::serve_once("200 OK", huge).0)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::Image(crate::image::ImageError::TooBig)
+        ));
+        assert!(!has_image(&notification));
+
+        notification
+            .image_url(&crate::http::serve_once("200 OK", png).0)
+            .await
+            .unwrap();
+        assert!(has_image(&notification));
+    }
+}
+
+#[cfg(all(test, feature = "serde", unix, not(target_os = "macos")))]
+mod serde_tests {
+    use super::*;
+
+    fn assert_round_trip(notification: &Notification) -> Notification {
+        let json = notification.to_json().unwrap();
+        let parsed = Notification::from_json(&json).unwrap();
+
+        assert_eq!(parsed.appname, notification.appname);
+        assert_eq!(parsed.summary, notification.summary);
+        assert_eq!(parsed.subtitle, notification.subtitle);
+        assert_eq!(parsed.body, notification.body);
+        assert_eq!(parsed.icon, notification.icon);
+        assert_eq!(parsed.hints, notification.hints);
+        assert_eq!(parsed.hints_unique, notification.hints_unique);
+        assert_eq!(parsed.actions, notification.actions);
+        assert_eq!(parsed.no_default_action, notification.no_default_action);
+        assert_eq!(parsed.timeout, notification.timeout);
+        assert_eq!(parsed.id, notification.id);
+        assert_eq!(parsed.sound_fallback, notification.sound_fallback);
+        parsed
+    }
+
+    #[cfg(feature = "http")]
+    #[async_std::test]
+    async fn webhook_posts_json() {
+        let (url, server) = crate::http::serve_once("200 OK", vec![]);
+        Notification::new()
+            .appname("backup")
+            .summary("Backup failed")
+            .body("disk full")
+            .urgency(Urgency::Critical)
+            .show_webhook(&url)
+            .await
+            .unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&server.join().unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "text": "Backup failed\ndisk full",
+                "appname": "backup",
+                "summary": "Backup failed",
+                "body": "disk full",
+                "urgency": "critical",
+                "icon": "",
+            })
+        );
+
+        let (url, _) = crate::http::serve_once("500 Internal Server Error", vec![]);
+        let error = Notification::new().show_webhook(&url).await.unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Http(_)));
+        assert!(error.to_string().contains("500"), "{}", error);
+    }
+
+    #[test]
+    fn round_trip_default() {
+        assert_round_trip(&Notification::new());
+    }
+
+    #[test]
+    fn round_trip_everything() {
+        assert_round_trip(
+            Notification::new()
+                .appname("thunderbird")
+                .summary("Category:email")
+                .subtitle("subtitle")
+                .body("body with <b>markup</b>")
+                .icon("thunderbird")
+                .hint(Hint::ActionIcons(true))
+                .hint(Hint::Category("email".into()))
+                .hint(Hint::DesktopEntry("thunderbird".into()))
+                .hint(Hint::ImagePath("/tmp/image.png".into()))
+                .hint(Hint::Resident(true))
+                .hint(Hint::SoundFile("/tmp/sound.ogg".into()))
+                .hint(Hint::SoundName("message-new-email".into()))
+                .hint(Hint::SuppressSound(false))
+                .hint(Hint::Transient(false))
+                .hint(Hint::X(10))
+                .hint(Hint::Y(-10))
+                .hint(Hint::Urgency(Urgency::Critical))
+                .hint(Hint::Custom("foo".into(), "bar".into()))
+                .hint(Hint::CustomInt("value".into(), 42))
+                .action("default", "Open")
+                .timeout(Timeout::Never)
+                .id(7),
+        );
+        assert_round_trip(Notification::new().timeout(Timeout::Milliseconds(1234)));
+        assert_round_trip(Notification::new().sound_with_fallback("bell", "/tmp/bell.oga"));
+
+        // the default action is kept, only sending leaves it out
+        let parsed = assert_round_trip(
+            Notification::new()
+                .action("default", "Open")
+                .action("clean", "Clean up")
+                .no_default_action(),
+        );
+        assert_eq!(parsed.actions_to_send(), ["clean", "Clean up"]);
+    }
+
+    #[test]
+    #[cfg(feature = "images")]
+    fn round_trip_image_data() {
+        let image = Image::from_rgba(2, 1, vec![0, 1, 2, 3, 255, 254, 253, 252]).unwrap();
+        let parsed = assert_round_trip(Notification::new().image_data(image.clone()));
+        assert!(parsed.hints.contains(&Hint::ImageData(image)));
+    }
+
+    #[test]
+    #[cfg(feature = "images")]
+    fn reject_image_data_of_wrong_size() {
+        let json = r#"{"hints": [{"image-data": {"width": 2, "height": 2, "alpha": false, "bits_per_sample": 8, "channels": 3, "data": "AAAA"}}]}"#;
+        assert!(Notification::from_json(json).is_err());
+    }
+
+    #[test]
+    fn hints_use_spec_names() {
+        let json = Notification::new()
+            .hint(Hint::DesktopEntry("firefox".into()))
+            .to_json()
+            .unwrap();
+        assert!(json.contains(r#""hints":[{"desktop-entry":"firefox"}]"#));
+    }
+
+    #[test]
+    fn missing_fields_are_defaults() {
+        let notification = Notification::from_json(r#"{"summary": "hi"}"#).unwrap();
+        assert_eq!(notification.summary, "hi");
+        assert_eq!(notification.appname, Notification::new().appname);
+        assert_eq!(notification.timeout, Timeout::Default);
+        assert!(notification.hints.is_empty());
+    }
+
+    #[test]
+    fn custom_hints_are_deduplicated() {
+        let notification = Notification::from_json(
+            r#"{"hints": [{"custom": ["foo", "bar1"]}, {"custom": ["foo", "bar2"]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            notification.get_hints().collect::<Vec<_>>(),
+            vec![&Hint::Custom("foo".into(), "bar2".into())]
+        );
+    }
 }
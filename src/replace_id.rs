@@ -0,0 +1,60 @@
+use std::num::NonZeroU32;
+
+/// Which notification a new one replaces, see [`Notification::id()`](crate::Notification::id).
+///
+/// The specification sends `0` as `replaces_id` for a new notification, so `0` can never be the id of an existing one.
+/// `ReplaceId` keeps that rule in one place: an id of `0` always means a new notification.
+/// ```
+/// # use notify_rust::ReplaceId;
+/// assert_eq!(ReplaceId::new().get(), None);
+/// assert_eq!(ReplaceId::existing(7).get(), Some(7));
+/// assert_eq!(ReplaceId::existing(0), ReplaceId::new());
+/// assert_eq!(ReplaceId::from(Some(7)).raw(), 7);
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ReplaceId(Option<NonZeroU32>);
+
+impl ReplaceId {
+    /// Show a new notification.
+    pub const fn new() -> Self {
+        ReplaceId(None)
+    }
+
+    /// Replace the notification with `id`, or show a new one if `id` is `0`.
+    pub fn existing(id: u32) -> Self {
+        ReplaceId(NonZeroU32::new(id))
+    }
+
+    /// The id of the notification to replace, `None` for a new one.
+    pub fn get(self) -> Option<u32> {
+        self.0.map(NonZeroU32::get)
+    }
+
+    /// Whether this shows a new notification.
+    pub fn is_new(self) -> bool {
+        self.0.is_none()
+    }
+
+    /// The `replaces_id` as sent to the server, `0` for a new notification.
+    pub fn raw(self) -> u32 {
+        self.get().unwrap_or(0)
+    }
+}
+
+impl From<u32> for ReplaceId {
+    fn from(id: u32) -> Self {
+        ReplaceId::existing(id)
+    }
+}
+
+impl From<Option<u32>> for ReplaceId {
+    fn from(id: Option<u32>) -> Self {
+        id.map_or_else(ReplaceId::new, ReplaceId::existing)
+    }
+}
+
+impl From<ReplaceId> for Option<u32> {
+    fn from(id: ReplaceId) -> Self {
+        id.get()
+    }
+}
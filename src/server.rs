@@ -20,7 +20,7 @@ use dbus::{
     Path,
 };
 
-use super::{Hint, Notification, Timeout};
+use super::{Action, Hint, Notification, Timeout};
 use crate::xdg::{NOTIFICATION_NAMESPACE, NOTIFICATION_OBJECTPATH};
 
 static DBUS_ERROR_FAILED: &str = "org.freedesktop.DBus.Error.Failed";
@@ -165,7 +165,7 @@ where
                 icon,
                 summary,
                 body,
-                actions,
+                actions: Action::from_flat(actions),
                 hints: hints_from_variants(&hints),
                 timeout: Timeout::from(timeout),
                 id: if replaces_id == 0 {
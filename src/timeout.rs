@@ -11,6 +11,11 @@ use std::{num::ParseIntError, str::FromStr, time::Duration};
 /// assert_eq!("42".parse(), Ok(Timeout::Milliseconds(42)));
 /// ```
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    all(feature = "serde", unix, not(target_os = "macos")),
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum Timeout {
     /// Expires according to server default.
     ///
@@ -32,6 +37,14 @@ fn timeout_from_i32() {
     assert_eq!(Timeout::from(0), Timeout::Never);
 }
 
+#[test]
+fn timeout_into_i32() {
+    assert_eq!(i32::from(Timeout::Default), -1);
+    assert_eq!(i32::from(Timeout::Never), 0);
+    assert_eq!(i32::from(Timeout::Milliseconds(234)), 234);
+    assert_eq!(i32::from(Timeout::Milliseconds(u32::MAX)), i32::MAX);
+}
+
 impl From<i32> for Timeout {
     fn from(int: i32) -> Timeout {
         use std::cmp::Ordering::*;
@@ -60,7 +73,8 @@ impl From<Timeout> for i32 {
         match timeout {
             Timeout::Default => -1,
             Timeout::Never => 0,
-            Timeout::Milliseconds(ms) => ms as i32,
+            // beyond `i32::MAX` the server would read a negative number, which means its default
+            Timeout::Milliseconds(ms) => i32::try_from(ms).unwrap_or(i32::MAX),
         }
     }
 }
@@ -77,6 +91,7 @@ impl FromStr for Timeout {
     }
 }
 
+#[allow(dead_code)]
 pub struct TimeoutMessage(Timeout);
 
 impl From<Timeout> for TimeoutMessage {
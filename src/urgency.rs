@@ -25,6 +25,11 @@ use crate::error::ErrorKind;
 /// ```
 ///
 #[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+#[cfg_attr(
+    all(feature = "serde", unix, not(target_os = "macos")),
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum Urgency {
     /// The behavior for `Low` urgency depends on the notification server.
     Low = 0,
@@ -22,8 +22,11 @@ pub(crate) fn show_notification(notification: &Notification) -> Result<()> {
         }
     };
 
-    let powershell_app_id = &Toast::POWERSHELL_APP_ID.to_string();
-    let app_id = &notification.app_id.as_ref().unwrap_or(powershell_app_id);
+    let app_id = notification
+        .app_id
+        .as_deref()
+        .or(notification.desktop_entry.as_deref())
+        .unwrap_or(Toast::POWERSHELL_APP_ID);
     let mut toast = Toast::new(app_id)
         .title(&notification.summary)
         .text1(notification.subtitle.as_ref().map_or("", AsRef::as_ref)) // subtitle
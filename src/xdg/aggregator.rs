@@ -0,0 +1,263 @@
+//! Coalesces bursts of notifications into one, see [`NotificationAggregator`].
+
+use std::time::{Duration, Instant};
+
+use crate::{error::*, notification::Notification, replace_id::ReplaceId, xdg::NotificationHandle};
+
+/// Collects notifications and shows a single summary of them instead.
+///
+/// The window starts with the first notification [added](Self::add) after a flush.
+/// Once it is over [`flush_if_due()`](Self::flush_if_due) shows one notification,
+/// whose summary is the [`summary_format()`](Self::summary_format) with `{count}` replaced,
+/// and whose body lists every collected notification in the [`item_format()`](Self::item_format).
+/// Appname, icon and hints are taken from the first collected notification, actions are left out.
+/// A single collected notification is shown as it is.
+///
+/// There is no timer, call [`flush_if_due()`](Self::flush_if_due) from your event loop
+/// and [`flush()`](Self::flush) before shutting down.
+///
+/// ```no_run
+/// # use notify_rust::*;
+/// # use std::time::Duration;
+/// # fn incoming_messages() -> Vec<(String, String)> { vec![] }
+/// let mut aggregator = NotificationAggregator::new(Duration::from_secs(5));
+/// aggregator.summary_format("{count} new messages");
+///
+/// loop {
+///     for (sender, text) in incoming_messages() {
+///         aggregator.add(Notification::new().summary(&sender).body(&text));
+///     }
+///     if let Some(handle) = aggregator.flush_if_due().unwrap() {
+///         println!("shown as {}", handle.id());
+///     }
+///     # break;
+/// }
+/// ```
+///
+/// (xdg only)
+#[derive(Debug)]
+pub struct NotificationAggregator {
+    window: Duration,
+    summary_format: String,
+    item_format: Option<String>,
+    pending: Vec<Notification>,
+    first_at: Option<Instant>,
+}
+
+impl NotificationAggregator {
+    /// Collects notifications for `window` after the first one comes in.
+    pub fn new(window: Duration) -> Self {
+        NotificationAggregator {
+            window,
+            summary_format: "{count} new notifications".into(),
+            item_format: None,
+            pending: Vec::new(),
+            first_at: None,
+        }
+    }
+
+    /// The summary of the combined notification, `{count}` is replaced with the number of notifications.
+    ///
+    /// Defaults to `"{count} new notifications"`.
+    pub fn summary_format(&mut self, format: &str) -> &mut Self {
+        format.clone_into(&mut self.summary_format);
+        self
+    }
+
+    /// One line of the body per notification, `{summary}` and `{body}` are replaced with their fields.
+    ///
+    /// Defaults to `"{summary}: {body}"`, or just `"{summary}"` for notifications without a body.
+    pub fn item_format(&mut self, format: &str) -> &mut Self {
+        self.item_format = Some(format.to_owned());
+        self
+    }
+
+    /// Collects `notification` for the next flush.
+    pub fn add(&mut self, notification: &Notification) -> &mut Self {
+        self.add_at(notification, Instant::now())
+    }
+
+    fn add_at(&mut self, notification: &Notification, now: Instant) -> &mut Self {
+        self.first_at.get_or_insert(now);
+        self.pending.push(notification.clone());
+        self
+    }
+
+    /// How many notifications are waiting for the next flush.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether the window of the first collected notification is over.
+    pub fn is_due(&self) -> bool {
+        self.is_due_at(Instant::now())
+    }
+
+    fn is_due_at(&self, now: Instant) -> bool {
+        self.first_at.is_some_and(|first_at| {
+            now.saturating_duration_since(first_at) >= self.window
+        })
+    }
+
+    /// [`flush()`](Self::flush)es if the window is over, otherwise does nothing.
+    pub fn flush_if_due(&mut self) -> Result<Option<NotificationHandle>> {
+        if self.is_due() {
+            self.flush()
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Shows what was collected so far right away, returns `None` if nothing was.
+    ///
+    /// If showing fails everything stays pending for the next flush.
+    pub fn flush(&mut self) -> Result<Option<NotificationHandle>> {
+        let Some(combined) = self.combined() else {
+            self.first_at = None;
+            return Ok(None);
+        };
+        let handle = combined.show()?;
+        self.pending.clear();
+        self.first_at = None;
+        Ok(Some(handle))
+    }
+
+    /// The notification showing everything that is pending.
+    fn combined(&self) -> Option<Notification> {
+        let (first, rest) = self.pending.split_first()?;
+        if rest.is_empty() {
+            return Some(first.clone());
+        }
+
+        let body = self
+            .pending
+            .iter()
+            .map(|notification| self.item(notification))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let summary = fill(
+            &self.summary_format,
+            &[("{count}", &self.pending.len().to_string())],
+        );
+        let mut combined = first.clone();
+        combined.summary(&summary).body(&body);
+        combined.actions.clear();
+        combined.id = ReplaceId::new();
+        Some(combined)
+    }
+
+    fn item(&self, notification: &Notification) -> String {
+        let format = match &self.item_format {
+            Some(format) => format,
+            None if notification.body.is_empty() => "{summary}",
+            None => "{summary}: {body}",
+        };
+        fill(
+            format,
+            &[
+                ("{summary}", &notification.summary),
+                ("{body}", &notification.body),
+            ],
+        )
+    }
+}
+
+/// Replaces the `placeholders` in `format` in a single pass, so values that contain a placeholder are left alone.
+fn fill(format: &str, placeholders: &[(&str, &str)]) -> String {
+    let mut filled = String::with_capacity(format.len());
+    let mut rest = format;
+    while !rest.is_empty() {
+        let found = placeholders
+            .iter()
+            .find(|(placeholder, _)| rest.starts_with(placeholder));
+        if let Some((placeholder, value)) = found {
+            filled.push_str(value);
+            rest = &rest[placeholder.len()..];
+        } else {
+            let next = rest.chars().next().map_or(1, char::len_utf8);
+            filled.push_str(&rest[..next]);
+            rest = &rest[next..];
+        }
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn due_after_window_of_first() {
+        let now = Instant::now();
+        let mut aggregator = NotificationAggregator::new(WINDOW);
+        assert!(!aggregator.is_due_at(now + WINDOW));
+
+        aggregator.add_at(Notification::new().summary("first"), now);
+        aggregator.add_at(Notification::new().summary("second"), now + WINDOW / 2);
+
+        assert!(!aggregator.is_due_at(now + WINDOW / 2));
+        assert!(aggregator.is_due_at(now + WINDOW));
+        assert_eq!(aggregator.pending(), 2);
+    }
+
+    #[test]
+    fn combines_into_summary() {
+        let mut aggregator = NotificationAggregator::new(WINDOW);
+        assert!(aggregator.combined().is_none());
+
+        aggregator.add(
+            Notification::new()
+                .appname("chat")
+                .summary("Alice")
+                .body("hi")
+                .action("reply", "Reply")
+                .id(3),
+        );
+        assert_eq!(aggregator.combined().unwrap().summary, "Alice");
+
+        aggregator
+            .add(Notification::new().summary("Bob").body("lunch?"))
+            .add(Notification::new().summary("Carol"));
+        let combined = aggregator.combined().unwrap();
+        assert_eq!(combined.appname, "chat");
+        assert_eq!(combined.summary, "3 new notifications");
+        assert_eq!(combined.body, "Alice: hi\nBob: lunch?\nCarol");
+        assert!(combined.actions.is_empty());
+        assert_eq!(combined.id, ReplaceId::new());
+
+        aggregator
+            .summary_format("{count} new messages")
+            .item_format("<b>{summary}</b> {body}");
+        let combined = aggregator.combined().unwrap();
+        assert_eq!(combined.summary, "3 new messages");
+        assert_eq!(
+            combined.body,
+            "<b>Alice</b> hi\n<b>Bob</b> lunch?\n<b>Carol</b> "
+        );
+    }
+
+    #[test]
+    fn placeholders_in_values_are_kept() {
+        let mut aggregator = NotificationAggregator::new(WINDOW);
+        aggregator
+            .add(Notification::new().summary("{body}").body("{summary}"))
+            .add(Notification::new().summary("{count}"));
+        let combined = aggregator.combined().unwrap();
+        assert_eq!(combined.summary, "2 new notifications");
+        assert_eq!(combined.body, "{body}: {summary}\n{count}");
+    }
+
+    #[test]
+    fn failed_flush_keeps_pending() {
+        let mut aggregator = NotificationAggregator::new(WINDOW);
+        aggregator
+            .add(Notification::new().summary("first").validate_lengths(8, 64))
+            .add(Notification::new().summary("second"));
+
+        assert!(aggregator.flush().is_err());
+        assert_eq!(aggregator.pending(), 2);
+        assert!(aggregator.first_at.is_some());
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use dbus::{
     arg::messageitem::{MessageItem, MessageItemArray},
     ffidisp::{BusType, Connection, ConnectionItem},
@@ -5,15 +7,15 @@ use dbus::{
 };
 
 use super::{
-    bus::NotificationBus, ActionResponse, ActionResponseHandler, CloseReason,
-    NOTIFICATION_INTERFACE,
+    bus::NotificationBus, observer, panic_hook, ActionResponse, ActionResponseHandler, CloseReason,
+    OwnedActionResponse, NOTIFICATION_INTERFACE,
 };
 
 use crate::{
     error::*,
     hints::message::HintMessage,
     notification::Notification,
-    xdg::{ServerInformation, NOTIFICATION_OBJECTPATH},
+    xdg::{self, servers, tags, ServerInformation, NOTIFICATION_OBJECTPATH},
 };
 
 pub mod bus {
@@ -72,6 +74,8 @@ pub struct DbusNotificationHandle {
     pub(crate) id: u32,
     pub(crate) connection: Connection,
     pub(crate) notification: Notification,
+    /// How long the last `Notify` call took.
+    pub(crate) send_latency: Duration,
 }
 
 impl DbusNotificationHandle {
@@ -84,19 +88,40 @@ impl DbusNotificationHandle {
             id,
             connection,
             notification,
+            send_latency: Duration::ZERO,
         }
     }
 
     pub fn wait_for_action(self, invocation_closure: impl ActionResponseHandler) {
+        self.wait_for_action_ref(invocation_closure);
+    }
+
+    pub fn wait_for_action_ref(&self, invocation_closure: impl ActionResponseHandler) {
         wait_for_action_signal(&self.connection, self.id, invocation_closure);
     }
 
+    pub fn close_fallible(self) -> Result<()> {
+        let mut message = build_message("CloseNotification", self.notification.bus.clone());
+        message.append_items(&[self.id.into()]);
+        send_with_call_timeout(&self.connection, message, self.notification.call_timeout)?;
+        Ok(())
+    }
+
     pub fn close(self) {
-        let mut message = build_message("CloseNotification", Default::default());
+        let mut message = build_message("CloseNotification", self.notification.bus.clone());
         message.append_items(&[self.id.into()]);
         let _ = self.connection.send(message); // If closing fails there's nothing we could do anyway
     }
 
+    pub fn wait_for_close_timeout(self, timeout: Duration) -> CloseReason {
+        let deadline = Instant::now() + timeout;
+        next_signal(&self.connection, self.id, Some(deadline), |response| {
+            response.is_closed()
+        })
+        .and_then(|response| response.close_reason())
+        .unwrap_or(CloseReason::Expired)
+    }
+
     pub fn on_close<F>(self, closure: F)
     where
         F: FnOnce(CloseReason),
@@ -108,9 +133,17 @@ impl DbusNotificationHandle {
         });
     }
 
+    pub fn update_fallible(&mut self) -> Result<()> {
+        let notification = self.notification.prepare()?;
+        (self.id, self.send_latency) =
+            send_notification_via_connection(&notification, self.id, &self.connection)?;
+        Ok(())
+    }
+
     pub fn update(&mut self) {
-        self.id = send_notification_via_connection(&self.notification, self.id, &self.connection)
-            .unwrap();
+        if let Err(error) = self.update_fallible() {
+            panic_hook::report(error);
+        }
     }
 }
 
@@ -118,8 +151,8 @@ pub fn send_notification_via_connection(
     notification: &Notification,
     id: u32,
     connection: &Connection,
-) -> Result<u32> {
-    send_notification_via_connection_at_bus(notification, id, connection, Default::default())
+) -> Result<(u32, Duration)> {
+    send_notification_via_connection_at_bus(notification, id, connection, notification.bus.clone())
 }
 
 pub fn send_notification_via_connection_at_bus(
@@ -127,25 +160,31 @@ pub fn send_notification_via_connection_at_bus(
     id: u32,
     connection: &Connection,
     bus: NotificationBus,
-) -> Result<u32> {
-    let mut message = build_message("Notify", bus);
-    let timeout: i32 = notification.timeout.into();
+) -> Result<(u32, Duration)> {
+    observer::observe(notification);
+    let mut message = build_message("Notify", bus.clone());
+    let body = notification.body_at_send().into_owned();
     message.append_items(&[
         notification.appname.to_owned().into(), // appname
         id.into(),                              // notification to update
         notification.icon.to_owned().into(),    // icon
         notification.summary.to_owned().into(), // summary (title)
-        notification.body.to_owned().into(),    // body
+        body.into(),                            // body
         pack_actions(notification),             // actions
         pack_hints(notification)?,              // hints
-        timeout.into(),                         // timeout
+        notification.timeout_ms().into(),       // timeout
     ]);
 
-    let reply = connection.send_with_reply_and_block(message, 2000)?;
+    let started = Instant::now();
+    let reply = send_with_call_timeout(connection, message, notification.call_timeout)?;
+    let latency = started.elapsed();
+    if let Some(server) = reply.sender() {
+        servers::replied(&bus, &server);
+    }
 
     match reply.get_items().first() {
-        Some(MessageItem::UInt32(ref id)) => Ok(*id),
-        _ => Ok(0),
+        Some(MessageItem::UInt32(ref id)) => Ok((*id, latency)),
+        _ => Ok((0, latency)),
     }
 }
 
@@ -161,14 +200,66 @@ pub fn connect_and_send_notification_at_bus(
     bus: NotificationBus,
 ) -> Result<DbusNotificationHandle> {
     let connection = Connection::get_private(BusType::Session)?;
-    let inner_id = notification.id.unwrap_or(0);
-    let id = send_notification_via_connection_at_bus(notification, inner_id, &connection, bus)?;
+    let inner_id = notification.id.raw();
+    let (id, send_latency) =
+        send_notification_via_connection_at_bus(notification, inner_id, &connection, bus)?;
 
-    Ok(DbusNotificationHandle::new(
-        id,
-        connection,
-        notification.clone(),
-    ))
+    Ok(DbusNotificationHandle {
+        send_latency,
+        ..DbusNotificationHandle::new(id, connection, notification.for_handle())
+    })
+}
+
+/// Sends `notification` on a connection of its own, which is dropped right after.
+pub fn send_notification_detached(notification: &Notification) -> Result<u32> {
+    let connection = Connection::get_private(BusType::Session)?;
+    let id = notification.id.raw();
+    let (id, _) = send_notification_via_connection(notification, id, &connection)?;
+    Ok(id)
+}
+
+/// Sends all `notifications` one after the other on a connection of their own, which is dropped right after.
+pub fn send_many_detached(notifications: &[Notification]) -> Result<Vec<u32>> {
+    let connection = Connection::get_private(BusType::Session)?;
+    notifications
+        .iter()
+        .map(|notification| {
+            send_notification_via_connection(notification, notification.id.raw(), &connection)
+                .map(|(id, _)| id)
+        })
+        .collect()
+}
+
+/// Closes notification `id` using a connection of its own, which is dropped right after.
+pub fn close_notification_detached(
+    id: u32,
+    bus: NotificationBus,
+    call_timeout: Option<Duration>,
+) -> Result<()> {
+    let connection = Connection::get_private(BusType::Session)?;
+    let mut message = build_message("CloseNotification", bus);
+    message.append_items(&[id.into()]);
+    send_with_call_timeout(&connection, message, call_timeout)?;
+    Ok(())
+}
+
+/// Sends a method call and waits for the reply, for `call_timeout` or the usual 2 seconds.
+fn send_with_call_timeout(
+    connection: &Connection,
+    message: Message,
+    call_timeout: Option<Duration>,
+) -> Result<Message> {
+    let millis = call_timeout.map_or(2000, |timeout| {
+        i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX)
+    });
+    connection
+        .send_with_reply_and_block(message, millis)
+        .map_err(|error| match call_timeout {
+            Some(timeout) if error.name() == Some("org.freedesktop.DBus.Error.NoReply") => {
+                ErrorKind::Timeout(timeout).into()
+            }
+            _ => error.into(),
+        })
 }
 
 fn build_message(method_name: &str, bus: NotificationBus) -> Message {
@@ -183,11 +274,18 @@ fn build_message(method_name: &str, bus: NotificationBus) -> Message {
 
 pub fn pack_hints(notification: &Notification) -> Result<MessageItem> {
     if !notification.hints.is_empty() || !notification.hints_unique.is_empty() {
-        let hints = notification
-            .get_hints()
+        #[allow(unused_mut)]
+        let mut hints = notification
+            .hints_to_send()
             .cloned()
             .map(HintMessage::wrap_hint)
             .collect::<Vec<(MessageItem, MessageItem)>>();
+        #[cfg(feature = "images")]
+        if !notification.icon_frames.is_empty() {
+            hints.push(crate::hints::message::image_frames(
+                &notification.icon_frames,
+            ));
+        }
 
         if let Ok(array) = MessageItem::new_dict(hints) {
             return Ok(array);
@@ -200,9 +298,10 @@ pub fn pack_hints(notification: &Notification) -> Result<MessageItem> {
 }
 
 pub fn pack_actions(notification: &Notification) -> MessageItem {
-    if !notification.actions.is_empty() {
+    let sent = notification.actions_to_send();
+    if !sent.is_empty() {
         let mut actions = vec![];
-        for action in &notification.actions {
+        for action in sent {
             actions.push(action.to_owned().into());
         }
         if let Ok(array) = MessageItem::new_array(actions) {
@@ -214,11 +313,18 @@ pub fn pack_actions(notification: &Notification) -> MessageItem {
 }
 
 pub fn get_capabilities() -> Result<Vec<String>> {
+    get_capabilities_at_bus(Default::default(), None)
+}
+
+pub fn get_capabilities_at_bus(
+    bus: NotificationBus,
+    call_timeout: Option<Duration>,
+) -> Result<Vec<String>> {
     let mut capabilities = vec![];
 
-    let message = build_message("GetCapabilities", Default::default());
+    let message = build_message("GetCapabilities", bus);
     let connection = Connection::get_private(BusType::Session)?;
-    let reply = connection.send_with_reply_and_block(message, 2000)?;
+    let reply = send_with_call_timeout(&connection, message, call_timeout)?;
 
     if let Some(MessageItem::Array(items)) = reply.get_items().first() {
         for item in items.iter() {
@@ -254,75 +360,168 @@ pub fn get_server_information() -> Result<ServerInformation> {
     })
 }
 
+pub fn introspect() -> Result<String> {
+    let message = Message::new_method_call(
+        NotificationBus::default().into_name(),
+        NOTIFICATION_OBJECTPATH,
+        xdg::INTROSPECTABLE_INTERFACE,
+        "Introspect",
+    )
+    .map_err(ErrorKind::Msg)?;
+    let connection = Connection::get_private(BusType::Session)?;
+    let reply = connection.send_with_reply_and_block(message, 2000)?;
+    Ok(reply.get1().unwrap_or_default())
+}
+
 /// Listens for the `ActionInvoked(UInt32, String)` Signal.
 ///
 /// No need to use this, check out `Notification::show_and_wait_for_action(FnOnce(action:&str))`
 pub fn handle_action(id: u32, func: impl ActionResponseHandler) {
-    let connection = Connection::get_private(BusType::Session).unwrap();
-    wait_for_action_signal(&connection, id, func);
+    match Connection::get_private(BusType::Session) {
+        Ok(connection) => wait_for_action_signal(&connection, id, func),
+        Err(error) => panic_hook::report(error.into()),
+    }
 }
 
 // Listens for the `ActionInvoked(UInt32, String)` signal.
 fn wait_for_action_signal(connection: &Connection, id: u32, handler: impl ActionResponseHandler) {
-    connection
-        .add_match(&format!(
-            "interface='{}',member='ActionInvoked'",
-            NOTIFICATION_INTERFACE
-        ))
-        .unwrap();
-    connection
-        .add_match(&format!(
-            "interface='{}',member='NotificationClosed'",
-            NOTIFICATION_INTERFACE
-        ))
-        .unwrap();
+    if let Some(response) = next_signal(connection, id, None, |_| true) {
+        handler.call(&response.as_response());
+    }
+}
 
-    for item in connection.iter(1000) {
+/// Subscribes `connection` to `ActionInvoked` and `NotificationClosed`.
+fn add_signal_matches(connection: &Connection) -> std::result::Result<(), dbus::Error> {
+    connection.add_match(&format!(
+        "interface='{}',member='ActionInvoked'",
+        NOTIFICATION_INTERFACE
+    ))?;
+    connection.add_match(&format!(
+        "interface='{}',member='NotificationClosed'",
+        NOTIFICATION_INTERFACE
+    ))
+}
+
+/// Waits for the first `ActionInvoked` or `NotificationClosed` concerning `id` that is `wanted`.
+///
+/// Returns `None` once `deadline` has passed or if `connection` is unusable.
+fn next_signal(
+    connection: &Connection,
+    id: u32,
+    deadline: Option<Instant>,
+    wanted: impl Fn(&OwnedActionResponse) -> bool,
+) -> Option<OwnedActionResponse> {
+    add_signal_matches(connection).ok()?;
+
+    // with a deadline, look at the clock more often
+    let poll_ms = if deadline.is_some() { 50 } else { 1000 };
+    for item in connection.iter(poll_ms) {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return None;
+        }
         if let ConnectionItem::Signal(message) = item {
-            let items = message.get_items();
-
-            let (path, interface, member) = (
-                message.path().map_or_else(String::new, |p| {
-                    p.into_cstring().to_string_lossy().into_owned()
-                }),
-                message.interface().map_or_else(String::new, |p| {
-                    p.into_cstring().to_string_lossy().into_owned()
-                }),
-                message.member().map_or_else(String::new, |p| {
-                    p.into_cstring().to_string_lossy().into_owned()
-                }),
-            );
-            match (path.as_str(), interface.as_str(), member.as_str()) {
-                // match (protocol.unwrap(), iface.unwrap(), member.unwrap()) {
-                // Action Invoked
-                (path, interface, "ActionInvoked")
-                    if path == NOTIFICATION_OBJECTPATH && interface == NOTIFICATION_INTERFACE =>
-                {
-                    if let (&MessageItem::UInt32(nid), MessageItem::Str(ref action)) =
-                        (&items[0], &items[1])
-                    {
-                        if nid == id {
-                            handler.call(&ActionResponse::Custom(action));
-                            break;
-                        }
-                    }
-                }
+            match observed_signal(&message) {
+                Some((nid, response)) if nid == id && wanted(&response) => return Some(response),
+                _ => (),
+            }
+        }
+    }
+    None
+}
 
-                // Notification Closed
-                (path, interface, "NotificationClosed")
-                    if path == NOTIFICATION_OBJECTPATH && interface == NOTIFICATION_INTERFACE =>
-                {
-                    if let (&MessageItem::UInt32(nid), &MessageItem::UInt32(reason)) =
-                        (&items[0], &items[1])
-                    {
-                        if nid == id {
-                            handler.call(&ActionResponse::Closed(reason.into()));
-                            break;
-                        }
-                    }
+/// Waits for the first signal of any of `handles`, returns it with the index it is paired with.
+///
+/// Every handle has a connection of its own, so they are polled in turn.
+/// Returns `None` once every connection has failed to subscribe or got disconnected.
+pub fn select_action(
+    handles: &[(usize, &DbusNotificationHandle)],
+) -> Option<(usize, OwnedActionResponse)> {
+    let mut handles = handles
+        .iter()
+        .filter(|(_, handle)| add_signal_matches(&handle.connection).is_ok())
+        .collect::<Vec<_>>();
+    while !handles.is_empty() {
+        let poll_ms = (50 / handles.len() as u32).max(1);
+        for (index, handle) in &handles {
+            for message in handle.connection.incoming(poll_ms) {
+                match observed_signal(&message) {
+                    Some((id, response)) if id == handle.id => return Some((*index, response)),
+                    _ => (),
                 }
-                (..) => (),
             }
         }
+        handles.retain(|(_, handle)| handle.connection.is_connected());
+    }
+    None
+}
+
+/// Like [`signal_from_message()`], but also forgets the tag of notifications that closed.
+fn observed_signal(message: &Message) -> Option<(u32, OwnedActionResponse)> {
+    let signal = signal_from_message(message);
+    if let (Some((id, OwnedActionResponse::Closed(_))), Some(server)) = (&signal, message.sender())
+    {
+        tags::forget(&server, *id);
+    }
+    signal
+}
+
+/// Parses `ActionInvoked` and `NotificationClosed`, together with the id they refer to.
+fn signal_from_message(message: &Message) -> Option<(u32, OwnedActionResponse)> {
+    let items = message.get_items();
+
+    let (path, interface, member) = (
+        message.path().map_or_else(String::new, |p| {
+            p.into_cstring().to_string_lossy().into_owned()
+        }),
+        message.interface().map_or_else(String::new, |p| {
+            p.into_cstring().to_string_lossy().into_owned()
+        }),
+        message.member().map_or_else(String::new, |p| {
+            p.into_cstring().to_string_lossy().into_owned()
+        }),
+    );
+    if path != NOTIFICATION_OBJECTPATH || interface != NOTIFICATION_INTERFACE {
+        return None;
+    }
+    match (member.as_str(), items.first(), items.get(1)) {
+        ("ActionInvoked", Some(&MessageItem::UInt32(nid)), Some(MessageItem::Str(action))) => {
+            Some((nid, OwnedActionResponse::Custom(action.clone())))
+        }
+        (
+            "NotificationClosed",
+            Some(&MessageItem::UInt32(nid)),
+            Some(&MessageItem::UInt32(reason)),
+        ) => Some((nid, OwnedActionResponse::Closed(reason.into()))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_action_gives_up_on_dead_connections() {
+        let path = std::env::temp_dir().join(format!("notify-rust-dead-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        // hang up on every client right away
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(2) {
+                drop(stream);
+            }
+        });
+
+        let handles = (0..2)
+            .map(|id| {
+                let address = format!("unix:path={}", path.display());
+                let connection = Connection::open_private(&address).unwrap();
+                DbusNotificationHandle::new(id, connection, Notification::new())
+            })
+            .collect::<Vec<_>>();
+        let handles = handles.iter().enumerate().collect::<Vec<_>>();
+
+        assert_eq!(select_action(&handles), None);
+        let _ = std::fs::remove_file(&path);
     }
 }
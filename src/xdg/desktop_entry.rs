@@ -0,0 +1,116 @@
+//! Looks up the icon of an installed `.desktop` file, see [`Notification::icon_from_desktop_entry`].
+//!
+//! Desktop entries live in the `applications` directory of the XDG data directories,
+//! as described in the [desktop entry specification](https://specifications.freedesktop.org/desktop-entry-spec/latest/).
+//!
+//! [`Notification::icon_from_desktop_entry`]: crate::Notification::icon_from_desktop_entry
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+/// `$XDG_DATA_HOME` followed by `$XDG_DATA_DIRS`, with the defaults of the basedir specification.
+fn data_dirs() -> Vec<PathBuf> {
+    let home = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")));
+    let dirs = env::var_os("XDG_DATA_DIRS")
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+    home.into_iter().chain(env::split_paths(&dirs)).collect()
+}
+
+/// Where the entry with `id` might be inside one data directory.
+///
+/// Ids of entries in subdirectories have the `/` replaced by `-`, so `kde-foo` may also be `kde/foo.desktop`.
+fn candidates(id: &str) -> impl Iterator<Item = PathBuf> + '_ {
+    let direct = std::iter::once(PathBuf::from(format!("{}.desktop", id)));
+    let nested = id
+        .match_indices('-')
+        .map(move |(at, _)| Path::new(&id[..at]).join(format!("{}.desktop", &id[at + 1..])));
+    direct.chain(nested)
+}
+
+/// The `Icon` key of the `[Desktop Entry]` group.
+fn parse_icon(contents: &str) -> Option<String> {
+    let mut in_main_group = false;
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_main_group = line == "[Desktop Entry]";
+        } else if in_main_group {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim_end() == "Icon" {
+                    return Some(value.trim_start().to_owned()).filter(|icon| !icon.is_empty());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// The icon of the first entry called `id` in `dirs`, earlier directories take precedence.
+fn icon_in(id: &str, dirs: &[PathBuf]) -> Option<String> {
+    let id = id.strip_suffix(".desktop").unwrap_or(id);
+    dirs.iter()
+        .map(|dir| dir.join("applications"))
+        .flat_map(|applications| candidates(id).map(move |file| applications.join(file)))
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| parse_icon(&contents))
+}
+
+/// The icon of the installed desktop entry called `id`.
+pub(crate) fn icon(id: &str) -> Option<String> {
+    icon_in(id, &data_dirs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icon_of_main_group_only() {
+        let entry = "\
+# a comment
+[Desktop Entry]
+Name=Firefox
+Icon[de]=firefox-de
+Icon = firefox
+[Desktop Action new-window]
+Icon=window
+";
+        assert_eq!(parse_icon(entry).as_deref(), Some("firefox"));
+        assert_eq!(
+            parse_icon("[Desktop Action new]\nIcon=window\n[Desktop Entry]\nName=Nothing"),
+            None
+        );
+        assert_eq!(parse_icon("[Desktop Entry]\nIcon="), None);
+    }
+
+    #[test]
+    fn earlier_dirs_and_nested_ids() {
+        let root = env::temp_dir().join(format!("notify-rust-desktop-{}", std::process::id()));
+        let (home, system) = (root.join("home"), root.join("system"));
+        std::fs::create_dir_all(home.join("applications")).unwrap();
+        std::fs::create_dir_all(system.join("applications/kde")).unwrap();
+        let write = |path: PathBuf, icon: &str| {
+            std::fs::write(path, format!("[Desktop Entry]\nIcon={}\n", icon)).unwrap();
+        };
+        write(home.join("applications/editor.desktop"), "editor-custom");
+        write(system.join("applications/editor.desktop"), "editor");
+        write(system.join("applications/kde/mail.desktop"), "kmail");
+
+        let dirs = [home, system];
+        let found = (
+            icon_in("editor", &dirs),
+            icon_in("kde-mail.desktop", &dirs),
+            icon_in("missing", &dirs),
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(found.0.as_deref(), Some("editor-custom"));
+        assert_eq!(found.1.as_deref(), Some("kmail"));
+        assert_eq!(found.2, None);
+    }
+}
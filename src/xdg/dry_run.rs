@@ -0,0 +1,115 @@
+//! Pretending to send, see [`Notification::show()`](crate::Notification::show) and `$NOTIFY_RUST_DRY_RUN`.
+//!
+//! With the variable set to anything but `""` or `"0"` notifications are logged instead of sent,
+//! and `show()` returns a handle that talks to nothing.
+//! The same handle answers with an injected response under the `test-mock` feature.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::{
+    notification::Notification,
+    xdg::{observer, CloseReason, OwnedActionResponse},
+};
+
+/// Skips the D-Bus call of every send if set.
+pub(crate) const DRY_RUN_VAR: &str = "NOTIFY_RUST_DRY_RUN";
+
+/// Waiting on a dry run handle returns right away, as if the notification was closed.
+pub(crate) const CLOSE_REASON: CloseReason = CloseReason::Other(4);
+
+static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Whether `$NOTIFY_RUST_DRY_RUN` is set, checked on every send.
+pub(crate) fn enabled() -> bool {
+    std::env::var_os(DRY_RUN_VAR).is_some_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Logs `notification` and returns the id a server would have given it.
+///
+/// Like a real server this keeps the id of a notification that replaces another one.
+pub(crate) fn send(notification: &Notification) -> u32 {
+    observer::observe(notification);
+    let id = id_of(notification);
+    log::info!(
+        "{} is set, not sending #{}: {:?} {:?} (appname {:?}, icon {:?}, actions {:?}, hints {:?}, timeout {})",
+        DRY_RUN_VAR,
+        id,
+        notification.summary,
+        notification.body_at_send(),
+        notification.appname,
+        notification.icon,
+        notification.actions_to_send(),
+        notification.hints_to_send().collect::<Vec<_>>(),
+        notification.timeout_ms(),
+    );
+    id
+}
+
+/// The id of the notification it replaces, or a new one.
+pub(crate) fn id_of(notification: &Notification) -> u32 {
+    notification
+        .id
+        .get()
+        .unwrap_or_else(|| NEXT_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// What `show()` returns in a dry run, there is no server that could send actions or close it.
+#[derive(Debug)]
+pub(crate) struct DryRunHandle {
+    pub(crate) id: u32,
+    pub(crate) notification: Notification,
+    /// What waiting returns right away.
+    pub(crate) response: OwnedActionResponse,
+}
+
+impl DryRunHandle {
+    pub(crate) fn new(id: u32, notification: Notification) -> Self {
+        Self::responding(id, notification, OwnedActionResponse::Closed(CLOSE_REASON))
+    }
+
+    pub(crate) fn responding(
+        id: u32,
+        notification: Notification,
+        response: OwnedActionResponse,
+    ) -> Self {
+        DryRunHandle {
+            id,
+            notification,
+            response,
+        }
+    }
+
+    /// Why the notification closed, invoked actions close it as dismissed.
+    pub(crate) fn close_reason(&self) -> CloseReason {
+        match self.response {
+            OwnedActionResponse::Closed(reason) => reason,
+            _ => CloseReason::Dismissed,
+        }
+    }
+
+    pub(crate) fn update(&mut self) {
+        self.notification.id = self.id.into();
+        send(&self.notification);
+    }
+
+    pub(crate) fn close(self) {
+        log::info!("{} is set, not closing #{}", DRY_RUN_VAR, self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_replaced_ids() {
+        let first = send(Notification::new().summary("first"));
+        let second = send(Notification::new().summary("second"));
+        assert_ne!(first, second);
+        assert_eq!(send(Notification::new().summary("again").id(first)), first);
+
+        let mut handle = DryRunHandle::new(second, Notification::new());
+        handle.update();
+        assert_eq!(handle.notification.id.get(), Some(second));
+    }
+}
@@ -0,0 +1,157 @@
+//! Remembers recently shown notifications by summary, see [`Notification::merge_by_summary`].
+
+use std::{
+    borrow::Cow,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{notification::Notification, replace_id::ReplaceId};
+
+/// How many summaries are remembered at most, the least recently shown one is forgotten first.
+const CAPACITY: usize = 16;
+
+static RECENT: Mutex<Recent> = Mutex::new(Recent::new());
+
+#[derive(Debug)]
+struct Shown {
+    /// Ids are only unique per server, so only notifications sent to the same bus are merged.
+    bus: String,
+    key: String,
+    id: u32,
+    at: Instant,
+}
+
+/// Recently shown notifications, the most recent one last.
+#[derive(Debug)]
+struct Recent(Vec<Shown>);
+
+impl Recent {
+    const fn new() -> Self {
+        Recent(Vec::new())
+    }
+
+    fn lookup(&self, bus: &str, key: &str, window: Duration, now: Instant) -> Option<u32> {
+        self.0
+            .iter()
+            .find(|shown| shown.bus == bus && shown.key == key)
+            .filter(|shown| now.saturating_duration_since(shown.at) <= window)
+            .map(|shown| shown.id)
+    }
+
+    fn record(&mut self, bus: &str, key: &str, id: u32, now: Instant) {
+        self.0.retain(|shown| shown.bus != bus || shown.key != key);
+        if self.0.len() == CAPACITY {
+            self.0.remove(0);
+        }
+        self.0.push(Shown {
+            bus: bus.to_owned(),
+            key: key.to_owned(),
+            id,
+            at: now,
+        });
+    }
+}
+
+fn recent() -> std::sync::MutexGuard<'static, Recent> {
+    RECENT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// What `notification` is merged by: its bus and its summary, or the summary prefix it was given.
+///
+/// `None` if it doesn't take part in merging, or its summary doesn't start with the prefix.
+fn key(notification: &Notification) -> Option<(String, &str)> {
+    notification.merge_window?;
+    let key = match &notification.merge_prefix {
+        Some(prefix) if notification.summary.starts_with(prefix.as_str()) => prefix,
+        Some(_) => return None,
+        None => &notification.summary,
+    };
+    Some((notification.bus.clone().into_name().to_string(), key))
+}
+
+/// Sets the id to replace if a notification with the same summary was shown within the merge window.
+pub(crate) fn prepare(notification: &Notification) -> Cow<'_, Notification> {
+    let (Some(window), Some((bus, key))) = (notification.merge_window, key(notification)) else {
+        return Cow::Borrowed(notification);
+    };
+    if !notification.id.is_new() {
+        return Cow::Borrowed(notification);
+    }
+    match recent().lookup(&bus, key, window, Instant::now()) {
+        Some(id) => {
+            let mut merged = notification.clone();
+            merged.id = ReplaceId::existing(id);
+            Cow::Owned(merged)
+        }
+        None => Cow::Borrowed(notification),
+    }
+}
+
+/// Remembers the id a notification was shown with, if it takes part in merging.
+pub(crate) fn record(notification: &Notification, id: u32) {
+    if let Some((bus, key)) = key(notification) {
+        recent().record(&bus, key, id, Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(5);
+    const BUS: &str = "org.freedesktop.Notifications";
+
+    #[test]
+    fn merges_within_window() {
+        let now = Instant::now();
+        let mut recent = Recent::new();
+        recent.record(BUS, "Sync", 7, now);
+
+        assert_eq!(recent.lookup(BUS, "Sync", WINDOW, now + WINDOW), Some(7));
+        assert_eq!(recent.lookup(BUS, "Sync", WINDOW, now + WINDOW * 2), None);
+        assert_eq!(recent.lookup(BUS, "Other", WINDOW, now), None);
+        assert_eq!(
+            recent.lookup("de.hoodie.Notification.other", "Sync", WINDOW, now),
+            None
+        );
+    }
+
+    #[test]
+    fn forgets_least_recently_shown() {
+        let now = Instant::now();
+        let mut recent = Recent::new();
+        for id in 0..CAPACITY as u32 {
+            recent.record(BUS, &id.to_string(), id, now);
+        }
+        recent.record(BUS, "0", 100, now);
+        recent.record(BUS, "new", 101, now);
+
+        assert_eq!(recent.lookup(BUS, "0", WINDOW, now), Some(100));
+        assert_eq!(recent.lookup(BUS, "1", WINDOW, now), None);
+        assert_eq!(recent.lookup(BUS, "new", WINDOW, now), Some(101));
+        assert_eq!(recent.0.len(), CAPACITY);
+    }
+
+    #[test]
+    fn keyed_by_prefix() {
+        let mut syncing = Notification::new();
+        syncing
+            .summary("Syncing…")
+            .merge_by_summary_prefix("Sync", WINDOW);
+        assert_eq!(key(&syncing).map(|(_, key)| key), Some("Sync"));
+
+        let mut other = Notification::new();
+        other
+            .summary("Backup")
+            .merge_by_summary_prefix("Sync", WINDOW);
+        assert_eq!(key(&other), None);
+
+        let mut exact = Notification::new();
+        exact.summary("Sync complete").merge_by_summary(WINDOW);
+        assert_eq!(key(&exact).map(|(_, key)| key), Some("Sync complete"));
+        assert_eq!(key(&Notification::new()), None);
+    }
+}
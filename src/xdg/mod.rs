@@ -5,17 +5,41 @@
 #[cfg(feature = "dbus")]
 use dbus::ffidisp::Connection as DbusConnection;
 #[cfg(feature = "zbus")]
-use zbus::{block_on, zvariant};
+use zbus::zvariant;
 
 use crate::{error::*, notification::Notification};
 
-use std::ops::{Deref, DerefMut};
+use std::{
+    ffi::OsString,
+    ops::{Deref, DerefMut},
+    path::Path,
+    time::Duration,
+};
 
+mod aggregator;
 #[cfg(feature = "dbus")]
 mod dbus_rs;
+pub(crate) mod desktop_entry;
+pub(crate) mod dry_run;
 #[cfg(all(feature = "dbus", not(feature = "zbus")))]
 use dbus_rs::bus;
 
+pub(crate) mod merge;
+mod observer;
+mod panic_hook;
+pub(crate) mod quiet;
+mod reconnecting;
+#[cfg(feature = "serde")]
+mod record;
+mod schedule;
+mod scoped;
+pub(crate) mod servers;
+pub(crate) mod strict;
+pub(crate) mod tags;
+pub(crate) mod timestamp;
+
+#[cfg(feature = "zbus")]
+mod runtime;
 #[cfg(feature = "zbus")]
 mod zbus_rs;
 #[cfg(all(feature = "zbus", not(feature = "dbus")))]
@@ -48,8 +72,25 @@ pub static NOTIFICATION_INTERFACE: &str = "org.freedesktop.Notifications";
 #[doc(hidden)]
 pub static NOTIFICATION_OBJECTPATH: &str = "/org/freedesktop/Notifications";
 
+/// The de facto hint for progress in percent, understood by GNOME, KDE and dunst.
+const PROGRESS_HINT: &str = "value";
+
+static INTROSPECTABLE_INTERFACE: &str = "org.freedesktop.DBus.Introspectable";
+
 pub(crate) use bus::NotificationBus;
 
+pub use aggregator::NotificationAggregator;
+pub use observer::set_send_observer;
+pub use panic_hook::set_panic_hook;
+pub use quiet::set_quiet;
+pub use reconnecting::ReconnectingHandle;
+#[cfg(feature = "serde")]
+pub use record::{record_to, replay};
+pub use schedule::ScheduledNotification;
+pub use scoped::ScopedNotification;
+pub use strict::set_strict_capabilities;
+pub use tags::close_by_tag;
+
 #[derive(Debug)]
 enum NotificationHandleInner {
     #[cfg(feature = "dbus")]
@@ -57,11 +98,43 @@ enum NotificationHandleInner {
 
     #[cfg(feature = "zbus")]
     Zbus(zbus_rs::ZbusNotificationHandle),
+
+    DryRun(dry_run::DryRunHandle),
+}
+
+impl NotificationHandleInner {
+    #[cfg(feature = "dbus")]
+    #[allow(unreachable_patterns)]
+    fn as_dbus(&self) -> Option<&dbus_rs::DbusNotificationHandle> {
+        match self {
+            NotificationHandleInner::Dbus(inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "zbus")]
+    #[allow(unreachable_patterns)]
+    fn as_zbus(&self) -> Option<&zbus_rs::ZbusNotificationHandle> {
+        match self {
+            NotificationHandleInner::Zbus(inner) => Some(inner),
+            _ => None,
+        }
+    }
 }
 
 /// A handle to a shown notification.
 ///
 /// This keeps a connection alive to ensure actions work on certain desktops.
+///
+/// With zbus the blocking methods of this handle don't drive any D-Bus futures on the calling thread,
+/// they are all dispatched onto one internal background thread and the caller only waits for the result.
+/// So it is fine to call them from as many threads as you like.
+///
+/// With zbus all notifications are sent through one shared session connection, which subscribes to the
+/// server's signals only once. Waiting on many handles at the same time therefore doesn't pile up match rules.
+///
+/// With only zbus enabled the handle is `Send` and `Sync`, so it can be moved across `.await` points and into other tasks.
+/// dbus-rs connections can't leave their thread, so with the `d` feature it is neither.
 #[derive(Debug)]
 pub struct NotificationHandle {
     inner: NotificationHandleInner,
@@ -91,8 +164,59 @@ impl NotificationHandle {
         }
     }
 
+    pub(crate) fn dry_run(id: u32, notification: Notification) -> NotificationHandle {
+        NotificationHandle {
+            inner: NotificationHandleInner::DryRun(dry_run::DryRunHandle::new(id, notification)),
+        }
+    }
+
+    /// A handle that sends nothing and answers every wait with `response`, to test action handling without a server.
+    ///
+    /// [`wait_for_action()`](Self::wait_for_action), [`wait_for_action_typed()`](Self::wait_for_action_typed)
+    /// and the like return `response` right away, [`on_close()`](Self::on_close) only runs for a [`OwnedActionResponse::Closed`].
+    /// Updating and closing the handle are only logged, just like in a dry run.
+    ///
+    /// ```
+    /// # use notify_rust::*;
+    /// fn answer(handle: NotificationHandle) -> &'static str {
+    ///     let mut answer = "missed";
+    ///     handle.wait_for_action(|action| {
+    ///         if action == "accept" {
+    ///             answer = "accepted"
+    ///         }
+    ///     });
+    ///     answer
+    /// }
+    ///
+    /// let call = Notification::new().summary("Incoming call").action("accept", "Accept").finalize();
+    /// let handle = NotificationHandle::with_injected_action(
+    ///     call,
+    ///     OwnedActionResponse::Custom("accept".into()),
+    /// );
+    /// assert_eq!(answer(handle), "accepted");
+    /// ```
+    ///
+    /// (`test-mock` feature only)
+    #[cfg(feature = "test-mock")]
+    pub fn with_injected_action(
+        notification: Notification,
+        response: OwnedActionResponse,
+    ) -> NotificationHandle {
+        NotificationHandle {
+            inner: NotificationHandleInner::DryRun(dry_run::DryRunHandle::responding(
+                dry_run::id_of(&notification),
+                notification,
+                response,
+            )),
+        }
+    }
+
     /// Waits for the user to act on a notification and then calls
     /// `invocation_closure` with the name of the corresponding action.
+    ///
+    /// Signals are matched by the id of this handle, not by the actions it was first shown with,
+    /// so after [`update()`](Self::update) or [`update_actions()`](Self::update_actions)
+    /// this waits for whatever action set was sent last.
     pub fn wait_for_action<F>(self, invocation_closure: F)
     where
         F: FnOnce(&str),
@@ -108,16 +232,115 @@ impl NotificationHandle {
 
             #[cfg(feature = "zbus")]
             NotificationHandleInner::Zbus(inner) => {
-                block_on(
-                    inner.wait_for_action(|action: &ActionResponse| match action {
-                        ActionResponse::Custom(action) => invocation_closure(action),
-                        ActionResponse::Closed(_reason) => invocation_closure("__closed"), // FIXME: remove backward compatibility with 5.0
-                    }),
-                );
+                inner.wait_for_action_blocking(|action: &ActionResponse| match action {
+                    ActionResponse::Custom(action) => invocation_closure(action),
+                    ActionResponse::Closed(_reason) => invocation_closure("__closed"), // FIXME: remove backward compatibility with 5.0
+                });
             }
+
+            NotificationHandleInner::DryRun(inner) => match inner.response {
+                OwnedActionResponse::Custom(action) => invocation_closure(&action),
+                OwnedActionResponse::Closed(_reason) => invocation_closure("__closed"),
+            },
         };
     }
 
+    /// Like [`wait_for_action()`](Self::wait_for_action), but borrows the handle,
+    /// so it can still be updated or closed afterwards.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// let handle = Notification::new()
+    ///     .summary("Update available")
+    ///     .action("install", "Install")
+    ///     .show()
+    ///     .unwrap();
+    /// handle.wait_for_action_ref(|response: &ActionResponse| {
+    ///     if let ActionResponse::Custom("install") = response {
+    ///         println!("installing");
+    ///     }
+    /// });
+    /// handle.close();
+    /// ```
+    pub fn wait_for_action_ref(&self, handler: impl ActionResponseHandler) {
+        match self.inner {
+            #[cfg(feature = "dbus")]
+            NotificationHandleInner::Dbus(ref inner) => inner.wait_for_action_ref(handler),
+            #[cfg(feature = "zbus")]
+            NotificationHandleInner::Zbus(ref inner) => inner.wait_for_action_ref_blocking(handler),
+            NotificationHandleInner::DryRun(ref inner) => {
+                handler.call(&inner.response.as_response());
+            }
+        }
+    }
+
+    /// Async version of [`wait_for_action_ref()`](Self::wait_for_action_ref).
+    ///
+    /// With dbus-rs this still blocks while waiting.
+    #[cfg(all(feature = "async", feature = "zbus"))]
+    pub async fn wait_for_action_ref_async(&self, handler: impl ActionResponseHandler) {
+        match self.inner {
+            #[cfg(feature = "dbus")]
+            NotificationHandleInner::Dbus(ref inner) => inner.wait_for_action_ref(handler),
+            NotificationHandleInner::Zbus(ref inner) => inner.wait_for_action_ref(handler).await,
+            NotificationHandleInner::DryRun(ref inner) => {
+                handler.call(&inner.response.as_response());
+            }
+        }
+    }
+
+    /// Waits for an action like [`wait_for_action_ref()`](Self::wait_for_action_ref), and parses its key into your own type.
+    ///
+    /// Fails with [`ErrorKind::UnknownAction`](crate::error::ErrorKind::UnknownAction) if the key doesn't parse,
+    /// e.g. `"default"` for clicking the notification itself if your type has no such action.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// enum Answer {
+    ///     Accept,
+    ///     Decline,
+    /// }
+    ///
+    /// impl std::str::FromStr for Answer {
+    ///     type Err = String;
+    ///     fn from_str(key: &str) -> Result<Self, String> {
+    ///         match key {
+    ///             "accept" => Ok(Answer::Accept),
+    ///             "decline" => Ok(Answer::Decline),
+    ///             _ => Err(format!("not an answer")),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// # fn _doc() -> error::Result<()> {
+    /// let handle = Notification::new()
+    ///     .summary("Incoming call")
+    ///     .action("accept", "Accept")
+    ///     .action("decline", "Decline")
+    ///     .show()?;
+    /// match handle.wait_for_action_typed::<Answer>()? {
+    ///     TypedActionResponse::Action(Answer::Accept) => println!("accepted"),
+    ///     TypedActionResponse::Action(Answer::Decline) => println!("declined"),
+    ///     TypedActionResponse::Closed(reason) => println!("missed: {:?}", reason),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn wait_for_action_typed<A>(&self) -> Result<TypedActionResponse<A>>
+    where
+        A: std::str::FromStr,
+        A::Err: std::fmt::Display,
+    {
+        self.wait_for_response()?.parse_action()
+    }
+
+    /// Waits like [`wait_for_action_ref()`](Self::wait_for_action_ref) and returns the owned response.
+    pub(crate) fn wait_for_response(&self) -> Result<OwnedActionResponse> {
+        let mut response = None;
+        self.wait_for_action_ref(|r: &ActionResponse| response = Some(r.to_owned_response()));
+        Ok(response.ok_or("stopped waiting, the notification server is gone")?)
+    }
+
     /// Manually close the notification
     ///
     /// # Example
@@ -140,7 +363,36 @@ impl NotificationHandle {
             #[cfg(feature = "dbus")]
             NotificationHandleInner::Dbus(inner) => inner.close(),
             #[cfg(feature = "zbus")]
-            NotificationHandleInner::Zbus(inner) => block_on(inner.close()),
+            NotificationHandleInner::Zbus(inner) => runtime::block_on(inner.close()),
+            NotificationHandleInner::DryRun(inner) => inner.close(),
+        }
+    }
+
+    /// Closes the notification and waits for the server, failures are only logged.
+    ///
+    /// Only on the zbus runtime thread, which can't wait for itself, the close is spawned instead.
+    pub(crate) fn close_logged(self) {
+        fn log_failure(id: u32, closed: Result<()>) {
+            if let Err(error) = closed {
+                if !quiet::is_quiet() {
+                    log::warn!("failed to close notification {}: {}", id, error);
+                }
+            }
+        }
+
+        let id = self.id();
+        match self.inner {
+            #[cfg(feature = "dbus")]
+            NotificationHandleInner::Dbus(inner) => log_failure(id, inner.close_fallible()),
+            #[cfg(feature = "zbus")]
+            NotificationHandleInner::Zbus(inner) if runtime::is_current() => {
+                runtime::spawn(async move { log_failure(id, inner.close_fallible().await) });
+            }
+            #[cfg(feature = "zbus")]
+            NotificationHandleInner::Zbus(inner) => {
+                log_failure(id, runtime::block_on(inner.close_fallible()));
+            }
+            NotificationHandleInner::DryRun(inner) => inner.close(),
         }
     }
 
@@ -169,25 +421,60 @@ impl NotificationHandle {
     ///                    .unwrap()
     ///                    .on_close(|reason| println!("closed: {:?}", reason));
     /// ```
+    ///
+    /// ## Example 3: *I also want to know what was closed*
+    ///
+    /// The handler gets the notification as it was last sent, including any [`update()`](Self::update).
+    ///
+    /// ```no_run
+    /// # use notify_rust::{CloseReason, Notification};
+    /// Notification::new().summary("Time is running out")
+    ///                    .show()
+    ///                    .unwrap()
+    ///                    .on_close(|reason: CloseReason, notification: &Notification| {
+    ///                        println!("{:?} closed: {:?}", notification.summary, reason)
+    ///                    });
+    /// ```
     pub fn on_close<A>(self, handler: impl CloseHandler<A>) {
-        match self.inner {
+        let notification = match self.inner {
             #[cfg(feature = "dbus")]
-            NotificationHandleInner::Dbus(inner) => {
-                inner.wait_for_action(|action: &ActionResponse| {
-                    if let ActionResponse::Closed(reason) = action {
-                        handler.call(*reason);
-                    }
-                });
-            }
+            NotificationHandleInner::Dbus(ref inner) => &inner.notification,
             #[cfg(feature = "zbus")]
-            NotificationHandleInner::Zbus(inner) => {
-                block_on(inner.wait_for_action(|action: &ActionResponse| {
-                    if let ActionResponse::Closed(reason) = action {
-                        handler.call(*reason);
-                    }
-                }));
-            }
+            NotificationHandleInner::Zbus(ref inner) => &inner.notification,
+            NotificationHandleInner::DryRun(ref inner) => &inner.notification,
         };
+        self.wait_for_action_ref(|action: &ActionResponse| {
+            if let ActionResponse::Closed(reason) = action {
+                handler.call_with_notification(*reason, notification);
+            }
+        });
+    }
+
+    /// Waits until the notification is closed, but for no longer than `timeout`.
+    ///
+    /// Returns the reason the server gives, or [`CloseReason::Expired`] if it doesn't tell within `timeout`.
+    /// Some servers never send `NotificationClosed` for notifications that expired,
+    /// so unlike [`on_close()`](Self::on_close) this can't block forever.
+    /// Invoked actions are ignored.
+    ///
+    /// ```no_run
+    /// # use notify_rust::Notification;
+    /// # use std::time::Duration;
+    /// let reason = Notification::new()
+    ///     .summary("Backup done")
+    ///     .show()
+    ///     .unwrap()
+    ///     .wait_for_close_timeout(Duration::from_secs(30));
+    /// println!("closed: {:?}", reason);
+    /// ```
+    pub fn wait_for_close_timeout(self, timeout: Duration) -> CloseReason {
+        match self.inner {
+            #[cfg(feature = "dbus")]
+            NotificationHandleInner::Dbus(inner) => inner.wait_for_close_timeout(timeout),
+            #[cfg(feature = "zbus")]
+            NotificationHandleInner::Zbus(inner) => inner.wait_for_close_timeout(timeout),
+            NotificationHandleInner::DryRun(inner) => inner.close_reason(),
+        }
     }
 
     /// Replace the original notification with an updated version
@@ -216,7 +503,108 @@ impl NotificationHandle {
             NotificationHandleInner::Dbus(ref mut inner) => inner.update(),
             #[cfg(feature = "zbus")]
             NotificationHandleInner::Zbus(ref mut inner) => inner.update(),
+            NotificationHandleInner::DryRun(ref mut inner) => inner.update(),
+        }
+    }
+
+    /// Change the notification in a closure and send the update right away.
+    ///
+    /// The id and bus of the notification stay the same, so this replaces the original one.
+    /// ## Example
+    /// ```no_run
+    /// # use notify_rust::Notification;
+    /// let mut notification = Notification::new().summary("New message")
+    ///                                           .action("read", "Mark as read")
+    ///                                           .show()
+    ///                                           .unwrap();
+    ///
+    /// // ... once the message was read elsewhere
+    /// notification.modify(|notification| {
+    ///     notification.summary("Message read");
+    ///     notification.actions.clear();
+    /// });
+    /// ```
+    pub fn modify<F>(&mut self, modification: F)
+    where
+        F: FnOnce(&mut Notification),
+    {
+        modification(self);
+        self.update();
+    }
+
+    /// Replace the icon of the original notification and send the update.
+    ///
+    /// All other fields of the notification are kept as they were.
+    /// ## Example
+    /// ```no_run
+    /// # use notify_rust::Notification;
+    /// let mut notification = Notification::new().summary("Downloading")
+    ///                                           .icon("folder-download")
+    ///                                           .show()
+    ///                                           .unwrap();
+    ///
+    /// // ... and once the download is done
+    /// notification.update_icon("emblem-ok");
+    /// ```
+    pub fn update_icon(&mut self, icon: &str) {
+        self.icon(icon);
+        self.update();
+    }
+
+    /// Turn a progress notification into its final message and send the update.
+    ///
+    /// Removes the progress, that is the `value` hint set through [`Hint::CustomInt`](crate::Hint::CustomInt),
+    /// replaces summary, body and icon and lets the notification expire after the server's default timeout.
+    /// ## Example
+    /// ```no_run
+    /// # use notify_rust::{Hint, Notification};
+    /// let mut notification = Notification::new().summary("Downloading")
+    ///                                           .icon("folder-download")
+    ///                                           .hint(Hint::CustomInt("value".into(), 0))
+    ///                                           .timeout(0)
+    ///                                           .show()
+    ///                                           .unwrap();
+    ///
+    /// for percent in (10..=100).step_by(10) {
+    ///     notification.modify(|notification| {
+    ///         notification.hint(Hint::CustomInt("value".into(), percent));
+    ///     });
+    /// }
+    ///
+    /// notification.complete("Download finished", "ubuntu.iso", "emblem-ok");
+    /// ```
+    pub fn complete(&mut self, summary: &str, body: &str, icon: &str) {
+        self.hints_unique.retain(|(key, _), _| key != PROGRESS_HINT);
+        self.summary(summary)
+            .body(body)
+            .icon(icon)
+            .timeout(crate::Timeout::Default);
+        self.update();
+    }
+
+    /// Replace the actions of the original notification and send the update.
+    ///
+    /// The notification keeps its id, so [`wait_for_action()`](Self::wait_for_action) can be called
+    /// on this handle afterwards and will receive the new action keys.
+    /// ## Example
+    /// ```no_run
+    /// # use notify_rust::Notification;
+    /// let mut notification = Notification::new().summary("Incoming call")
+    ///                                           .action("accept", "Accept")
+    ///                                           .action("decline", "Decline")
+    ///                                           .show()
+    ///                                           .unwrap();
+    ///
+    /// // ... once the call was accepted
+    /// notification.update_actions(&[("hang-up", "Hang up")]);
+    /// notification.wait_for_action(|action| println!("{}", action));
+    /// ```
+    pub fn update_actions(&mut self, actions: &[(&str, &str)]) {
+        self.actions.clear();
+        for (identifier, label) in actions {
+            self.action(identifier, label);
         }
+        self.update();
     }
 
     /// Returns the Handle's id.
@@ -226,6 +614,30 @@ impl NotificationHandle {
             NotificationHandleInner::Dbus(ref inner) => inner.id,
             #[cfg(feature = "zbus")]
             NotificationHandleInner::Zbus(ref inner) => inner.id,
+            NotificationHandleInner::DryRun(ref inner) => inner.id,
+        }
+    }
+
+    /// How long the server took to answer the last `Notify` call, of [`show()`](Notification::show) or the latest update.
+    ///
+    /// Measured around the D-Bus call, so this includes the way through the bus daemon.
+    /// A slow notification server shows up here, so does one that is stuck until the [call timeout](Notification::with_call_timeout).
+    /// Zero in a dry run.
+    ///
+    /// ```no_run
+    /// # use notify_rust::*;
+    /// let handle = Notification::new().summary("ping").show().unwrap();
+    /// if handle.last_send_latency() > std::time::Duration::from_millis(100) {
+    ///     eprintln!("the notification server is slow to respond");
+    /// }
+    /// ```
+    pub fn last_send_latency(&self) -> Duration {
+        match self.inner {
+            #[cfg(feature = "dbus")]
+            NotificationHandleInner::Dbus(ref inner) => inner.send_latency,
+            #[cfg(feature = "zbus")]
+            NotificationHandleInner::Zbus(ref inner) => inner.send_latency,
+            NotificationHandleInner::DryRun(_) => Duration::ZERO,
         }
     }
 }
@@ -240,6 +652,7 @@ impl Deref for NotificationHandle {
             NotificationHandleInner::Dbus(ref inner) => &inner.notification,
             #[cfg(feature = "zbus")]
             NotificationHandleInner::Zbus(ref inner) => &inner.notification,
+            NotificationHandleInner::DryRun(ref inner) => &inner.notification,
         }
     }
 }
@@ -252,6 +665,7 @@ impl DerefMut for NotificationHandle {
             NotificationHandleInner::Dbus(ref mut inner) => &mut inner.notification,
             #[cfg(feature = "zbus")]
             NotificationHandleInner::Zbus(ref mut inner) => &mut inner.notification,
+            NotificationHandleInner::DryRun(ref mut inner) => &mut inner.notification,
         }
     }
 }
@@ -288,6 +702,47 @@ impl From<zbus_rs::ZbusNotificationHandle> for NotificationHandle {
     }
 }
 
+/// A handle to a shown notification that does not keep a connection alive.
+///
+/// Returned by [`Notification::show_light()`], it only remembers the id and the bus of the notification.
+/// [`close()`](Self::close) opens a short lived connection just to send `CloseNotification`.
+///
+/// Compared to a [`NotificationHandle`] this saves holding on to a D-Bus connection,
+/// but you can't wait for actions or the notification being closed,
+/// some servers (e.g. GNOME) only send those signals to the connection that is still around.
+#[derive(Clone, Debug)]
+pub struct LightHandle {
+    id: u32,
+    bus: NotificationBus,
+    call_timeout: Option<Duration>,
+    dry_run: bool,
+}
+
+impl LightHandle {
+    /// Returns the Handle's id.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Manually close the notification.
+    pub fn close(self) -> Result<()> {
+        if self.dry_run {
+            log::info!("{} is set, not closing #{}", dry_run::DRY_RUN_VAR, self.id);
+            return Ok(());
+        }
+        close_notification_detached(self.id, self.bus, self.call_timeout)
+    }
+
+    pub(crate) fn dry_run(id: u32, notification: &Notification) -> LightHandle {
+        LightHandle {
+            id,
+            bus: notification.bus.clone(),
+            call_timeout: notification.call_timeout,
+            dry_run: true,
+        }
+    }
+}
+
 // here be public functions
 
 // TODO: breaking change, wait for 5.0
@@ -313,9 +768,16 @@ pub enum DbusStack {
 #[cfg(all(feature = "dbus", feature = "zbus"))]
 const DBUS_SWITCH_VAR: &str = "DBUSRS";
 
+#[cfg(feature = "zbus")]
+fn show_notification_zbus(notification: &Notification) -> Result<NotificationHandle> {
+    let notification = notification.clone();
+    runtime::block_on(async move { zbus_rs::connect_and_send_notification(&notification).await })
+        .map(Into::into)
+}
+
 #[cfg(all(feature = "zbus", not(feature = "dbus")))]
 pub(crate) fn show_notification(notification: &Notification) -> Result<NotificationHandle> {
-    block_on(zbus_rs::connect_and_send_notification(notification)).map(Into::into)
+    show_notification_zbus(notification)
 }
 
 #[cfg(all(feature = "async", feature = "zbus"))]
@@ -327,6 +789,11 @@ pub(crate) async fn show_notification_async(
         .map(Into::into)
 }
 
+#[cfg(all(feature = "async", feature = "zbus"))]
+pub(crate) async fn send_notification_detached_async(notification: &Notification) -> Result<u32> {
+    zbus_rs::send_notification_detached(notification).await
+}
+
 #[cfg(all(feature = "async", feature = "zbus"))]
 pub(crate) async fn show_notification_async_at_bus(
     notification: &Notification,
@@ -347,83 +814,455 @@ pub(crate) fn show_notification(notification: &Notification) -> Result<Notificat
     if std::env::var(DBUS_SWITCH_VAR).is_ok() {
         dbus_rs::connect_and_send_notification(notification).map(Into::into)
     } else {
-        block_on(zbus_rs::connect_and_send_notification(notification)).map(Into::into)
+        show_notification_zbus(notification)
     }
 }
 
-/// Get the currently used [`DbusStack`]
-///
-/// (zbus only)
+#[cfg(feature = "zbus")]
+fn show_light_zbus(notification: &Notification) -> Result<LightHandle> {
+    let bus = notification.bus.clone();
+    let call_timeout = notification.call_timeout;
+    let notification = notification.clone();
+    let id =
+        runtime::block_on(async move { zbus_rs::send_notification_detached(&notification).await })?;
+    Ok(LightHandle {
+        id,
+        bus,
+        call_timeout,
+        dry_run: false,
+    })
+}
+
+#[cfg(feature = "dbus")]
+fn show_light_dbus(notification: &Notification) -> Result<LightHandle> {
+    let id = dbus_rs::send_notification_detached(notification)?;
+    Ok(LightHandle {
+        id,
+        bus: notification.bus.clone(),
+        call_timeout: notification.call_timeout,
+        dry_run: false,
+    })
+}
+
 #[cfg(all(feature = "zbus", not(feature = "dbus")))]
-pub fn dbus_stack() -> Option<DbusStack> {
-    Some(DbusStack::Zbus)
+pub(crate) fn show_light(notification: &Notification) -> Result<LightHandle> {
+    show_light_zbus(notification)
 }
 
-/// Get the currently used [`DbusStack`]
-///
-/// (dbus-rs only)
 #[cfg(all(feature = "dbus", not(feature = "zbus")))]
-pub fn dbus_stack() -> Option<DbusStack> {
-    Some(DbusStack::Dbus)
+pub(crate) fn show_light(notification: &Notification) -> Result<LightHandle> {
+    show_light_dbus(notification)
 }
 
-/// Get the currently used [`DbusStack`]
-///
-/// both dbus-rs and zbus, switch via `$ZBUS_NOTIFICATION`
 #[cfg(all(feature = "dbus", feature = "zbus"))]
-pub fn dbus_stack() -> Option<DbusStack> {
-    Some(if std::env::var(DBUS_SWITCH_VAR).is_ok() {
-        DbusStack::Dbus
+pub(crate) fn show_light(notification: &Notification) -> Result<LightHandle> {
+    if std::env::var(DBUS_SWITCH_VAR).is_ok() {
+        show_light_dbus(notification)
     } else {
-        DbusStack::Zbus
-    })
+        show_light_zbus(notification)
+    }
 }
 
-/// Get the currently used [`DbusStack`]
+/// Closes the notification with `id`, no handle needed.
 ///
-/// neither zbus nor dbus-rs are configured
-#[cfg(all(not(feature = "dbus"), not(feature = "zbus")))]
-pub fn dbus_stack() -> Option<DbusStack> {
-    None
+/// For tools that only got the id of a notification, e.g. from [`Notification::send_id()`] in another process.
+/// Closing an id the server doesn't know is not an error.
+///
+/// ```no_run
+/// # fn main() -> notify_rust::error::Result<()> {
+/// let id = std::env::args().nth(1).unwrap_or_default().parse()?;
+/// notify_rust::close_notification(id)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// (xdg only)
+pub fn close_notification(id: u32) -> Result<()> {
+    close_notification_detached(id, NotificationBus::default(), None)
 }
 
-/// Get list of all capabilities of the running notification server.
+/// Sends all `notifications` and only returns the ids the server assigned to them, in the same order.
 ///
-/// (zbus only)
+/// The throughput oriented counterpart to showing them one by one, e.g. for services that emit many one-shot notifications
+/// or for stress testing a notification server.
+/// They all go over a single connection that is dropped once the last id came back, no handles are kept.
+/// zbus sends them concurrently, dbus-rs one after the other.
+///
+/// Fails with the first error, notifications that were sent by then stay on screen.
+///
+/// ```no_run
+/// # use notify_rust::*;
+/// let notifications = (0..100)
+///     .map(|i| Notification::new().summary(&format!("load test #{}", i)).finalize())
+///     .collect::<Vec<_>>();
+/// let ids = notify_rust::show_many_ids(&notifications).unwrap();
+/// assert_eq!(ids.len(), 100);
+/// ```
+///
+/// (xdg only)
+pub fn show_many_ids(notifications: &[Notification]) -> Result<Vec<u32>> {
+    let prepared = notifications
+        .iter()
+        .map(Notification::prepare)
+        .collect::<Result<Vec<_>>>()?;
+    let ids = if dry_run::enabled() {
+        prepared
+            .iter()
+            .map(|notification| dry_run::send(notification))
+            .collect()
+    } else {
+        send_many_detached(prepared.iter().map(|n| n.clone().into_owned()).collect())?
+    };
+    for (notification, id) in prepared.iter().zip(&ids) {
+        notification.shown(*id);
+    }
+    Ok(ids)
+}
+
 #[cfg(all(feature = "zbus", not(feature = "dbus")))]
-pub fn get_capabilities() -> Result<Vec<String>> {
-    block_on(zbus_rs::get_capabilities())
+fn send_many_detached(notifications: Vec<Notification>) -> Result<Vec<u32>> {
+    runtime::block_on(async move { zbus_rs::send_many_detached(&notifications).await })
 }
 
-/// Get list of all capabilities of the running notification server.
-///
-/// (dbus-rs only)
 #[cfg(all(feature = "dbus", not(feature = "zbus")))]
-pub fn get_capabilities() -> Result<Vec<String>> {
-    dbus_rs::get_capabilities()
+fn send_many_detached(notifications: Vec<Notification>) -> Result<Vec<u32>> {
+    dbus_rs::send_many_detached(&notifications)
 }
 
-/// Get list of all capabilities of the running notification server.
-///
-/// both dbus-rs and zbus, switch via `$ZBUS_NOTIFICATION`
 #[cfg(all(feature = "dbus", feature = "zbus"))]
-pub fn get_capabilities() -> Result<Vec<String>> {
+fn send_many_detached(notifications: Vec<Notification>) -> Result<Vec<u32>> {
     if std::env::var(DBUS_SWITCH_VAR).is_ok() {
-        dbus_rs::get_capabilities()
+        dbus_rs::send_many_detached(&notifications)
     } else {
-        block_on(zbus_rs::get_capabilities())
+        runtime::block_on(async move { zbus_rs::send_many_detached(&notifications).await })
     }
 }
 
-/// Returns a struct containing `ServerInformation`.
-///
-/// This struct contains `name`, `vendor`, `version` and `spec_version` of the notification server
-/// running.
+/// Async version of [`close_notification()`].
 ///
 /// (zbus only)
-#[cfg(all(feature = "zbus", not(feature = "dbus")))]
+#[cfg(all(feature = "async", feature = "zbus"))]
+pub async fn close_notification_async(id: u32) -> Result<()> {
+    zbus_rs::close_notification_detached(id, NotificationBus::default(), None).await
+}
+
+/// Drops the session connection that notifications and functions like [`get_capabilities()`] share.
+///
+/// That connection is made on first use and reused afterwards, one that died is replaced on its own.
+/// Resetting is only needed to connect to another bus, e.g. after changing `DBUS_SESSION_BUS_ADDRESS`.
+/// This also forgets the capabilities cached for [`set_strict_capabilities()`] and [`Notification::with_action_fallback()`].
+/// Handles keep using the connection they were shown on.
+///
+/// (zbus only)
+#[cfg(feature = "zbus")]
+pub fn reset_connection() {
+    zbus_rs::reset_connection();
+    strict::forget_capabilities();
+}
+
+/// Tells when the notification server starts or stops.
+///
+/// Notifications don't survive the server, after a restart (e.g. a crash or a reload of the theme)
+/// the old handles refer to nothing and their actions and close signals never arrive.
+/// Use this to show important persistent notifications again once a new server is [`Started`](ServerEvent::Started).
+/// A server directly taking over from another one only shows up as `Started`.
+///
+/// ```no_run
+/// # use notify_rust::*;
+/// use zbus::export::futures_util::StreamExt;
+/// # async fn run() -> error::Result<()> {
+/// let mut events = notify_rust::watch_server().await?;
+/// while let Some(event) = events.next().await {
+///     if event == ServerEvent::Started {
+///         Notification::new().summary("VPN connected").show_async().await?;
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// (zbus only)
+#[cfg(all(feature = "async", feature = "zbus"))]
+pub async fn watch_server() -> Result<impl zbus::export::futures_util::Stream<Item = ServerEvent>> {
+    zbus_rs::watch_server_at_bus(NotificationBus::default()).await
+}
+
+/// This is for testing purposes only and will not work with actual implementations.
+#[doc(hidden)]
+#[cfg(all(feature = "async", feature = "zbus"))]
+pub async fn watch_server_at_bus(
+    sub_bus: &str,
+) -> Result<impl zbus::export::futures_util::Stream<Item = ServerEvent>> {
+    let bus = NotificationBus::custom(sub_bus).ok_or("invalid subpath")?;
+    zbus_rs::watch_server_at_bus(bus).await
+}
+
+/// Changes of the notification server, see [`watch_server()`].
+#[cfg(all(feature = "async", feature = "zbus"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ServerEvent {
+    /// A server took the notifications name, notifications shown before it are gone.
+    Started,
+    /// The server is gone.
+    Stopped,
+}
+
+/// Yields every signal of the notification server that arrives on `connection`, whatever notification it is about.
+///
+/// Handles only get the signals of their own notification, sorted out of this very stream.
+/// For routing signals yourself, e.g. to notifications of another process,
+/// listen to all of them here and match on [`NotificationSignal::id()`].
+///
+/// ```no_run
+/// # use notify_rust::*;
+/// use zbus::export::futures_util::StreamExt;
+/// # async fn run() -> error::Result<()> {
+/// let connection = zbus::Connection::session().await?;
+/// let mut signals = notify_rust::signal_stream(&connection).await?;
+/// while let Some(signal) = signals.next().await {
+///     match signal {
+///         NotificationSignal::ActionInvoked { id, action_key } => println!("{} invoked {}", id, action_key),
+///         NotificationSignal::NotificationClosed { id, reason } => println!("{} closed: {:?}", id, reason),
+///         _ => {}
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// (zbus only)
+#[cfg(all(feature = "async", feature = "zbus"))]
+pub async fn signal_stream(
+    connection: &zbus::Connection,
+) -> Result<impl zbus::export::futures_util::Stream<Item = NotificationSignal>> {
+    zbus_rs::signal_stream(connection).await
+}
+
+/// A signal sent by the notification server, see [`signal_stream()`].
+#[cfg(feature = "zbus")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NotificationSignal {
+    /// `ActionInvoked`, the user picked an action.
+    ActionInvoked {
+        /// The notification the action belongs to.
+        id: u32,
+        /// The key the action was added with, `"default"` for clicking the notification itself.
+        action_key: String,
+    },
+    /// `NotificationClosed`, the notification is gone.
+    NotificationClosed {
+        /// The notification that was closed.
+        id: u32,
+        /// Why it was closed.
+        reason: CloseReason,
+    },
+    /// `NotificationReplied`, the user sent a reply from the [inline reply](crate::Notification::inline_reply) field (KDE only).
+    NotificationReplied {
+        /// The notification that was replied to.
+        id: u32,
+        /// What the user wrote.
+        text: String,
+    },
+    /// `ActivationToken`, sent right before `ActionInvoked` by servers that support xdg-activation.
+    ActivationToken {
+        /// The notification whose action is about to be invoked.
+        id: u32,
+        /// Lets the window that handles the action take focus.
+        activation_token: String,
+    },
+}
+
+#[cfg(feature = "zbus")]
+impl NotificationSignal {
+    /// The notification this signal is about.
+    pub fn id(&self) -> u32 {
+        match self {
+            NotificationSignal::ActionInvoked { id, .. }
+            | NotificationSignal::NotificationClosed { id, .. }
+            | NotificationSignal::NotificationReplied { id, .. }
+            | NotificationSignal::ActivationToken { id, .. } => *id,
+        }
+    }
+}
+
+/// This is for testing purposes only and will not work with actual implementations.
+#[doc(hidden)]
+pub fn close_notification_at_bus(id: u32, sub_bus: &str) -> Result<()> {
+    let bus = NotificationBus::custom(sub_bus).ok_or("invalid subpath")?;
+    close_notification_detached(id, bus, None)
+}
+
+#[cfg(all(feature = "zbus", not(feature = "dbus")))]
+fn close_notification_detached(
+    id: u32,
+    bus: NotificationBus,
+    call_timeout: Option<Duration>,
+) -> Result<()> {
+    runtime::block_on(zbus_rs::close_notification_detached(id, bus, call_timeout))
+}
+
+#[cfg(all(feature = "dbus", not(feature = "zbus")))]
+fn close_notification_detached(
+    id: u32,
+    bus: NotificationBus,
+    call_timeout: Option<Duration>,
+) -> Result<()> {
+    dbus_rs::close_notification_detached(id, bus, call_timeout)
+}
+
+#[cfg(all(feature = "dbus", feature = "zbus"))]
+fn close_notification_detached(
+    id: u32,
+    bus: NotificationBus,
+    call_timeout: Option<Duration>,
+) -> Result<()> {
+    if std::env::var(DBUS_SWITCH_VAR).is_ok() {
+        dbus_rs::close_notification_detached(id, bus, call_timeout)
+    } else {
+        runtime::block_on(zbus_rs::close_notification_detached(id, bus, call_timeout))
+    }
+}
+
+/// Get the currently used [`DbusStack`]
+///
+/// (zbus only)
+#[cfg(all(feature = "zbus", not(feature = "dbus")))]
+pub fn dbus_stack() -> Option<DbusStack> {
+    Some(DbusStack::Zbus)
+}
+
+/// Get the currently used [`DbusStack`]
+///
+/// (dbus-rs only)
+#[cfg(all(feature = "dbus", not(feature = "zbus")))]
+pub fn dbus_stack() -> Option<DbusStack> {
+    Some(DbusStack::Dbus)
+}
+
+/// Get the currently used [`DbusStack`]
+///
+/// both dbus-rs and zbus, switch via `$ZBUS_NOTIFICATION`
+#[cfg(all(feature = "dbus", feature = "zbus"))]
+pub fn dbus_stack() -> Option<DbusStack> {
+    Some(if std::env::var(DBUS_SWITCH_VAR).is_ok() {
+        DbusStack::Dbus
+    } else {
+        DbusStack::Zbus
+    })
+}
+
+/// Get the currently used [`DbusStack`]
+///
+/// neither zbus nor dbus-rs are configured
+#[cfg(all(not(feature = "dbus"), not(feature = "zbus")))]
+pub fn dbus_stack() -> Option<DbusStack> {
+    None
+}
+
+/// Get list of all capabilities of the running notification server.
+///
+/// (zbus only)
+#[cfg(all(feature = "zbus", not(feature = "dbus")))]
+pub fn get_capabilities() -> Result<Vec<String>> {
+    runtime::block_on(zbus_rs::get_capabilities())
+}
+
+/// Get list of all capabilities of the running notification server.
+///
+/// (dbus-rs only)
+#[cfg(all(feature = "dbus", not(feature = "zbus")))]
+pub fn get_capabilities() -> Result<Vec<String>> {
+    dbus_rs::get_capabilities()
+}
+
+/// Get list of all capabilities of the running notification server.
+///
+/// both dbus-rs and zbus, switch via `$ZBUS_NOTIFICATION`
+#[cfg(all(feature = "dbus", feature = "zbus"))]
+pub fn get_capabilities() -> Result<Vec<String>> {
+    if std::env::var(DBUS_SWITCH_VAR).is_ok() {
+        dbus_rs::get_capabilities()
+    } else {
+        runtime::block_on(zbus_rs::get_capabilities())
+    }
+}
+
+#[cfg(all(feature = "zbus", not(feature = "dbus")))]
+fn get_capabilities_at_bus(
+    bus: NotificationBus,
+    call_timeout: Option<Duration>,
+) -> Result<Vec<String>> {
+    runtime::block_on(zbus_rs::get_capabilities_at_bus(bus, call_timeout))
+}
+
+#[cfg(all(feature = "dbus", not(feature = "zbus")))]
+fn get_capabilities_at_bus(
+    bus: NotificationBus,
+    call_timeout: Option<Duration>,
+) -> Result<Vec<String>> {
+    dbus_rs::get_capabilities_at_bus(bus, call_timeout)
+}
+
+#[cfg(all(feature = "dbus", feature = "zbus"))]
+fn get_capabilities_at_bus(
+    bus: NotificationBus,
+    call_timeout: Option<Duration>,
+) -> Result<Vec<String>> {
+    if std::env::var(DBUS_SWITCH_VAR).is_ok() {
+        dbus_rs::get_capabilities_at_bus(bus, call_timeout)
+    } else {
+        runtime::block_on(zbus_rs::get_capabilities_at_bus(bus, call_timeout))
+    }
+}
+
+/// Whether actions shown by this process can actually be invoked.
+///
+/// This is the case if the session bus is reachable and the notification server advertises the `"actions"` capability.
+/// Use it to fall back to a notification without buttons where nobody could receive them.
+/// Keep in mind that callbacks only arrive while the process is still running and holds on to
+/// the [`NotificationHandle`], a CLI that exits right after sending will never see them.
+///
+/// ```no_run
+/// # use notify_rust::*;
+/// let mut notification = Notification::new();
+/// notification.summary("Build finished");
+/// if notify_rust::actions_supported() {
+///     notification.action("open", "Open log");
+/// } else {
+///     notification.body("see build.log");
+/// }
+/// notification.show().unwrap();
+/// ```
+///
+/// (xdg only)
+pub fn actions_supported() -> bool {
+    session_bus_known(
+        std::env::var_os("DBUS_SESSION_BUS_ADDRESS"),
+        std::env::var_os("XDG_RUNTIME_DIR"),
+    ) && get_capabilities().is_ok_and(|capabilities| {
+        capabilities
+            .iter()
+            .any(|capability| capability == "actions")
+    })
+}
+
+/// Whether there is a session bus to connect to, either by its address or at the default socket.
+fn session_bus_known(address: Option<OsString>, runtime_dir: Option<OsString>) -> bool {
+    address.is_some_and(|address| !address.is_empty())
+        || runtime_dir.is_some_and(|dir| Path::new(&dir).join("bus").exists())
+}
+
+/// Returns a struct containing `ServerInformation`.
+///
+/// This struct contains `name`, `vendor`, `version` and `spec_version` of the notification server
+/// running.
+///
+/// (zbus only)
+#[cfg(all(feature = "zbus", not(feature = "dbus")))]
 pub fn get_server_information() -> Result<ServerInformation> {
-    block_on(zbus_rs::get_server_information())
+    runtime::block_on(zbus_rs::get_server_information())
 }
 
 /// Returns a struct containing `ServerInformation`.
@@ -448,13 +1287,155 @@ pub fn get_server_information() -> Result<ServerInformation> {
     if std::env::var(DBUS_SWITCH_VAR).is_ok() {
         dbus_rs::get_server_information()
     } else {
-        block_on(zbus_rs::get_server_information())
+        runtime::block_on(zbus_rs::get_server_information())
+    }
+}
+
+/// Waits for the first of `handles` the user responds to and closes all others.
+///
+/// Returns the index of that handle together with the response,
+/// which is [`Closed`](OwnedActionResponse::Closed) if the user dismissed it instead of picking an action.
+/// Returns `None` if `handles` is empty or all their connections are gone.
+///
+/// ```no_run
+/// # use notify_rust::*;
+/// let handles = ["Coffee", "Tea"]
+///     .iter()
+///     .map(|drink| {
+///         Notification::new()
+///             .summary(drink)
+///             .action("default", "Pick")
+///             .show()
+///             .unwrap()
+///     })
+///     .collect();
+///
+/// if let Some((picked, OwnedActionResponse::Custom(_))) = notify_rust::select_action(handles) {
+///     println!("picked #{}", picked);
+/// }
+/// ```
+///
+/// With both dbus-rs and zbus, all handles have to be shown with the same one.
+///
+/// (xdg only)
+pub fn select_action(handles: Vec<NotificationHandle>) -> Option<(usize, OwnedActionResponse)> {
+    let first = first_response(&handles);
+    if let Some((picked, _)) = first {
+        for (index, handle) in handles.into_iter().enumerate() {
+            if index != picked {
+                handle.close();
+            }
+        }
+    }
+    first
+}
+
+fn first_response(handles: &[NotificationHandle]) -> Option<(usize, OwnedActionResponse)> {
+    match handles.first()?.inner {
+        #[cfg(feature = "dbus")]
+        NotificationHandleInner::Dbus(_) => {
+            let handles = handles
+                .iter()
+                .enumerate()
+                .filter_map(|(index, handle)| Some((index, handle.inner.as_dbus()?)))
+                .collect::<Vec<_>>();
+            dbus_rs::select_action(&handles)
+        }
+        #[cfg(feature = "zbus")]
+        NotificationHandleInner::Zbus(_) => {
+            let handles = handles
+                .iter()
+                .enumerate()
+                .filter_map(|(index, handle)| Some((index, handle.inner.as_zbus()?)))
+                .collect::<Vec<_>>();
+            zbus_rs::select_action_blocking(&handles)
+        }
+        NotificationHandleInner::DryRun(ref inner) => Some((0, inner.response.clone())),
+    }
+}
+
+/// Returns the introspection XML of the notification server's object.
+///
+/// This lists every interface and method the server offers at `/org/freedesktop/Notifications`,
+/// including vendor extensions that [`get_capabilities()`] doesn't tell about.
+/// See [`introspect_server_methods()`] for just the method names.
+///
+/// (zbus only)
+#[cfg(all(feature = "zbus", not(feature = "dbus")))]
+pub fn introspect_server() -> Result<String> {
+    runtime::block_on(zbus_rs::introspect())
+}
+
+/// Returns the introspection XML of the notification server's object.
+///
+/// This lists every interface and method the server offers at `/org/freedesktop/Notifications`,
+/// including vendor extensions that [`get_capabilities()`] doesn't tell about.
+/// See [`introspect_server_methods()`] for just the method names.
+///
+/// (dbus-rs only)
+#[cfg(all(feature = "dbus", not(feature = "zbus")))]
+pub fn introspect_server() -> Result<String> {
+    dbus_rs::introspect()
+}
+
+/// Returns the introspection XML of the notification server's object.
+///
+/// This lists every interface and method the server offers at `/org/freedesktop/Notifications`,
+/// including vendor extensions that [`get_capabilities()`] doesn't tell about.
+/// See [`introspect_server_methods()`] for just the method names.
+///
+/// both dbus-rs and zbus, switch via `$ZBUS_NOTIFICATION`
+#[cfg(all(feature = "dbus", feature = "zbus"))]
+pub fn introspect_server() -> Result<String> {
+    if std::env::var(DBUS_SWITCH_VAR).is_ok() {
+        dbus_rs::introspect()
+    } else {
+        runtime::block_on(zbus_rs::introspect())
+    }
+}
+
+/// Returns the methods of the notification server's object, prefixed with their interface.
+///
+/// ```no_run
+/// let methods = notify_rust::introspect_server_methods().unwrap();
+/// if methods.iter().any(|method| method == "org.gnome.Shell.Notifications.Frobnicate") {
+///     // ...
+/// }
+/// ```
+///
+/// (xdg only)
+pub fn introspect_server_methods() -> Result<Vec<String>> {
+    introspect_server().map(|xml| methods_from_introspection(&xml))
+}
+
+/// Collects `interface.method` for every `<method>` in `xml`.
+fn methods_from_introspection(xml: &str) -> Vec<String> {
+    fn name_of(tag: &str) -> Option<&str> {
+        let (_, rest) = tag.split_once("name=")?;
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        rest[1..].split(quote).next()
+    }
+
+    let mut interface = None;
+    let mut methods = Vec::new();
+    for tag in xml.split('<').skip(1) {
+        let tag = tag.split('>').next().unwrap_or_default();
+        if tag.starts_with("interface") {
+            interface = name_of(tag);
+        } else if tag.starts_with("/interface") {
+            interface = None;
+        } else if tag.starts_with("method") {
+            if let (Some(interface), Some(method)) = (interface, name_of(tag)) {
+                methods.push(format!("{}.{}", interface, method));
+            }
+        }
     }
+    methods
 }
 
 /// Return value of `get_server_information()`.
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(any(feature = "zbus", feature = "serde"), derive(serde::Deserialize))]
 #[cfg_attr(feature = "zbus", derive(zvariant::Type))]
 pub struct ServerInformation {
     /// The product name of the server.
@@ -467,6 +1448,193 @@ pub struct ServerInformation {
     pub spec_version: String,
 }
 
+impl ServerInformation {
+    /// Constructs a `ServerInformation` by hand.
+    ///
+    /// Usually you get this from [`get_server_information()`],
+    /// this is useful to fake a certain server in your tests.
+    ///
+    /// ```
+    /// # use notify_rust::ServerInformation;
+    /// let info = ServerInformation::new("dunst", "knopwob", "1.9.0", "1.2");
+    /// assert_eq!(info.name, "dunst");
+    /// ```
+    pub fn new(name: &str, vendor: &str, version: &str, spec_version: &str) -> Self {
+        ServerInformation {
+            name: name.to_owned(),
+            vendor: vendor.to_owned(),
+            version: version.to_owned(),
+            spec_version: spec_version.to_owned(),
+        }
+    }
+}
+
+/// Returns the length limits the notification server advertises, as far as it does.
+///
+/// This is derived from [`get_capabilities()`], see [`ServerLimits`].
+///
+/// (xdg only)
+pub fn get_server_limits() -> Result<ServerLimits> {
+    get_capabilities().map(|capabilities| ServerLimits::from_capabilities(&capabilities))
+}
+
+/// How long summary and body may be before the server cuts them off, in characters.
+///
+/// The specification has no way to advertise this, and how much of a text fits depends on font and screen size anyway.
+///
+/// **Experimental:** the only thing recognized are capabilities of the form `x-<vendor>-max-summary-length=<n>`
+/// and `x-<vendor>-max-body-length=<n>`.
+/// That format is a proposal of this crate, no server is known to advertise it yet and it may still change.
+/// Expect `None` and fall back to a limit of your own.
+///
+/// ```no_run
+/// # use notify_rust::*;
+/// # let long_text = "";
+/// let limits = get_server_limits().unwrap_or_default();
+/// Notification::new()
+///     .summary("Build failed")
+///     .body(long_text)
+///     .validate_lengths(limits.max_summary_length_or(64), limits.max_body_length_or(1024))
+///     .show()
+///     .unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ServerLimits {
+    max_summary_length: Option<usize>,
+    max_body_length: Option<usize>,
+}
+
+impl ServerLimits {
+    /// Picks the limits out of the result of [`get_capabilities()`].
+    ///
+    /// ```
+    /// # use notify_rust::ServerLimits;
+    /// let limits = ServerLimits::from_capabilities(&["body", "x-acme-max-body-length=200"]);
+    /// assert_eq!(limits.max_body_length(), Some(200));
+    /// assert_eq!(limits.max_summary_length(), None);
+    /// ```
+    pub fn from_capabilities<S: AsRef<str>>(capabilities: &[S]) -> Self {
+        let limit = |name: &str| {
+            capabilities.iter().find_map(|capability| {
+                let (key, value) = capability.as_ref().split_once('=')?;
+                let vendor = key
+                    .strip_prefix("x-")?
+                    .strip_suffix(name)?
+                    .strip_suffix('-')?;
+                if vendor.is_empty() {
+                    return None;
+                }
+                value.trim().parse().ok()
+            })
+        };
+        ServerLimits {
+            max_summary_length: limit("max-summary-length"),
+            max_body_length: limit("max-body-length"),
+        }
+    }
+
+    /// The longest summary the server advertises to display, `None` if unknown.
+    pub fn max_summary_length(&self) -> Option<usize> {
+        self.max_summary_length
+    }
+
+    /// The longest body the server advertises to display, `None` if unknown.
+    pub fn max_body_length(&self) -> Option<usize> {
+        self.max_body_length
+    }
+
+    /// The advertised summary limit, or `default` if there is none.
+    pub fn max_summary_length_or(&self, default: usize) -> usize {
+        self.max_summary_length.unwrap_or(default)
+    }
+
+    /// The advertised body limit, or `default` if there is none.
+    pub fn max_body_length_or(&self, default: usize) -> usize {
+        self.max_body_length.unwrap_or(default)
+    }
+}
+
+/// Where a notification is likely shown, see [`Notification::predicted_presentation()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Presentation {
+    /// Pops up and is gone once it expires or is dismissed.
+    Banner,
+    /// Goes to the notification center (or tray) without popping up.
+    Persistent,
+    /// Pops up and is kept in the notification center afterwards.
+    Both,
+}
+
+/// Returns how the notification server shows images, see [`IconSupport`].
+///
+/// (xdg only)
+pub fn get_icon_support() -> Result<IconSupport> {
+    get_capabilities().map(|capabilities| IconSupport::from_capabilities(&capabilities))
+}
+
+/// Whether the server shows images, and whether it animates them.
+///
+/// Servers advertise this as the `"icon-multi"` or `"icon-static"` capability.
+/// With `"icon-multi"` images sent as several frames, e.g. via `Notification::animated_icon()`, are animated,
+/// servers advertising only `"icon-static"` show the first frame.
+///
+/// ```no_run
+/// # use notify_rust::*;
+/// match get_icon_support().unwrap_or_default() {
+///     IconSupport::Animated => println!("spinners spin"),
+///     IconSupport::Static => println!("spinners stand still"),
+///     IconSupport::Unsupported => println!("no images at all"),
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IconSupport {
+    /// `"icon-multi"`, images with several frames are animated.
+    Animated,
+    /// `"icon-static"`, only the first frame of an image is shown.
+    Static,
+    /// Neither is advertised, images are probably not shown at all.
+    #[default]
+    Unsupported,
+}
+
+impl IconSupport {
+    /// Picks the icon capability out of the result of [`get_capabilities()`].
+    ///
+    /// ```
+    /// # use notify_rust::IconSupport;
+    /// assert_eq!(IconSupport::from_capabilities(&["body", "icon-static"]), IconSupport::Static);
+    /// assert_eq!(IconSupport::from_capabilities(&["icon-multi", "icon-static"]), IconSupport::Animated);
+    /// ```
+    pub fn from_capabilities<S: AsRef<str>>(capabilities: &[S]) -> Self {
+        let supports = |capability: &str| capabilities.iter().any(|c| c.as_ref() == capability);
+        if supports("icon-multi") {
+            IconSupport::Animated
+        } else if supports("icon-static") {
+            IconSupport::Static
+        } else {
+            IconSupport::Unsupported
+        }
+    }
+
+    /// Whether images are shown at all.
+    pub fn shows_images(self) -> bool {
+        self != IconSupport::Unsupported
+    }
+}
+
+/// Where the icon of a notification comes from, see [`Notification::effective_icon()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IconSource<'a> {
+    /// The pixels sent as `image-data`, e.g. via `Notification::image_data()`.
+    ImageData,
+    /// The file sent as `image-path`, via [`Notification::image_path()`].
+    ImagePath(&'a str),
+    /// The icon name or file sent as `app_icon`, via [`Notification::icon()`].
+    AppIcon(&'a str),
+    /// Neither is set, the server shows its default icon, if any.
+    None,
+}
+
 /// Strictly internal.
 /// The NotificationServer implemented here exposes a "Stop" function.
 /// stops the notification server
@@ -487,7 +1655,7 @@ pub fn handle_action<F>(id: u32, func: F)
 where
     F: FnOnce(&ActionResponse),
 {
-    block_on(zbus_rs::handle_action(id, func));
+    zbus_rs::handle_action(id, func);
 }
 
 /// Listens for the `ActionInvoked(UInt32, String)` Signal.
@@ -516,7 +1684,7 @@ where
     if std::env::var(DBUS_SWITCH_VAR).is_ok() {
         dbus_rs::handle_action(id, func);
     } else {
-        block_on(zbus_rs::handle_action(id, func));
+        zbus_rs::handle_action(id, func);
     }
 }
 
@@ -524,7 +1692,7 @@ where
 ///
 /// ## Specification
 /// As listed under [Table 8. `NotificationClosed` Parameters](https://specifications.freedesktop.org/notification-spec/latest/ar01s09.html#idm46350804042704)
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum CloseReason {
     /// The notification expired
     Expired,
@@ -532,7 +1700,10 @@ pub enum CloseReason {
     Dismissed,
     /// The notification was closed by a call to `CloseNotification`
     CloseAction,
-    /// Undefined/Reserved reason
+    /// Undefined/Reserved reason, with the code the server sent
+    ///
+    /// That includes `4`, which the specification reserves and some servers send
+    /// when they close notifications on their own or don't know why they were closed.
     Other(u32),
 }
 
@@ -547,7 +1718,10 @@ impl From<u32> for CloseReason {
     }
 }
 
-/// Helper Trait implemented by `Fn()`
+/// Helper Trait implemented by `FnOnce(&ActionResponse)`
+///
+/// Boxed closures (`Box<dyn FnOnce(&ActionResponse)>`) implement it as well,
+/// so a handler can be picked at runtime.
 pub trait ActionResponseHandler {
     fn call(self, response: &ActionResponse);
 }
@@ -562,7 +1736,71 @@ where
     }
 }
 
+/// An owned, type erased action handler, to keep handlers of different types side by side.
+///
+/// Any `Fn(&ActionResponse) + Send + 'static` converts into one.
+/// Unlike a plain closure it can be passed by reference, so one handler can serve several notifications.
+///
+/// ```no_run
+/// # use notify_rust::*;
+/// let mut handlers: Vec<(NotificationHandle, BoxedActionHandler)> = Vec::new();
+///
+/// let handle = Notification::new().summary("Mail").action("open", "Open").show().unwrap();
+/// handlers.push((handle, BoxedActionHandler::new(|response| println!("mail: {:?}", response))));
+///
+/// let handle = Notification::new().summary("Chat").action("reply", "Reply").show().unwrap();
+/// handlers.push((handle, (|response: &ActionResponse| println!("chat: {:?}", response)).into()));
+///
+/// for (handle, handler) in &handlers {
+///     handle.wait_for_action_ref(handler);
+/// }
+/// ```
+pub struct BoxedActionHandler(Box<dyn Fn(&ActionResponse) + Send>);
+
+impl BoxedActionHandler {
+    /// Boxes `handler`.
+    pub fn new<F>(handler: F) -> Self
+    where
+        F: Fn(&ActionResponse) + Send + 'static,
+    {
+        BoxedActionHandler(Box::new(handler))
+    }
+}
+
+impl<F> From<F> for BoxedActionHandler
+where
+    F: Fn(&ActionResponse) + Send + 'static,
+{
+    fn from(handler: F) -> Self {
+        BoxedActionHandler::new(handler)
+    }
+}
+
+impl std::fmt::Debug for BoxedActionHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BoxedActionHandler").finish_non_exhaustive()
+    }
+}
+
+impl ActionResponseHandler for BoxedActionHandler {
+    fn call(self, response: &ActionResponse) {
+        (self.0)(response);
+    }
+}
+
+impl ActionResponseHandler for &BoxedActionHandler {
+    fn call(self, response: &ActionResponse) {
+        (self.0)(response);
+    }
+}
+
 /// Response to an action
+///
+/// More kinds of responses may be added in the future, so a `match` needs a wildcard arm.
+/// Use [`as_custom()`](Self::as_custom) and [`is_closed()`](Self::is_closed) if you only care about one of them.
+/// [`OwnedActionResponse`] is the same without the borrow, to keep it around or send it to another thread.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ActionResponse<'a> {
     /// Custom Action configured by the Notification.
     Custom(&'a str),
@@ -571,19 +1809,128 @@ pub enum ActionResponse<'a> {
     Closed(CloseReason),
 }
 
+impl<'a> ActionResponse<'a> {
+    /// The key of the invoked action, if an action was invoked.
+    pub fn as_custom(&self) -> Option<&'a str> {
+        match self {
+            ActionResponse::Custom(action) => Some(action),
+            ActionResponse::Closed(_) => None,
+        }
+    }
+
+    /// Whether the notification was closed.
+    pub fn is_closed(&self) -> bool {
+        self.close_reason().is_some()
+    }
+
+    /// Why the notification was closed, if it was.
+    pub fn close_reason(&self) -> Option<CloseReason> {
+        match self {
+            ActionResponse::Closed(reason) => Some(*reason),
+            ActionResponse::Custom(_) => None,
+        }
+    }
+
+    /// Copies the response, so it no longer borrows from the signal.
+    pub fn to_owned_response(&self) -> OwnedActionResponse {
+        match *self {
+            ActionResponse::Custom(action) => OwnedActionResponse::Custom(action.to_owned()),
+            ActionResponse::Closed(reason) => OwnedActionResponse::Closed(reason),
+        }
+    }
+}
+
 impl<'a> From<&'a str> for ActionResponse<'a> {
     fn from(raw: &'a str) -> Self {
         Self::Custom(raw)
     }
 }
 
+/// Owned version of [`ActionResponse`], so it can be stored or passed between threads.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OwnedActionResponse {
+    /// Custom Action configured by the Notification.
+    Custom(String),
+
+    /// The Notification was closed.
+    Closed(CloseReason),
+}
+
+impl OwnedActionResponse {
+    /// Borrows this as an [`ActionResponse`], e.g. to pass it to an action handler.
+    pub fn as_response(&self) -> ActionResponse<'_> {
+        match self {
+            OwnedActionResponse::Custom(action) => ActionResponse::Custom(action),
+            OwnedActionResponse::Closed(reason) => ActionResponse::Closed(*reason),
+        }
+    }
+
+    /// The key of the invoked action, if an action was invoked.
+    pub fn as_custom(&self) -> Option<&str> {
+        self.as_response().as_custom()
+    }
+
+    /// Whether the notification was closed.
+    pub fn is_closed(&self) -> bool {
+        self.as_response().is_closed()
+    }
+
+    /// Why the notification was closed, if it was.
+    pub fn close_reason(&self) -> Option<CloseReason> {
+        self.as_response().close_reason()
+    }
+
+    /// Parses the key of an invoked action, see [`NotificationHandle::wait_for_action_typed()`].
+    fn parse_action<A>(self) -> Result<TypedActionResponse<A>>
+    where
+        A: std::str::FromStr,
+        A::Err: std::fmt::Display,
+    {
+        match self {
+            OwnedActionResponse::Custom(key) => match key.parse() {
+                Ok(action) => Ok(TypedActionResponse::Action(action)),
+                Err(error) => Err(ErrorKind::UnknownAction {
+                    message: error.to_string(),
+                    key,
+                }
+                .into()),
+            },
+            OwnedActionResponse::Closed(reason) => Ok(TypedActionResponse::Closed(reason)),
+        }
+    }
+}
+
+/// An [`OwnedActionResponse`] whose action key was parsed into `A`, see [`NotificationHandle::wait_for_action_typed()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypedActionResponse<A> {
+    /// The action that was invoked.
+    Action(A),
+
+    /// The Notification was closed.
+    Closed(CloseReason),
+}
+
+impl From<ActionResponse<'_>> for OwnedActionResponse {
+    fn from(response: ActionResponse<'_>) -> Self {
+        response.to_owned_response()
+    }
+}
+
 /// Your handy callback for the `Close` signal of your Notification.
 ///
-/// This is implemented by `Fn()` and `Fn(CloseReason)`, so there is probably no good reason for you to manually implement this trait.
+/// This is implemented by `Fn()`, `Fn(CloseReason)` and `Fn(CloseReason, &Notification)`, so there is probably no good reason for you to manually implement this trait.
 /// Should you find one anyway, please notify me and I'll gladly remove this obviously redundant comment.
 pub trait CloseHandler<T> {
     /// This is called with the [`CloseReason`].
     fn call(&self, reason: CloseReason);
+
+    /// This is called with the [`CloseReason`] and the notification as it was last sent.
+    ///
+    /// Forwards to [`call()`](Self::call) by default.
+    fn call_with_notification(&self, reason: CloseReason, _notification: &Notification) {
+        self.call(reason);
+    }
 }
 
 impl<F> CloseHandler<CloseReason> for F
@@ -603,3 +1950,224 @@ where
         self();
     }
 }
+
+impl<F> CloseHandler<(CloseReason, Notification)> for F
+where
+    F: Fn(CloseReason, &Notification),
+{
+    /// Without a notification at hand this passes an empty one.
+    fn call(&self, reason: CloseReason) {
+        self(reason, &Notification::new());
+    }
+
+    fn call_with_notification(&self, reason: CloseReason, notification: &Notification) {
+        self(reason, notification);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn methods_from_xml() {
+        let xml = r#"<!DOCTYPE node PUBLIC "-//freedesktop//DTD D-BUS Object Introspection 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/introspect.dtd">
+<node>
+  <interface name="org.freedesktop.DBus.Introspectable">
+    <method name="Introspect">
+      <arg type="s" direction="out"/>
+    </method>
+  </interface>
+  <interface name='org.freedesktop.Notifications'>
+    <method name='GetCapabilities'><arg type='as' direction='out'/></method>
+    <method name="Notify"/>
+    <signal name="ActionInvoked"><arg type="u"/><arg type="s"/></signal>
+    <property name="methods" type="s" access="read"/>
+  </interface>
+  <method name="Orphan"/>
+</node>"#;
+        assert_eq!(
+            methods_from_introspection(xml),
+            [
+                "org.freedesktop.DBus.Introspectable.Introspect",
+                "org.freedesktop.Notifications.GetCapabilities",
+                "org.freedesktop.Notifications.Notify",
+            ]
+        );
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn handles_are_send_and_sync() {
+        assert_send_sync::<LightHandle>();
+        assert_send_sync::<ScheduledNotification>();
+        assert_send_sync::<OwnedActionResponse>();
+        #[cfg(not(feature = "dbus"))]
+        assert_send_sync::<NotificationHandle>();
+    }
+
+    #[test]
+    fn session_bus_from_environment() {
+        let missing = std::env::temp_dir().join("notify-rust-no-runtime-dir");
+        assert!(session_bus_known(Some("unix:path=/run/bus".into()), None));
+        assert!(!session_bus_known(Some("".into()), None));
+        assert!(!session_bus_known(None, Some(missing.into())));
+        assert!(!session_bus_known(None, None));
+    }
+
+    #[test]
+    fn limits_from_capabilities() {
+        let limits = ServerLimits::from_capabilities(&[
+            "body",
+            "x-acme-max-summary-length=40",
+            "x-acme-max-body-length= 300",
+        ]);
+        assert_eq!(limits.max_summary_length(), Some(40));
+        assert_eq!(limits.max_body_length(), Some(300));
+
+        for unknown in [
+            &["body", "body-markup"][..],
+            &["max-body-length=300"],
+            &["x-max-body-length=300"],
+            &["x-acme-max-body-length=lots"],
+            &["x-acme-max-body-lengthy=300"],
+        ] {
+            let limits = ServerLimits::from_capabilities(unknown);
+            assert_eq!(limits.max_body_length(), None, "{:?}", unknown);
+            assert_eq!(limits.max_body_length_or(1024), 1024);
+        }
+    }
+
+    #[test]
+    fn close_reasons_from_codes() {
+        for (code, reason) in [
+            (1, CloseReason::Expired),
+            (2, CloseReason::Dismissed),
+            (3, CloseReason::CloseAction),
+            (4, CloseReason::Other(4)),
+            (0, CloseReason::Other(0)),
+            (u32::MAX, CloseReason::Other(u32::MAX)),
+        ] {
+            assert_eq!(CloseReason::from(code), reason, "{}", code);
+        }
+    }
+
+    #[test]
+    fn action_response_helpers() {
+        let custom = ActionResponse::Custom("reply");
+        assert_eq!(custom.as_custom(), Some("reply"));
+        assert!(!custom.is_closed());
+        assert_eq!(custom.close_reason(), None);
+
+        let closed = ActionResponse::Closed(CloseReason::Dismissed);
+        assert_eq!(closed.as_custom(), None);
+        assert!(closed.is_closed());
+        assert_eq!(closed.close_reason(), Some(CloseReason::Dismissed));
+
+        for response in [custom, closed] {
+            let owned = OwnedActionResponse::from(response);
+            assert_eq!(owned.as_response(), response);
+            assert_eq!(owned.as_custom(), response.as_custom());
+            assert_eq!(owned.is_closed(), response.is_closed());
+        }
+    }
+
+    #[test]
+    fn typed_actions_parse_keys() {
+        #[derive(Debug, PartialEq)]
+        enum Answer {
+            Accept,
+        }
+        impl std::str::FromStr for Answer {
+            type Err = String;
+            fn from_str(key: &str) -> std::result::Result<Self, String> {
+                match key {
+                    "accept" => Ok(Answer::Accept),
+                    _ => Err("not an answer".into()),
+                }
+            }
+        }
+
+        let accepted = OwnedActionResponse::Custom("accept".into()).parse_action();
+        assert_eq!(
+            accepted.unwrap(),
+            TypedActionResponse::Action(Answer::Accept)
+        );
+        let closed = OwnedActionResponse::Closed(CloseReason::Expired).parse_action::<Answer>();
+        assert_eq!(
+            closed.unwrap(),
+            TypedActionResponse::Closed(CloseReason::Expired)
+        );
+
+        let unknown = OwnedActionResponse::Custom("default".into())
+            .parse_action::<Answer>()
+            .unwrap_err();
+        assert!(matches!(
+            unknown.kind(),
+            ErrorKind::UnknownAction { key, message } if key == "default" && message == "not an answer"
+        ));
+    }
+
+    #[test]
+    fn boxed_action_handlers_are_reusable() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&calls);
+        let handlers = [
+            BoxedActionHandler::new(move |_| {
+                counted.fetch_add(1, Ordering::SeqCst);
+            }),
+            BoxedActionHandler::from(|response: &ActionResponse| assert!(response.is_closed())),
+        ];
+
+        (&handlers[0]).call(&ActionResponse::Custom("first"));
+        (&handlers[0]).call(&ActionResponse::Custom("second"));
+        for handler in handlers {
+            handler.call(&ActionResponse::Closed(CloseReason::Expired));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    type BoxedHandler<'a> = Box<dyn FnOnce(&ActionResponse) + 'a>;
+
+    #[test]
+    fn boxed_closures_are_handlers() {
+        fn handle(handler: impl ActionResponseHandler, response: &ActionResponse) {
+            handler.call(response);
+        }
+        fn _assert_object_safe(_: &dyn ActionResponseHandler) {}
+
+        let received = std::cell::RefCell::new(Vec::new());
+        let handlers: Vec<BoxedHandler> = vec![
+            Box::new(|response| received.borrow_mut().push(response.to_owned_response())),
+            Box::new(|response| {
+                if let Some(reason) = response.close_reason() {
+                    received
+                        .borrow_mut()
+                        .push(OwnedActionResponse::Closed(reason));
+                }
+            }),
+        ];
+        let responses = [
+            ActionResponse::Custom("open"),
+            ActionResponse::Closed(CloseReason::Expired),
+        ];
+        for (handler, response) in handlers.into_iter().zip(&responses) {
+            handle(handler, response);
+        }
+
+        assert_eq!(
+            *received.borrow(),
+            [
+                OwnedActionResponse::Custom("open".into()),
+                OwnedActionResponse::Closed(CloseReason::Expired)
+            ]
+        );
+    }
+}
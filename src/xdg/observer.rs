@@ -0,0 +1,80 @@
+//! A single process wide hook that sees every notification before it is sent.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, RwLock,
+};
+
+use crate::notification::Notification;
+
+type Observer = Arc<dyn Fn(&Notification) + Send + Sync>;
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static OBSERVER: RwLock<Option<Observer>> = RwLock::new(None);
+
+/// Installs a callback that is invoked with every notification this crate sends.
+///
+/// The observer is called right before the notification goes out over D-Bus,
+/// for [`show()`](Notification::show) as well as for updates through a [`NotificationHandle`](crate::NotificationHandle).
+/// Use it to count, sample or record notifications without wrapping every call site.
+/// Installing an observer replaces the previous one.
+///
+/// With zbus the observer runs on the internal background thread, so keep it short.
+///
+/// ```no_run
+/// # use notify_rust::*;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// static SENT: AtomicUsize = AtomicUsize::new(0);
+///
+/// notify_rust::set_send_observer(Box::new(|notification: &Notification| {
+///     SENT.fetch_add(1, Ordering::Relaxed);
+///     eprintln!("sending {:?}", notification.summary);
+/// }));
+///
+/// Notification::new().summary("counted").show().unwrap();
+/// ```
+///
+/// (xdg only)
+pub fn set_send_observer(observer: Box<dyn Fn(&Notification) + Send + Sync>) {
+    *OBSERVER
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(observer.into());
+    INSTALLED.store(true, Ordering::Release);
+}
+
+/// Passes `notification` to the installed observer, if there is one.
+pub(crate) fn observe(notification: &Notification) {
+    if !INSTALLED.load(Ordering::Acquire) {
+        return;
+    }
+    // don't hold the lock while the observer runs, it might want to install a new one
+    let observer = OBSERVER
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone();
+    if let Some(observer) = observer {
+        observer(notification);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn observer_sees_notifications() {
+        static SEEN: AtomicUsize = AtomicUsize::new(0);
+        set_send_observer(Box::new(|notification| {
+            if notification.summary == "observed" {
+                SEEN.fetch_add(1, Ordering::SeqCst);
+            }
+        }));
+
+        observe(Notification::new().summary("observed"));
+        observe(Notification::new().summary("ignored"));
+        observe(Notification::new().summary("observed"));
+
+        assert_eq!(SEEN.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,63 @@
+//! Decides what the infallible convenience methods do with an error, see [`set_panic_hook()`].
+
+use std::sync::{Arc, RwLock};
+
+use crate::error::Error;
+
+type Hook = Arc<dyn Fn(&Error) + Send + Sync>;
+
+static HOOK: RwLock<Option<Hook>> = RwLock::new(None);
+
+/// Installs a callback for errors of the methods that have no way of returning them.
+///
+/// [`NotificationHandle::update()`](crate::NotificationHandle::update), [`NotificationHandle::close()`](crate::NotificationHandle::close)
+/// and [`handle_action()`](crate::handle_action) panic if talking to the server fails.
+/// Once a hook is installed they hand it the error instead and return,
+/// so an application can log failures in one place instead of switching every call site to a fallible method.
+/// Installing a hook replaces the previous one.
+///
+/// ```no_run
+/// # use notify_rust::*;
+/// notify_rust::set_panic_hook(|error: &error::Error| eprintln!("notification failed: {}", error));
+///
+/// let mut handle = Notification::new().summary("Syncing").show().unwrap();
+/// handle.summary("Synced");
+/// handle.update(); // logs instead of panicking if the server went away
+/// ```
+///
+/// (xdg only)
+pub fn set_panic_hook(hook: impl Fn(&Error) + Send + Sync + 'static) {
+    *HOOK
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(hook));
+}
+
+/// Passes `error` to the installed hook, panics if there is none.
+pub(crate) fn report(error: Error) {
+    // don't hold the lock while the hook runs, it might want to install a new one
+    let hook = HOOK
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone();
+    match hook {
+        Some(hook) => hook(&error),
+        None => panic!("called `Result::unwrap()` on an `Err` value: {:?}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn hook_replaces_panic() {
+        assert!(std::panic::catch_unwind(|| report(Error::from("unhooked"))).is_err());
+
+        static REPORTED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        set_panic_hook(|error: &Error| REPORTED.lock().unwrap().push(error.to_string()));
+        report(Error::from("hooked"));
+
+        assert_eq!(*REPORTED.lock().unwrap(), ["hooked"]);
+    }
+}
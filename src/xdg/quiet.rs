@@ -0,0 +1,41 @@
+//! Switch for the diagnostics this crate prints while carrying on, see [`set_quiet`].
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Stops this crate from printing or logging diagnostics about problems it recovers from.
+///
+/// That is currently hints from the server that can't be parsed,
+/// an id that [`persist_id_to()`](crate::Notification::persist_id_to) couldn't write
+/// and an image that `image_url()` left out.
+/// None of these fail the operation they occur in,
+/// but the output can be confusing in command line tools.
+/// Errors that are returned to you are not affected.
+///
+/// ```no_run
+/// notify_rust::set_quiet(true);
+/// ```
+///
+/// (xdg only)
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Whether diagnostics should be kept to ourselves.
+pub(crate) fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggles() {
+        set_quiet(true);
+        assert!(is_quiet());
+        set_quiet(false);
+        assert!(!is_quiet());
+    }
+}
@@ -0,0 +1,85 @@
+//! Handles for notifications that stay around for hours, see [`ReconnectingHandle`].
+
+use std::ops::{Deref, DerefMut};
+
+use crate::{
+    error::*,
+    notification::Notification,
+    xdg::{dry_run, LightHandle},
+};
+
+/// A handle that keeps working after its D-Bus connection died, e.g. a status notification held across suspend and resume.
+///
+/// Returned by [`Notification::show_reconnecting()`], it holds no connection, only the id and the notification as it was last sent.
+/// [`update()`](Self::update) and [`close()`](Self::close) connect anew every time.
+/// If the notification server was restarted in the meantime and no longer knows the id,
+/// `update()` shows the notification again and the handle takes on the id the new server assigned.
+///
+/// Signals only reach connections that are still open, so there is no waiting for actions,
+/// use a [`NotificationHandle`](crate::NotificationHandle) for that.
+///
+/// ```no_run
+/// # use notify_rust::*;
+/// # fn _doc() -> error::Result<()> {
+/// let mut status = Notification::new()
+///     .summary("VPN")
+///     .body("connected")
+///     .timeout(Timeout::Never)
+///     .show_reconnecting()?;
+/// // ... hours and a suspend later
+/// status.body("reconnecting");
+/// status.update()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// (xdg only)
+#[derive(Clone, Debug)]
+pub struct ReconnectingHandle {
+    id: u32,
+    notification: Notification,
+}
+
+impl ReconnectingHandle {
+    pub(crate) fn new(id: u32, notification: Notification) -> Self {
+        ReconnectingHandle { id, notification }
+    }
+
+    /// Returns the Handle's id, which changes if an update had to show the notification again.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Sends the changed notification, replacing the one shown before.
+    pub fn update(&mut self) -> Result<()> {
+        self.id = self.notification.id(self.id).send_id()?;
+        Ok(())
+    }
+
+    /// Closes the notification.
+    pub fn close(self) -> Result<()> {
+        LightHandle {
+            id: self.id,
+            bus: self.notification.bus,
+            call_timeout: self.notification.call_timeout,
+            dry_run: dry_run::enabled(),
+        }
+        .close()
+    }
+}
+
+/// Required for `DerefMut`
+impl Deref for ReconnectingHandle {
+    type Target = Notification;
+
+    fn deref(&self) -> &Notification {
+        &self.notification
+    }
+}
+
+/// Allow you to easily modify notification properties
+impl DerefMut for ReconnectingHandle {
+    fn deref_mut(&mut self) -> &mut Notification {
+        &mut self.notification
+    }
+}
@@ -0,0 +1,140 @@
+//! Records sent notifications to a file and shows them again later, see [`record_to()`].
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{error::*, notification::Notification, replace_id::ReplaceId, xdg};
+
+/// One line of a recording.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Recorded {
+    /// Milliseconds since the recording started.
+    offset_ms: u64,
+    notification: Notification,
+}
+
+struct Recorder {
+    file: Mutex<File>,
+    started: Instant,
+}
+
+impl Recorder {
+    fn write(&self, notification: &Notification) -> Result<()> {
+        let offset_ms = self.started.elapsed().as_millis() as u64;
+        let mut line = serde_json::to_string(&Recorded {
+            offset_ms,
+            notification: notification.clone(),
+        })?;
+        line.push('\n');
+        let mut file = self
+            .file
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Appends every notification this crate sends to the file at `path`, one JSON object per line.
+///
+/// Each line holds the [serialized](Notification::to_json) notification and the milliseconds since recording started,
+/// so [`replay()`] can show them again with the same timing.
+/// This installs a [send observer](crate::set_send_observer), replacing the one installed before.
+///
+/// ```no_run
+/// # use notify_rust::*;
+/// notify_rust::record_to("/tmp/notifications.jsonl").unwrap();
+/// Notification::new().summary("recorded").show().unwrap();
+/// ```
+///
+/// (xdg only, needs the `serde` feature)
+pub fn record_to<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref().to_owned();
+    let recorder = Recorder {
+        file: Mutex::new(OpenOptions::new().create(true).append(true).open(&path)?),
+        started: Instant::now(),
+    };
+    xdg::set_send_observer(Box::new(move |notification: &Notification| {
+        if let Err(error) = recorder.write(notification) {
+            if !xdg::quiet::is_quiet() {
+                log::warn!("failed to record notification to {:?}: {}", path, error);
+            }
+        }
+    }));
+    Ok(())
+}
+
+/// Shows the notifications recorded by [`record_to()`] again, keeping the time between them.
+///
+/// Blocks until the last notification is shown and stops at the first line that can't be read or sent.
+/// Recorded ids are dropped, so replayed notifications don't replace whatever the server shows right now.
+///
+/// (xdg only, needs the `serde` feature)
+pub fn replay<P: AsRef<Path>>(path: P) -> Result<()> {
+    let mut previous = 0;
+    for recorded in read(path.as_ref())? {
+        let Recorded {
+            offset_ms,
+            mut notification,
+        } = recorded?;
+        thread::sleep(Duration::from_millis(offset_ms.saturating_sub(previous)));
+        previous = offset_ms;
+        notification.id = ReplaceId::new();
+        notification.show()?;
+    }
+    Ok(())
+}
+
+fn read(path: &Path) -> Result<impl Iterator<Item = Result<Recorded>>> {
+    let lines = BufReader::new(File::open(path)?).lines();
+    Ok(lines.filter_map(|line| match line {
+        Ok(line) if line.trim().is_empty() => None,
+        Ok(line) => Some(serde_json::from_str(&line).map_err(Error::from)),
+        Err(error) => Some(Err(error.into())),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_reads_back() {
+        let path = std::env::temp_dir().join(format!("notify-rust-record-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let recorder = Recorder {
+            file: Mutex::new(File::create(&path).unwrap()),
+            started: Instant::now(),
+        };
+        recorder
+            .write(Notification::new().summary("first").body("with body"))
+            .unwrap();
+        thread::sleep(Duration::from_millis(20));
+        recorder
+            .write(Notification::new().summary("second").action("ok", "OK"))
+            .unwrap();
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"\n")
+            .unwrap();
+
+        let recorded = read(&path).unwrap().collect::<Result<Vec<_>>>().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[0].notification.summary, "first");
+        assert_eq!(recorded[0].notification.body, "with body");
+        assert_eq!(recorded[1].notification.summary, "second");
+        assert_eq!(recorded[1].notification.actions[0].key, "ok");
+        assert!(recorded[1].offset_ms >= recorded[0].offset_ms + 20);
+    }
+}
@@ -0,0 +1,116 @@
+//! A dedicated thread that drives the futures of the blocking API.
+//!
+//! `zbus::block_on()` polls its future on whichever thread happens to call it.
+//! Instead of letting every caller drive its own D-Bus futures, the blocking functions of this crate
+//! hand their futures to a single background thread and only wait for the result.
+//! That way blocking calls coming in from arbitrary threads all end up on the same executor.
+
+use std::{
+    cell::Cell,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Once},
+    thread,
+};
+
+use async_executor::Executor;
+use zbus::export::futures_util::FutureExt;
+
+static EXECUTOR: Executor<'static> = Executor::new();
+static START: Once = Once::new();
+
+thread_local! {
+    static ON_RUNTIME: Cell<bool> = const { Cell::new(false) };
+}
+
+fn start() {
+    START.call_once(|| {
+        thread::Builder::new()
+            .name("notify-rust".into())
+            .spawn(|| {
+                ON_RUNTIME.with(|on_runtime| on_runtime.set(true));
+                zbus::block_on(EXECUTOR.run(std::future::pending::<()>()));
+            })
+            .expect("failed to spawn the notify-rust runtime thread");
+    });
+}
+
+/// Whether the current thread is the runtime thread, which must not wait for its own futures.
+pub(crate) fn is_current() -> bool {
+    ON_RUNTIME.with(Cell::get)
+}
+
+/// Runs `future` on the runtime thread in the background.
+pub(crate) fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    start();
+    EXECUTOR.spawn(future).detach();
+}
+
+/// Runs `future` on the runtime thread and blocks the current thread until it is done.
+///
+/// Panics inside of `future` are passed on to the caller.
+pub(crate) fn block_on<F>(future: F) -> F::Output
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    if is_current() {
+        // e.g. `SPEC_VERSION` being initialized while a notification is sent,
+        // waiting for the runtime thread from the runtime thread would never return
+        return zbus::block_on(future);
+    }
+
+    start();
+
+    let (sender, receiver) = mpsc::channel();
+    EXECUTOR
+        .spawn(async move {
+            let _ = sender.send(AssertUnwindSafe(future).catch_unwind().await);
+        })
+        .detach();
+
+    match receiver.recv() {
+        Ok(Ok(output)) => output,
+        Ok(Err(payload)) => panic::resume_unwind(payload),
+        Err(_) => unreachable!("the notify-rust runtime thread dropped a task"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_on_runtime_thread() {
+        let name = block_on(async { thread::current().name().map(ToOwned::to_owned) });
+        assert_eq!(name.as_deref(), Some("notify-rust"));
+    }
+
+    #[test]
+    fn concurrent_callers() {
+        let callers = (0..8u32)
+            .map(|i| thread::spawn(move || block_on(async move { i * 2 })))
+            .collect::<Vec<_>>();
+
+        let results = callers
+            .into_iter()
+            .map(|caller| caller.join().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(results, (0..8).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn nested_block_on_does_not_deadlock() {
+        assert_eq!(block_on(async { block_on(async { 42 }) }), 42);
+    }
+
+    #[test]
+    fn panics_reach_the_caller() {
+        let caught = panic::catch_unwind(|| block_on(async { panic!("oh no") }));
+        assert!(caught.is_err());
+        assert_eq!(block_on(async { "still running" }), "still running");
+    }
+}
@@ -0,0 +1,90 @@
+//! Notifications that are sent after a delay, see [`Notification::show_after()`].
+
+use std::{
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    thread,
+    time::Duration,
+};
+
+use super::LightHandle;
+use crate::{error::*, notification::Notification};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Pending,
+    Cancelled,
+    /// The timer ran out, from here on it will be sent no matter what.
+    Sending,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A notification that is going to be sent once its delay has passed.
+///
+/// Returned by [`Notification::show_after()`].
+/// Dropping this does not cancel the notification, use [`cancel()`](Self::cancel) for that.
+#[derive(Debug)]
+pub struct ScheduledNotification {
+    shared: Arc<Shared>,
+    thread: thread::JoinHandle<Option<Result<LightHandle>>>,
+}
+
+impl ScheduledNotification {
+    pub(crate) fn start(notification: Notification, delay: Duration) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::Pending),
+            changed: Condvar::new(),
+        });
+        let waiting = Arc::clone(&shared);
+        let thread = thread::spawn(move || {
+            let (mut state, _) = waiting
+                .changed
+                .wait_timeout_while(waiting.lock(), delay, |state| *state == State::Pending)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if *state == State::Cancelled {
+                return None;
+            }
+            // decided while holding the lock, so `cancel()` can't slip in between
+            *state = State::Sending;
+            drop(state);
+            Some(notification.show_light())
+        });
+        ScheduledNotification { shared, thread }
+    }
+
+    /// Cancels the notification if it hasn't been sent yet.
+    ///
+    /// Returns `true` if it is guaranteed to never be sent,
+    /// `false` if the delay already passed and it is being sent or was sent already.
+    pub fn cancel(&self) -> bool {
+        let mut state = self.shared.lock();
+        match *state {
+            State::Pending => {
+                *state = State::Cancelled;
+                self.shared.changed.notify_all();
+                true
+            }
+            State::Cancelled => true,
+            State::Sending => false,
+        }
+    }
+
+    /// Blocks until the notification was sent, `None` if it got cancelled instead.
+    pub fn wait(self) -> Option<Result<LightHandle>> {
+        self.thread
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+}
@@ -0,0 +1,91 @@
+//! Notifications that are taken back at the end of a scope, see [`ScopedNotification`].
+
+use std::ops::{Deref, DerefMut};
+
+use crate::xdg::NotificationHandle;
+
+/// A handle that closes its notification when it is dropped, e.g. a "Processing…" notification for the length of a task.
+///
+/// Returned by [`Notification::show_scoped()`](crate::Notification::show_scoped),
+/// it replaces closing the notification by hand after the work is done,
+/// which is easily skipped by an early return, a `?` or a panic.
+///
+/// ```no_run
+/// # use notify_rust::*;
+/// # fn import(path: &str) -> error::Result<()> { Ok(()) }
+/// fn import_all(paths: &[&str]) -> error::Result<()> {
+///     let mut progress = Notification::new().summary("Importing").show_scoped()?;
+///     for path in paths {
+///         progress.body(path);
+///         progress.update();
+///         import(path)?; // closed on failure as well
+///     }
+///     Ok(())
+/// } // closed here
+/// ```
+///
+/// Dropping closes the notification right away and blocks until the server replied,
+/// so it is done before the program exits.
+/// Inside of an async task that blocks the executor for one round trip,
+/// call [`into_handle()`](Self::into_handle) and close it with `close_notification_async()` to avoid that.
+/// Failures are logged instead of panicking, dropping happens during unwinding as well.
+/// Call [`close()`](Self::close) to close it and wait,
+/// or [`into_handle()`](Self::into_handle) to keep the notification after all.
+///
+/// (xdg only)
+#[derive(Debug)]
+pub struct ScopedNotification {
+    /// Only `None` once closed or released.
+    handle: Option<NotificationHandle>,
+}
+
+impl ScopedNotification {
+    pub(crate) fn new(handle: NotificationHandle) -> Self {
+        ScopedNotification {
+            handle: Some(handle),
+        }
+    }
+
+    /// Closes the notification right away and waits for the server, like [`NotificationHandle::close()`].
+    pub fn close(mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.close();
+        }
+    }
+
+    /// Keeps the notification open past the scope, returning its plain handle.
+    pub fn into_handle(mut self) -> NotificationHandle {
+        self.handle
+            .take()
+            .expect("the handle is only taken when consuming the guard")
+    }
+}
+
+/// Closes the notification, logging failures.
+impl Drop for ScopedNotification {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.close_logged();
+        }
+    }
+}
+
+/// Required for `DerefMut`
+impl Deref for ScopedNotification {
+    type Target = NotificationHandle;
+
+    fn deref(&self) -> &NotificationHandle {
+        self.handle
+            .as_ref()
+            .expect("the handle is only taken when consuming the guard")
+    }
+}
+
+/// Allow you to easily modify notification properties
+impl DerefMut for ScopedNotification {
+    fn deref_mut(&mut self) -> &mut NotificationHandle {
+        self.handle
+            .as_mut()
+            .expect("the handle is only taken when consuming the guard")
+    }
+}
@@ -0,0 +1,36 @@
+//! Which server answered on which bus name.
+//!
+//! Ids are only unique per server, and signals only carry the unique name of the server that sent them,
+//! so the unique name the `Notify` reply came from is remembered for each bus name notifications are sent to.
+
+use std::sync::{Mutex, MutexGuard};
+
+use crate::xdg::NotificationBus;
+
+static SERVERS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+fn servers() -> MutexGuard<'static, Vec<(String, String)>> {
+    SERVERS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Remembers that the server with the unique name `server` replied to a notification sent to `bus`.
+pub(crate) fn replied(bus: &NotificationBus, server: &str) {
+    let bus = bus.clone().into_name().to_string();
+    let mut servers = servers();
+    match servers.iter_mut().find(|(known, _)| *known == bus) {
+        Some((_, known)) if known == server => {}
+        Some((_, known)) => *known = server.to_owned(),
+        None => servers.push((bus, server.to_owned())),
+    }
+}
+
+/// The unique name of the server that last replied on `bus`, `None` if nothing was sent there yet.
+pub(crate) fn server_of(bus: &NotificationBus) -> Option<String> {
+    let bus = bus.clone().into_name().to_string();
+    servers()
+        .iter()
+        .find(|(known, _)| *known == bus)
+        .map(|(_, server)| server.clone())
+}
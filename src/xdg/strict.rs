@@ -0,0 +1,243 @@
+//! Refusing to send what the server would silently drop, see [`set_strict_capabilities`].
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{error::*, notification::Notification, xdg};
+
+static STRICT: AtomicBool = AtomicBool::new(false);
+
+/// Capabilities by bus name and when they were fetched, also used by [`Notification::with_action_fallback()`].
+static CAPABILITIES: Mutex<Vec<Cached>> = Mutex::new(Vec::new());
+
+/// How long fetched capabilities are trusted, a restarted or replaced server may differ.
+const MAX_AGE: Duration = Duration::from_secs(60);
+
+type Cached = (String, Instant, Arc<Vec<String>>);
+
+/// Makes `show()` fail instead of sending what the server doesn't support.
+///
+/// In strict mode the capabilities of the server are fetched on the first `show()` and kept for a minute,
+/// sending fails with [`ErrorKind::UnsupportedFeature`] if the notification
+/// has actions, body markup or hints the server doesn't advertise (see [`Notification::unsupported_hints()`]).
+/// Meant for development, to find out why parts of a notification don't show up.
+/// By default notifications are sent as they are and the server makes the best of them.
+///
+/// ```no_run
+/// notify_rust::set_strict_capabilities(cfg!(debug_assertions));
+/// ```
+///
+/// (xdg only)
+pub fn set_strict_capabilities(strict: bool) {
+    STRICT.store(strict, Ordering::Relaxed);
+}
+
+/// Whether [`set_strict_capabilities()`] is on.
+pub(crate) fn enabled() -> bool {
+    STRICT.load(Ordering::Relaxed)
+}
+
+/// Fails if strict mode is on and the server doesn't support everything `notification` uses.
+///
+/// Passes if the `capabilities` are unknown, as in a dry run.
+pub(crate) fn check(notification: &Notification, capabilities: Option<&[String]>) -> Result<()> {
+    let Some(capabilities) = capabilities.filter(|_| enabled()) else {
+        return Ok(());
+    };
+    match missing_capability(notification, capabilities) {
+        Some(feature) => Err(ErrorKind::UnsupportedFeature { feature }.into()),
+        None => Ok(()),
+    }
+}
+
+/// The capabilities of the server at `bus`, cached for [`MAX_AGE`].
+///
+/// `call_timeout` limits the call that fetches them, see [`Notification::call_timeout()`].
+pub(crate) fn capabilities(
+    bus: &xdg::NotificationBus,
+    call_timeout: Option<Duration>,
+) -> Result<Arc<Vec<String>>> {
+    if let Some(capabilities) = cached(bus) {
+        return Ok(capabilities);
+    }
+
+    // don't hold the lock during the call, failures are not cached
+    let capabilities = Arc::new(xdg::get_capabilities_at_bus(bus.clone(), call_timeout)?);
+    Ok(store(bus, capabilities))
+}
+
+/// Async version of [`capabilities()`], for sending from an executor.
+#[cfg(all(feature = "async", feature = "zbus"))]
+pub(crate) async fn capabilities_async(
+    bus: &xdg::NotificationBus,
+    call_timeout: Option<Duration>,
+) -> Result<Arc<Vec<String>>> {
+    if let Some(capabilities) = cached(bus) {
+        return Ok(capabilities);
+    }
+    let capabilities =
+        Arc::new(xdg::zbus_rs::get_capabilities_at_bus(bus.clone(), call_timeout).await?);
+    Ok(store(bus, capabilities))
+}
+
+fn cached(bus: &xdg::NotificationBus) -> Option<Arc<Vec<String>>> {
+    let name = bus.clone().into_name().to_string();
+    let mut cache = lock();
+    cache.retain(|(_, fetched, _)| fetched.elapsed() < MAX_AGE);
+    cache
+        .iter()
+        .find(|(cached, _, _)| *cached == name)
+        .map(|(_, _, capabilities)| Arc::clone(capabilities))
+}
+
+fn store(bus: &xdg::NotificationBus, capabilities: Arc<Vec<String>>) -> Arc<Vec<String>> {
+    let name = bus.clone().into_name().to_string();
+    lock().push((name, Instant::now(), Arc::clone(&capabilities)));
+    capabilities
+}
+
+/// The outcome of fetching capabilities for a send: only strict mode fails if they can't be fetched,
+/// everything else goes on without them.
+pub(crate) fn fetched(capabilities: Result<Arc<Vec<String>>>) -> Result<Option<Arc<Vec<String>>>> {
+    match capabilities {
+        Ok(capabilities) => Ok(Some(capabilities)),
+        Err(error) if enabled() => Err(error),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Drops all cached capabilities, the next send fetches them anew.
+#[cfg(feature = "zbus")]
+pub(crate) fn forget_capabilities() {
+    lock().clear();
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Vec<Cached>> {
+    CAPABILITIES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The first capability `notification` needs that is not among `capabilities`.
+fn missing_capability<S: AsRef<str>>(
+    notification: &Notification,
+    capabilities: &[S],
+) -> Option<&'static str> {
+    let supports = |capability: &str| capabilities.iter().any(|c| c.as_ref() == capability);
+    if !notification.actions_to_send().is_empty() && !supports("actions") {
+        return Some("actions");
+    }
+    if uses_markup(&notification.body) && !supports("body-markup") {
+        return Some("body-markup");
+    }
+    notification
+        .unsupported_hints(capabilities)
+        .first()
+        .and_then(|hint| hint.capabilities().first().copied())
+}
+
+/// Whether `body` contains any of the tags allowed by the specification.
+pub(crate) fn uses_markup(body: &str) -> bool {
+    body.split('<').skip(1).any(|tag| markup_tag(tag).is_some())
+}
+
+/// The name of the tag allowed by the specification that `tag` starts with, right after its `<`,
+/// and the length of the tag after the `<`.
+fn markup_tag(tag: &str) -> Option<(&str, usize)> {
+    let (inner, _) = tag.split_once('>')?;
+    let name = inner.strip_prefix('/').unwrap_or(inner);
+    let name = name.split_whitespace().next().unwrap_or_default();
+    matches!(name, "b" | "i" | "u" | "a" | "img").then(|| (name, inner.len() + 1))
+}
+
+/// Removes the tags `strip` returns `true` for, keeping the text between them.
+pub(crate) fn strip_tags(body: &str, strip: impl Fn(&str) -> bool) -> String {
+    let mut stripped = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(start) = rest.find('<') {
+        stripped.push_str(&rest[..start]);
+        let tag = &rest[start + 1..];
+        if let Some((_, len)) = markup_tag(tag).filter(|(name, _)| strip(name)) {
+            rest = &tag[len..];
+        } else {
+            stripped.push('<');
+            rest = tag;
+        }
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+/// Turns the body of a notification with markup into plain text.
+pub(crate) fn plain_text(body: &str) -> String {
+    strip_tags(body, |_| true)
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Hint;
+
+    #[test]
+    fn detects_markup() {
+        for markup in [
+            "<b>bold</b>",
+            "an <i>emphasized</i> word",
+            "<a href=\"https://example.com\">link</a>",
+            "<img src=\"icon.png\" alt=\"icon\"/>",
+        ] {
+            assert!(uses_markup(markup), "{}", markup);
+        }
+        for plain in ["1 < 2", "a <-> b", "<html>", "<b", "i>"] {
+            assert!(!uses_markup(plain), "{}", plain);
+        }
+    }
+
+    #[test]
+    fn strips_markup() {
+        let body = "<b>Alice</b> sent <a href=\"https://example.com\">a link</a> <img src=\"cat.png\"/> &amp; 1 < 2";
+        assert_eq!(
+            strip_tags(body, |tag| tag == "a"),
+            "<b>Alice</b> sent a link <img src=\"cat.png\"/> &amp; 1 < 2"
+        );
+        assert_eq!(plain_text(body), "Alice sent a link  & 1 < 2");
+        assert_eq!(plain_text("&amp;lt; <i>x</i>"), "&lt; x");
+    }
+
+    #[test]
+    fn finds_missing_capability() {
+        let everything = ["actions", "body", "body-markup", "icon-static", "sound"];
+
+        let mut notification = Notification::new();
+        notification.body("<b>bold</b>");
+        assert_eq!(missing_capability(&notification, &everything), None);
+        assert_eq!(
+            missing_capability(&notification, &["body"]),
+            Some("body-markup")
+        );
+
+        notification.action("default", "Open");
+        assert_eq!(
+            missing_capability(&notification, &["body"]),
+            Some("actions")
+        );
+
+        let mut notification = Notification::new();
+        notification.hint(Hint::ImagePath("/tmp/image.png".into()));
+        assert_eq!(
+            missing_capability(&notification, &["body"]),
+            Some("icon-static")
+        );
+        assert_eq!(missing_capability(&notification, &["icon-multi"]), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! Remembers which notifications are on screen under a stack tag, see [`close_by_tag()`].
+
+use std::sync::{Mutex, MutexGuard};
+
+use crate::{
+    error::*,
+    hints::Hint,
+    notification::Notification,
+    xdg::{dry_run, quiet, servers, NotificationBus},
+};
+
+/// Sent by [`Notification::stack_tag()`].
+pub(crate) const STACK_TAG_HINT: &str = "x-dunst-stack-tag";
+
+/// How many tagged notifications are remembered at most, the oldest one is forgotten first.
+///
+/// dbus-rs only sees notifications closing while waiting on a handle or in [`select_action()`](crate::select_action),
+/// otherwise the list is kept short by this.
+const CAPACITY: usize = 256;
+
+static LIVE: Mutex<Vec<Tagged>> = Mutex::new(Vec::new());
+
+#[derive(Debug)]
+struct Tagged {
+    tag: String,
+    id: u32,
+    bus: NotificationBus,
+    /// The unique name of the server that assigned `id`, signals only carry that one.
+    server: Option<String>,
+}
+
+fn live() -> MutexGuard<'static, Vec<Tagged>> {
+    LIVE.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The stack tag of `notification`, if it has one.
+fn tag_of(notification: &Notification) -> Option<&str> {
+    notification.get_hints().find_map(|hint| match hint {
+        Hint::Custom(key, tag) if key == STACK_TAG_HINT => Some(tag.as_str()),
+        _ => None,
+    })
+}
+
+/// Remembers that `notification` is shown as `id`, or forgets `id` if it lost its tag.
+pub(crate) fn record(notification: &Notification, id: u32) {
+    let bus = notification.bus.clone().into_name();
+    let mut live = live();
+    live.retain(|tagged| tagged.id != id || tagged.bus.clone().into_name() != bus);
+    if let Some(tag) = tag_of(notification) {
+        if live.len() == CAPACITY {
+            live.remove(0);
+        }
+        live.push(Tagged {
+            tag: tag.to_owned(),
+            id,
+            bus: notification.bus.clone(),
+            server: servers::server_of(&notification.bus),
+        });
+    }
+}
+
+/// Forgets `id` once the server with the unique name `server` closed it.
+pub(crate) fn forget(server: &str, id: u32) {
+    live().retain(|tagged| tagged.id != id || tagged.server.as_deref() != Some(server));
+}
+
+/// Forgets and returns every notification tagged `tag`.
+fn take(tag: &str) -> Vec<Tagged> {
+    let mut live = live();
+    let (tagged, others) = live.drain(..).partition(|tagged| tagged.tag == tag);
+    *live = others;
+    tagged
+}
+
+/// Closes every notification that was shown with the stack `tag` and is still open.
+///
+/// The specification has no way of closing notifications by tag,
+/// so this closes each of the notifications shown with [`Notification::stack_tag()`] by its id.
+/// Returns how many were closed, failing only if none of them could be closed.
+///
+/// ```no_run
+/// # use notify_rust::*;
+/// # fn _doc() -> Result<(), error::Error> {
+/// Notification::new().summary("Alice").body("lunch?").stack_tag("chat-42").show()?;
+/// Notification::new().summary("Alice").body("at 12?").stack_tag("chat-42").show()?;
+/// // ... the user opened the conversation
+/// let closed = notify_rust::close_by_tag("chat-42")?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Only notifications shown by this process are known.
+/// With zbus, notifications that expired or were dismissed are forgotten right away,
+/// with dbus-rs only while waiting on a handle or in [`select_action()`](crate::select_action),
+/// so this may try to close some that are gone already, which servers ignore.
+///
+/// (xdg only)
+pub fn close_by_tag(tag: &str) -> Result<usize> {
+    let (mut closed, mut first_error) = (0, None::<Error>);
+    for Tagged { id, bus, .. } in take(tag) {
+        if dry_run::enabled() {
+            closed += 1;
+            continue;
+        }
+        match super::close_notification_detached(id, bus, None) {
+            Ok(()) => closed += 1,
+            Err(error) => {
+                if !quiet::is_quiet() {
+                    log::warn!(
+                        "failed to close notification {} tagged {:?}: {}",
+                        id,
+                        tag,
+                        error
+                    );
+                }
+                first_error.get_or_insert(error);
+            }
+        }
+    }
+    match first_error {
+        Some(error) if closed == 0 => Err(error),
+        _ => Ok(closed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tagged(tag: &str) -> Vec<u32> {
+        live()
+            .iter()
+            .filter(|tagged| tagged.tag == tag)
+            .map(|tagged| tagged.id)
+            .collect()
+    }
+
+    #[test]
+    fn tracks_tagged_ids() {
+        let bus = NotificationBus::custom("tags").unwrap();
+        let at_bus = |notification: &mut Notification| {
+            notification.bus = bus.clone();
+            notification.clone()
+        };
+        servers::replied(&bus, ":1.7");
+
+        let chat = at_bus(Notification::new().stack_tag("tags-chat"));
+        record(&chat, 7_001);
+        record(&chat, 7_002);
+        record(&at_bus(Notification::new().stack_tag("tags-volume")), 7_003);
+        record(&at_bus(&mut Notification::new()), 7_004);
+        assert_eq!(tagged("tags-chat"), [7_001, 7_002]);
+        assert_eq!(tagged("tags-volume"), [7_003]);
+
+        // another server closing the same id changes nothing
+        forget(":1.8", 7_003);
+        assert_eq!(tagged("tags-volume"), [7_003]);
+
+        // replaced without a tag, closed by the server
+        record(&at_bus(&mut Notification::new()), 7_002);
+        forget(":1.7", 7_003);
+        assert_eq!(tagged("tags-chat"), [7_001]);
+        assert!(tagged("tags-volume").is_empty());
+
+        let taken = take("tags-chat");
+        assert_eq!(
+            taken.iter().map(|tagged| tagged.id).collect::<Vec<_>>(),
+            [7_001]
+        );
+        assert!(tagged("tags-chat").is_empty());
+        assert!(take("tags-chat").is_empty());
+    }
+}
@@ -0,0 +1,80 @@
+//! Renders the current time for [`Notification::with_timestamp`](crate::Notification::with_timestamp).
+
+/// The current local time in `format`, using chrono's `strftime` syntax.
+#[cfg(feature = "chrono")]
+pub(crate) fn now(format: &str) -> String {
+    chrono::Local::now().format(format).to_string()
+}
+
+/// The current time in UTC in `format`.
+///
+/// Without chrono there is no way to tell the local timezone,
+/// and only `%Y`, `%m`, `%d`, `%H`, `%M`, `%S` and `%%` are understood, anything else is kept as it is.
+#[cfg(not(feature = "chrono"))]
+pub(crate) fn now(format: &str) -> String {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    format_utc(seconds, format)
+}
+
+/// Formats `seconds` since the unix epoch.
+#[cfg(any(test, not(feature = "chrono")))]
+fn format_utc(seconds: u64, format: &str) -> String {
+    let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+    let time = seconds % 86400;
+    let (hour, minute, second) = (time / 3600, time / 60 % 60, time % 60);
+
+    let mut formatted = String::with_capacity(format.len() + 8);
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            formatted.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => formatted.push_str(&year.to_string()),
+            Some('m') => formatted.push_str(&format!("{:02}", month)),
+            Some('d') => formatted.push_str(&format!("{:02}", day)),
+            Some('H') => formatted.push_str(&format!("{:02}", hour)),
+            Some('M') => formatted.push_str(&format!("{:02}", minute)),
+            Some('S') => formatted.push_str(&format!("{:02}", second)),
+            Some('%') | None => formatted.push('%'),
+            Some(other) => {
+                formatted.push('%');
+                formatted.push(other);
+            }
+        }
+    }
+    formatted
+}
+
+/// Year, month and day of the `days` since 1970-01-01, see <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+#[cfg(any(test, not(feature = "chrono")))]
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_utc() {
+        assert_eq!(format_utc(0, "%Y-%m-%d %H:%M:%S"), "1970-01-01 00:00:00");
+        assert_eq!(format_utc(951_782_400, "%d.%m.%Y"), "29.02.2000");
+        assert_eq!(
+            format_utc(1_700_000_000, "[%H:%M:%S] 100%% %a%"),
+            "[22:13:20] 100% %a%"
+        );
+    }
+}
@@ -1,7 +1,22 @@
+use std::{
+    future::Future,
+    pin::pin,
+    time::{Duration, Instant},
+};
+
+use async_io::Timer;
+use zbus::export::futures_util::future::{join_all, select, select_all, Either};
+#[cfg(feature = "async")]
+use zbus::export::futures_util::{future::ready, Stream, StreamExt};
+
 use crate::{error::*, notification::Notification, xdg};
-use zbus::{export::futures_util::TryStreamExt, MatchRule};
 
-use super::{bus::NotificationBus, ActionResponse, ActionResponseHandler, CloseReason};
+use super::{
+    bus::NotificationBus, observer, panic_hook, runtime, servers, ActionResponse,
+    ActionResponseHandler, CloseReason, NotificationSignal, OwnedActionResponse,
+};
+
+mod dispatcher;
 
 pub mod bus {
 
@@ -56,11 +71,14 @@ pub mod bus {
 /// A handle to a shown notification.
 ///
 /// This keeps a connection alive to ensure actions work on certain desktops.
+/// Notifications shown by this crate all share one connection, see [`dispatcher`].
 #[derive(Debug)]
 pub struct ZbusNotificationHandle {
     pub(crate) id: u32,
     pub(crate) connection: zbus::Connection,
     pub(crate) notification: Notification,
+    /// How long the last `Notify` call took.
+    pub(crate) send_latency: Duration,
 }
 
 impl ZbusNotificationHandle {
@@ -73,52 +91,112 @@ impl ZbusNotificationHandle {
             id,
             connection,
             notification,
+            send_latency: Duration::ZERO,
         }
     }
 
     pub async fn wait_for_action(self, invocation_closure: impl ActionResponseHandler) {
-        wait_for_action_signal(&self.connection, self.id, invocation_closure).await;
+        self.wait_for_action_ref(invocation_closure).await;
+    }
+
+    pub async fn wait_for_action_ref(&self, invocation_closure: impl ActionResponseHandler) {
+        wait_for_action_signal(
+            &self.connection,
+            &self.notification.bus,
+            self.id,
+            invocation_closure,
+        )
+        .await;
     }
 
     pub async fn close_fallible(self) -> Result<()> {
-        self.connection
-            .call_method(
-                Some(self.notification.bus.clone().into_name()),
-                xdg::NOTIFICATION_OBJECTPATH,
-                Some(xdg::NOTIFICATION_INTERFACE),
-                "CloseNotification",
-                &(self.id),
-            )
-            .await?;
-        Ok(())
+        close_notification_via_connection(
+            self.id,
+            &self.connection,
+            self.notification.bus.clone(),
+            self.notification.call_timeout,
+        )
+        .await
     }
 
     pub async fn close(self) {
-        self.close_fallible().await.unwrap();
+        if let Err(error) = self.close_fallible().await {
+            panic_hook::report(error);
+        }
+    }
+
+    /// Blocking version of [`wait_for_action`](Self::wait_for_action).
+    ///
+    /// The signal is awaited on the runtime thread, `invocation_closure` is called on the current thread.
+    pub fn wait_for_action_blocking(self, invocation_closure: impl ActionResponseHandler) {
+        self.wait_for_action_ref_blocking(invocation_closure);
+    }
+
+    /// Blocking version of [`wait_for_action_ref`](Self::wait_for_action_ref).
+    pub fn wait_for_action_ref_blocking(&self, invocation_closure: impl ActionResponseHandler) {
+        let (id, connection, bus) = (
+            self.id,
+            self.connection.clone(),
+            self.notification.bus.clone(),
+        );
+        let signal =
+            runtime::block_on(async move { next_action_signal(&connection, &bus, id).await });
+        if let Some(signal) = signal {
+            invocation_closure.call(&signal.as_response());
+        }
+    }
+
+    /// Waits for `NotificationClosed`, for at most `timeout`.
+    pub fn wait_for_close_timeout(self, timeout: Duration) -> CloseReason {
+        let Self {
+            id,
+            connection,
+            notification,
+            ..
+        } = self;
+        runtime::block_on(async move {
+            let closed = async {
+                // subscribed once for the whole wait, so no signal slips through in between
+                let dispatcher = dispatcher::for_connection(&connection).await.ok()?;
+                let signals = dispatcher.subscribe(servers::server_of(&notification.bus), id)?;
+                loop {
+                    if let OwnedActionResponse::Closed(reason) = signals.recv().await.ok()? {
+                        return Some(reason);
+                    }
+                }
+            };
+            match select(pin!(closed), Timer::after(timeout)).await {
+                Either::Left((reason, _)) => reason,
+                Either::Right(_) => None,
+            }
+        })
+        .unwrap_or(CloseReason::Expired)
     }
 
     pub fn on_close<F>(self, closure: F)
     where
         F: FnOnce(CloseReason),
     {
-        zbus::block_on(self.wait_for_action(|action: &ActionResponse| {
+        self.wait_for_action_blocking(|action: &ActionResponse| {
             if let ActionResponse::Closed(reason) = action {
                 closure(*reason);
             }
-        }));
+        });
     }
 
     pub fn update_fallible(&mut self) -> Result<()> {
-        self.id = zbus::block_on(send_notification_via_connection(
-            &self.notification,
-            self.id,
-            &self.connection,
-        ))?;
+        let notification = self.notification.prepare()?.into_owned();
+        let (id, connection) = (self.id, self.connection.clone());
+        (self.id, self.send_latency) = runtime::block_on(async move {
+            send_notification_via_connection(&notification, id, &connection).await
+        })?;
         Ok(())
     }
 
     pub fn update(&mut self) {
-        self.update_fallible().unwrap();
+        if let Err(error) = self.update_fallible() {
+            panic_hook::report(error);
+        }
     }
 }
 
@@ -126,37 +204,87 @@ async fn send_notification_via_connection(
     notification: &Notification,
     id: u32,
     connection: &zbus::Connection,
-) -> Result<u32> {
-    send_notification_via_connection_at_bus(notification, id, connection, Default::default()).await
+) -> Result<(u32, Duration)> {
+    send_notification_via_connection_at_bus(notification, id, connection, notification.bus.clone())
+        .await
 }
 
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(
+            replaces_id = id,
+            bus = %bus.clone().into_name(),
+            summary_len = notification.summary.len(),
+        ),
+        ret,
+        err,
+    )
+)]
 async fn send_notification_via_connection_at_bus(
     notification: &Notification,
     id: u32,
     connection: &zbus::Connection,
     bus: NotificationBus,
-) -> Result<u32> {
-    let reply: u32 = connection
-        .call_method(
-            Some(bus.into_name()),
-            xdg::NOTIFICATION_OBJECTPATH,
-            Some(xdg::NOTIFICATION_INTERFACE),
-            "Notify",
-            &(
-                &notification.appname,
-                id,
-                &notification.icon,
-                &notification.summary,
-                &notification.body,
-                &notification.actions,
-                crate::hints::hints_to_map(notification),
-                i32::from(notification.timeout),
-            ),
-        )
-        .await?
-        .body()
-        .deserialize()?;
-    Ok(reply)
+) -> Result<(u32, Duration)> {
+    observer::observe(notification);
+    let args = (
+        &notification.appname,
+        id,
+        &notification.icon,
+        &notification.summary,
+        notification.body_at_send(),
+        notification.actions_to_send(),
+        crate::hints::hints_to_map(notification),
+        notification.timeout_ms(),
+    );
+    let call = connection.call_method(
+        Some(bus.clone().into_name()),
+        xdg::NOTIFICATION_OBJECTPATH,
+        Some(xdg::NOTIFICATION_INTERFACE),
+        "Notify",
+        &args,
+    );
+    let started = Instant::now();
+    let reply = with_call_timeout(notification.call_timeout, call).await?;
+    let latency = started.elapsed();
+    if let Some(server) = reply.header().sender() {
+        servers::replied(&bus, server);
+    }
+    Ok((reply.body().deserialize()?, latency))
+}
+
+async fn close_notification_via_connection(
+    id: u32,
+    connection: &zbus::Connection,
+    bus: NotificationBus,
+    call_timeout: Option<Duration>,
+) -> Result<()> {
+    let call = connection.call_method(
+        Some(bus.into_name()),
+        xdg::NOTIFICATION_OBJECTPATH,
+        Some(xdg::NOTIFICATION_INTERFACE),
+        "CloseNotification",
+        &(id),
+    );
+    with_call_timeout(call_timeout, call).await?;
+    Ok(())
+}
+
+/// Awaits a method `call`, but only for as long as `call_timeout` if there is one.
+async fn with_call_timeout<T>(
+    call_timeout: Option<Duration>,
+    call: impl Future<Output = zbus::Result<T>>,
+) -> Result<T> {
+    let Some(call_timeout) = call_timeout else {
+        return Ok(call.await?);
+    };
+    match select(pin!(call), Timer::after(call_timeout)).await {
+        Either::Left((reply, _)) => Ok(reply?),
+        Either::Right(_) => Err(ErrorKind::Timeout(call_timeout).into()),
+    }
 }
 
 pub async fn connect_and_send_notification(
@@ -166,32 +294,109 @@ pub async fn connect_and_send_notification(
     connect_and_send_notification_at_bus(notification, bus).await
 }
 
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "connect_and_send_notification",
+        level = "debug",
+        skip_all,
+        fields(
+            bus = %bus.clone().into_name(),
+            summary_len = notification.summary.len(),
+            id = tracing::field::Empty,
+        ),
+        err,
+    )
+)]
 pub(crate) async fn connect_and_send_notification_at_bus(
     notification: &Notification,
     bus: NotificationBus,
 ) -> Result<ZbusNotificationHandle> {
-    let connection = zbus::Connection::session().await?;
-    let inner_id = notification.id.unwrap_or(0);
-    let id =
+    let connection = dispatcher::session().await?;
+    let inner_id = notification.id.raw();
+    let (id, send_latency) =
         send_notification_via_connection_at_bus(notification, inner_id, &connection, bus).await?;
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("id", id);
 
-    Ok(ZbusNotificationHandle::new(
-        id,
-        connection,
-        notification.clone(),
-    ))
+    Ok(ZbusNotificationHandle {
+        send_latency,
+        ..ZbusNotificationHandle::new(id, connection, notification.for_handle())
+    })
+}
+
+/// Drops the shared session connection, the next call connects anew.
+pub fn reset_connection() {
+    dispatcher::reset();
 }
 
-pub async fn get_capabilities_at_bus(bus: NotificationBus) -> Result<Vec<String>> {
+/// Sends `notification` on a connection of its own, which is dropped right after.
+pub async fn send_notification_detached(notification: &Notification) -> Result<u32> {
     let connection = zbus::Connection::session().await?;
-    let info: Vec<String> = connection
-        .call_method(
-            Some(bus.into_name()),
-            xdg::NOTIFICATION_OBJECTPATH,
-            Some(xdg::NOTIFICATION_INTERFACE),
-            "GetCapabilities",
-            &(),
-        )
+    let id = notification.id.raw();
+    let (id, _) = send_notification_via_connection(notification, id, &connection).await?;
+    Ok(id)
+}
+
+/// Sends all `notifications` concurrently on a connection of their own, which is dropped right after.
+pub async fn send_many_detached(notifications: &[Notification]) -> Result<Vec<u32>> {
+    let connection = zbus::Connection::session().await?;
+    let sends = notifications.iter().map(|notification| {
+        send_notification_via_connection(notification, notification.id.raw(), &connection)
+    });
+    join_all(sends)
+        .await
+        .into_iter()
+        .map(|sent| sent.map(|(id, _)| id))
+        .collect()
+}
+
+/// Closes notification `id` using a connection of its own, which is dropped right after.
+pub async fn close_notification_detached(
+    id: u32,
+    bus: NotificationBus,
+    call_timeout: Option<Duration>,
+) -> Result<()> {
+    let connection = zbus::Connection::session().await?;
+    close_notification_via_connection(id, &connection, bus, call_timeout).await
+}
+
+/// Watches who owns the name of the notification server.
+#[cfg(feature = "async")]
+pub async fn watch_server_at_bus(
+    bus: NotificationBus,
+) -> Result<impl Stream<Item = xdg::ServerEvent>> {
+    let connection = dispatcher::session().await?;
+    let proxy = zbus::fdo::DBusProxy::new(&connection).await?;
+    let name = bus.into_name().to_string();
+    let changes = proxy
+        .receive_name_owner_changed_with_args(&[(0, name.as_str())])
+        .await?;
+    Ok(changes.filter_map(|changed| {
+        let event = changed.args().ok().map(|args| {
+            if args.new_owner().is_some() {
+                xdg::ServerEvent::Started
+            } else {
+                xdg::ServerEvent::Stopped
+            }
+        });
+        ready(event)
+    }))
+}
+
+pub async fn get_capabilities_at_bus(
+    bus: NotificationBus,
+    call_timeout: Option<Duration>,
+) -> Result<Vec<String>> {
+    let connection = dispatcher::session().await?;
+    let call = connection.call_method(
+        Some(bus.into_name()),
+        xdg::NOTIFICATION_OBJECTPATH,
+        Some(xdg::NOTIFICATION_INTERFACE),
+        "GetCapabilities",
+        &(),
+    );
+    let info: Vec<String> = with_call_timeout(call_timeout, call)
         .await?
         .body()
         .deserialize()?;
@@ -199,11 +404,11 @@ pub async fn get_capabilities_at_bus(bus: NotificationBus) -> Result<Vec<String>
 }
 
 pub async fn get_capabilities() -> Result<Vec<String>> {
-    get_capabilities_at_bus(Default::default()).await
+    get_capabilities_at_bus(Default::default(), None).await
 }
 
 pub async fn get_server_information_at_bus(bus: NotificationBus) -> Result<xdg::ServerInformation> {
-    let connection = zbus::Connection::session().await?;
+    let connection = dispatcher::session().await?;
     let info: xdg::ServerInformation = connection
         .call_method(
             Some(bus.into_name()),
@@ -219,6 +424,22 @@ pub async fn get_server_information_at_bus(bus: NotificationBus) -> Result<xdg::
     Ok(info)
 }
 
+pub async fn introspect() -> Result<String> {
+    let connection = dispatcher::session().await?;
+    let xml: String = connection
+        .call_method(
+            Some(NotificationBus::default().into_name()),
+            xdg::NOTIFICATION_OBJECTPATH,
+            Some(xdg::INTROSPECTABLE_INTERFACE),
+            "Introspect",
+            &(),
+        )
+        .await?
+        .body()
+        .deserialize()?;
+    Ok(xml)
+}
+
 pub async fn get_server_information() -> Result<xdg::ServerInformation> {
     get_server_information_at_bus(Default::default()).await
 }
@@ -226,60 +447,142 @@ pub async fn get_server_information() -> Result<xdg::ServerInformation> {
 /// Listens for the `ActionInvoked(UInt32, String)` Signal.
 ///
 /// No need to use this, check out `Notification::show_and_wait_for_action(FnOnce(action:&str))`
-pub async fn handle_action(id: u32, func: impl ActionResponseHandler) {
-    let connection = zbus::Connection::session().await.unwrap();
-    wait_for_action_signal(&connection, id, func).await;
+pub fn handle_action(id: u32, func: impl ActionResponseHandler) {
+    let signal = runtime::block_on(async move {
+        let connection = dispatcher::session().await?;
+        Ok(next_action_signal(&connection, &NotificationBus::default(), id).await)
+    });
+    match signal {
+        Ok(Some(signal)) => func.call(&signal.as_response()),
+        Ok(None) => {}
+        Err(error) => panic_hook::report(error),
+    }
+}
+
+/// Waits for the first signal of any of `handles`, returns it with the index it is paired with.
+///
+/// Returns `None` if the connections of all handles are gone before that.
+pub fn select_action_blocking(
+    handles: &[(usize, &ZbusNotificationHandle)],
+) -> Option<(usize, OwnedActionResponse)> {
+    let mut waits = handles
+        .iter()
+        .map(|&(index, handle)| {
+            let (id, connection) = (handle.id, handle.connection.clone());
+            let bus = handle.notification.bus.clone();
+            Box::pin(async move { (index, next_action_signal(&connection, &bus, id).await) })
+        })
+        .collect::<Vec<_>>();
+    runtime::block_on(async move {
+        while !waits.is_empty() {
+            match select_all(waits).await {
+                ((index, Some(response)), _, _) => return Some((index, response)),
+                ((_, None), _, rest) => waits = rest,
+            }
+        }
+        None
+    })
+}
+
+/// Parses `ActionInvoked` and `NotificationClosed`, together with the id they refer to.
+fn signal_from_message(msg: &zbus::Message) -> Option<(u32, OwnedActionResponse)> {
+    match notification_signal(msg)? {
+        NotificationSignal::ActionInvoked { id, action_key } => {
+            Some((id, OwnedActionResponse::Custom(action_key)))
+        }
+        NotificationSignal::NotificationClosed { id, reason } => {
+            Some((id, OwnedActionResponse::Closed(reason)))
+        }
+        NotificationSignal::NotificationReplied { .. }
+        | NotificationSignal::ActivationToken { .. } => None,
+    }
+}
+
+/// Parses the signals of the notifications interface.
+fn notification_signal(msg: &zbus::Message) -> Option<NotificationSignal> {
+    let header = msg.header();
+    if header.message_type() != zbus::MessageType::Signal {
+        return None;
+    }
+    match header.member()?.as_str() {
+        "ActionInvoked" => {
+            let (id, action_key) = msg.body().deserialize::<(u32, String)>().ok()?;
+            Some(NotificationSignal::ActionInvoked { id, action_key })
+        }
+        "NotificationClosed" => {
+            let (id, reason) = msg.body().deserialize::<(u32, u32)>().ok()?;
+            Some(NotificationSignal::NotificationClosed {
+                id,
+                reason: reason.into(),
+            })
+        }
+        "NotificationReplied" => {
+            let (id, text) = msg.body().deserialize::<(u32, String)>().ok()?;
+            Some(NotificationSignal::NotificationReplied { id, text })
+        }
+        "ActivationToken" => {
+            let (id, activation_token) = msg.body().deserialize::<(u32, String)>().ok()?;
+            Some(NotificationSignal::ActivationToken {
+                id,
+                activation_token,
+            })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(feature = "async")]
+pub async fn signal_stream(
+    connection: &zbus::Connection,
+) -> Result<impl Stream<Item = NotificationSignal>> {
+    let messages = dispatcher::signal_messages(connection).await?;
+    Ok(messages.filter_map(|msg| ready(msg.ok().as_ref().and_then(notification_signal))))
 }
 
 async fn wait_for_action_signal(
     connection: &zbus::Connection,
+    bus: &NotificationBus,
     id: u32,
     handler: impl ActionResponseHandler,
 ) {
-    let action_signal_rule = MatchRule::builder()
-        .msg_type(zbus::MessageType::Signal)
-        .interface(xdg::NOTIFICATION_INTERFACE)
-        .unwrap()
-        .member("ActionInvoked")
-        .unwrap()
-        .build();
-
-    let proxy = zbus::fdo::DBusProxy::new(connection).await.unwrap();
-    proxy.add_match_rule(action_signal_rule).await.unwrap();
-
-    let close_signal_rule = MatchRule::builder()
-        .msg_type(zbus::MessageType::Signal)
-        .interface(xdg::NOTIFICATION_INTERFACE)
-        .unwrap()
-        .member("NotificationClosed")
-        .unwrap()
-        .build();
-    proxy.add_match_rule(close_signal_rule).await.unwrap();
-
-    while let Ok(Some(msg)) = zbus::MessageStream::from(connection).try_next().await {
-        let header = msg.header();
-        if let zbus::MessageType::Signal = header.message_type() {
-            match header.member() {
-                Some(name) if name == "ActionInvoked" => {
-                    match msg.body().deserialize::<(u32, String)>() {
-                        Ok((nid, action)) if nid == id => {
-                            handler.call(&ActionResponse::Custom(&action));
-                            break;
-                        }
-                        _ => {}
-                    }
-                }
-                Some(name) if name == "NotificationClosed" => {
-                    match msg.body().deserialize::<(u32, u32)>() {
-                        Ok((nid, reason)) if nid == id => {
-                            handler.call(&ActionResponse::Closed(reason.into()));
-                            break;
-                        }
-                        _ => {}
-                    }
-                }
-                _ => {}
-            }
-        }
+    if let Some(signal) = next_action_signal(connection, bus, id).await {
+        handler.call(&signal.as_response());
+    }
+}
+
+/// Waits for the next signal from the server at `bus` concerning `id`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "wait_for_action_signal",
+        level = "debug",
+        skip(connection, bus),
+        fields(outcome = tracing::field::Empty),
+    )
+)]
+async fn next_action_signal(
+    connection: &zbus::Connection,
+    bus: &NotificationBus,
+    id: u32,
+) -> Option<OwnedActionResponse> {
+    let signal = match dispatcher::for_connection(connection).await {
+        Ok(dispatcher) => dispatcher.next(servers::server_of(bus), id).await,
+        Err(_) => None,
+    };
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("outcome", tracing::field::debug(&signal));
+    signal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn handle_is_send_and_sync() {
+        assert_send_sync::<ZbusNotificationHandle>();
+        assert_send_sync::<Notification>();
     }
 }
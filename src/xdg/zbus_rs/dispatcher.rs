@@ -0,0 +1,477 @@
+//! Routes the signals of the notification server to whoever waits for them.
+//!
+//! Instead of every waiting handle adding its own match rules and going through the whole message
+//! stream, each connection subscribes to `ActionInvoked` and `NotificationClosed` once.
+//! Waiting for a notification then only registers its id with the [`SignalDispatcher`].
+//! Signals nobody waited for are kept for a few seconds, for whoever starts waiting right after `show()`.
+//!
+//! Some servers (e.g. GNOME) only send these signals to the connection a notification came from,
+//! so all notifications are sent through one shared [`session()`] connection whose dispatcher serves every handle.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex, MutexGuard, Weak},
+    time::{Duration, Instant},
+};
+
+use zbus::{export::futures_util::TryStreamExt, MatchRule, MessageStream};
+
+use crate::{
+    error::*,
+    xdg::{self, runtime, tags, OwnedActionResponse},
+};
+
+/// How long a signal nobody waited for is kept.
+const UNCLAIMED_FOR: Duration = Duration::from_secs(5);
+
+/// How many signals nobody waited for are kept at most, the oldest one is dropped first.
+const UNCLAIMED_CAPACITY: usize = 64;
+
+static SESSION: Mutex<Option<(zbus::Connection, Arc<SignalDispatcher>)>> = Mutex::new(None);
+
+/// Dispatchers of connections other than [`session()`], by unique name, as long as someone waits on them.
+static OTHERS: Mutex<Vec<(String, Weak<SignalDispatcher>)>> = Mutex::new(Vec::new());
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The session connection shared by all notifications, connected on first use.
+///
+/// Once the connection stopped delivering messages, e.g. because the bus went away, the next call connects anew.
+pub(super) async fn session() -> Result<zbus::Connection> {
+    {
+        let mut session = lock(&SESSION);
+        match &*session {
+            Some((_, dispatcher)) if dispatcher.is_closed() => *session = None,
+            Some((connection, _)) => return Ok(connection.clone()),
+            None => {}
+        }
+    }
+
+    let connection = zbus::Connection::session().await?;
+    let dispatcher = SignalDispatcher::start(&connection).await?;
+
+    let mut session = lock(&SESSION);
+    // someone else might have connected in the meantime, theirs wins
+    let (connection, _) = session.get_or_insert((connection, dispatcher));
+    Ok(connection.clone())
+}
+
+/// Drops the shared [`session()`] connection, the next call connects anew.
+///
+/// Handles keep the connection they were shown on.
+pub(super) fn reset() {
+    lock(&SESSION).take();
+}
+
+/// The dispatcher for signals arriving on `connection`.
+///
+/// Connections other than the shared [`session()`] get a dispatcher of their own,
+/// shared by everyone waiting on that connection, which stops once the last returned `Arc` is dropped.
+pub(super) async fn for_connection(connection: &zbus::Connection) -> Result<Arc<SignalDispatcher>> {
+    if let Some((session, dispatcher)) = &*lock(&SESSION) {
+        if session.unique_name() == connection.unique_name() {
+            return Ok(Arc::clone(dispatcher));
+        }
+    }
+    let name = connection.unique_name().map(ToString::to_string);
+    let running = name.as_ref().and_then(|name| {
+        lock(&OTHERS)
+            .iter()
+            .find(|(other, _)| other == name)
+            .and_then(|(_, dispatcher)| dispatcher.upgrade())
+            .filter(|dispatcher| !dispatcher.is_closed())
+    });
+    if let Some(dispatcher) = running {
+        return Ok(dispatcher);
+    }
+
+    let dispatcher = SignalDispatcher::start(connection).await?;
+    if let Some(name) = name {
+        let mut others = lock(&OTHERS);
+        others.retain(|(other, running)| running.strong_count() > 0 && *other != name);
+        others.push((name, Arc::downgrade(&dispatcher)));
+    }
+    Ok(dispatcher)
+}
+
+/// Every signal of the notifications interface arriving on `connection`.
+pub(super) async fn signal_messages(connection: &zbus::Connection) -> Result<MessageStream> {
+    let rule = MatchRule::builder()
+        .msg_type(zbus::MessageType::Signal)
+        .interface(xdg::NOTIFICATION_INTERFACE)?
+        .build();
+    Ok(MessageStream::for_match_rule(rule, connection, None).await?)
+}
+
+/// Hands the signals of a single connection to the waiters registered for the respective server and id.
+#[derive(Debug)]
+pub(super) struct SignalDispatcher {
+    /// `None` once the connection stopped delivering messages.
+    routes: Mutex<Option<Routes>>,
+}
+
+#[derive(Debug, Default)]
+struct Routes {
+    waiting: HashMap<u32, Vec<Waiter>>,
+    /// Signals that arrived while nobody waited for them, the latest one per server and id.
+    unclaimed: VecDeque<Unclaimed>,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    /// The unique name of the server the notification was sent to, `None` takes signals from any server.
+    server: Option<String>,
+    sender: async_channel::Sender<OwnedActionResponse>,
+    /// Stays registered after the first signal, until the receiver is dropped, see [`SignalDispatcher::subscribe()`].
+    subscribed: bool,
+}
+
+#[derive(Debug)]
+struct Unclaimed {
+    server: String,
+    id: u32,
+    signal: OwnedActionResponse,
+    arrived: Instant,
+}
+
+impl Routes {
+    fn keep_unclaimed(&mut self, server: &str, id: u32, signal: OwnedActionResponse) {
+        self.drop_expired();
+        self.unclaimed
+            .retain(|unclaimed| unclaimed.id != id || unclaimed.server != server);
+        if self.unclaimed.len() == UNCLAIMED_CAPACITY {
+            self.unclaimed.pop_front();
+        }
+        self.unclaimed.push_back(Unclaimed {
+            server: server.to_owned(),
+            id,
+            signal,
+            arrived: Instant::now(),
+        });
+    }
+
+    fn claim(&mut self, server: Option<&str>, id: u32) -> Option<OwnedActionResponse> {
+        self.drop_expired();
+        let index = self.unclaimed.iter().position(|unclaimed| {
+            unclaimed.id == id && server.map_or(true, |server| unclaimed.server == server)
+        })?;
+        self.unclaimed
+            .remove(index)
+            .map(|unclaimed| unclaimed.signal)
+    }
+
+    fn drop_expired(&mut self) {
+        self.unclaimed
+            .retain(|unclaimed| unclaimed.arrived.elapsed() < UNCLAIMED_FOR);
+    }
+
+    /// Registers `waiter` for `id`, forgetting everyone who stopped waiting before their signal came,
+    /// e.g. because the future of [`SignalDispatcher::next()`] was dropped after a timeout.
+    fn wait(&mut self, id: u32, waiter: Waiter) {
+        self.waiting.retain(|_, waiters| {
+            waiters.retain(|waiter| !waiter.sender.is_closed());
+            !waiters.is_empty()
+        });
+        self.waiting.entry(id).or_default().push(waiter);
+    }
+}
+
+impl SignalDispatcher {
+    fn new() -> Self {
+        SignalDispatcher {
+            routes: Mutex::new(Some(Routes::default())),
+        }
+    }
+
+    async fn start(connection: &zbus::Connection) -> Result<Arc<Self>> {
+        let stream = signal_messages(connection).await?;
+        let dispatcher = Arc::new(Self::new());
+        runtime::spawn(Self::route(Arc::downgrade(&dispatcher), stream));
+        Ok(dispatcher)
+    }
+
+    async fn route(dispatcher: Weak<Self>, mut stream: MessageStream) {
+        while let Ok(Some(msg)) = stream.try_next().await {
+            let Some(dispatcher) = dispatcher.upgrade() else {
+                return;
+            };
+            let header = msg.header();
+            let Some(server) = header.sender() else {
+                continue;
+            };
+            if let Some((id, signal)) = super::signal_from_message(&msg) {
+                if let OwnedActionResponse::Closed(_) = signal {
+                    tags::forget(server, id);
+                }
+                dispatcher.dispatch(server, id, signal);
+            }
+        }
+        // nothing is coming anymore, let everybody who is still waiting know
+        if let Some(dispatcher) = dispatcher.upgrade() {
+            lock(&dispatcher.routes).take();
+        }
+    }
+
+    /// Whether the connection stopped delivering messages.
+    fn is_closed(&self) -> bool {
+        lock(&self.routes).is_none()
+    }
+
+    /// Waits for the next signal from the server with the unique name `server` concerning notification `id`,
+    /// or takes the one that arrived shortly before.
+    ///
+    /// Returns `None` if the connection is gone before that happens.
+    pub(super) async fn next(
+        &self,
+        server: Option<String>,
+        id: u32,
+    ) -> Option<OwnedActionResponse> {
+        let (sender, receiver) = async_channel::bounded(1);
+        match &mut *lock(&self.routes) {
+            Some(routes) => {
+                if let Some(signal) = routes.claim(server.as_deref(), id) {
+                    return Some(signal);
+                }
+                routes.wait(
+                    id,
+                    Waiter {
+                        server,
+                        sender,
+                        subscribed: false,
+                    },
+                );
+            }
+            None => return None,
+        }
+        receiver.recv().await.ok()
+    }
+
+    /// Receives every signal from `server` concerning notification `id` from now on,
+    /// starting with the one that arrived shortly before, until the receiver is dropped.
+    ///
+    /// The receiver is closed once the connection is gone, `None` if it already is.
+    pub(super) fn subscribe(
+        &self,
+        server: Option<String>,
+        id: u32,
+    ) -> Option<async_channel::Receiver<OwnedActionResponse>> {
+        let (sender, receiver) = async_channel::unbounded();
+        let mut routes = lock(&self.routes);
+        let routes = routes.as_mut()?;
+        if let Some(signal) = routes.claim(server.as_deref(), id) {
+            let _ = sender.try_send(signal);
+        }
+        routes.wait(
+            id,
+            Waiter {
+                server,
+                sender,
+                subscribed: true,
+            },
+        );
+        Some(receiver)
+    }
+
+    fn dispatch(&self, server: &str, id: u32, signal: OwnedActionResponse) {
+        let mut routes = lock(&self.routes);
+        let Some(routes) = routes.as_mut() else {
+            return;
+        };
+        let (waiters, mut others): (Vec<_>, Vec<_>) = routes
+            .waiting
+            .remove(&id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|waiter| !waiter.sender.is_closed())
+            .partition(|waiter| waiter.server.as_deref().map_or(true, |s| s == server));
+        for waiter in &waiters {
+            let _ = waiter.sender.try_send(signal.clone());
+        }
+        let nobody_waited = waiters.is_empty();
+        others.extend(waiters.into_iter().filter(|waiter| waiter.subscribed));
+        if !others.is_empty() {
+            routes.waiting.insert(id, others);
+        }
+        if nobody_waited {
+            routes.keep_unclaimed(server, id, signal);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xdg::CloseReason;
+    use std::thread;
+    use zbus::export::futures_util::FutureExt;
+
+    fn registered(dispatcher: &SignalDispatcher, id: u32) -> usize {
+        lock(&dispatcher.routes)
+            .as_ref()
+            .and_then(|routes| routes.waiting.get(&id))
+            .map_or(0, Vec::len)
+    }
+
+    fn wait(
+        dispatcher: &Arc<SignalDispatcher>,
+        server: &str,
+        id: u32,
+    ) -> thread::JoinHandle<Option<OwnedActionResponse>> {
+        let before = registered(dispatcher, id);
+        let (waiting, server) = (Arc::clone(dispatcher), server.to_owned());
+        let handle = thread::spawn(move || zbus::block_on(waiting.next(Some(server), id)));
+        while registered(dispatcher, id) == before {
+            thread::yield_now();
+        }
+        handle
+    }
+
+    #[test]
+    fn routes_by_id() {
+        let dispatcher = Arc::new(SignalDispatcher::new());
+        let waiters = (1..=8)
+            .map(|id| (id, wait(&dispatcher, ":1.1", id)))
+            .collect::<Vec<_>>();
+
+        for id in (1..=8).rev() {
+            dispatcher.dispatch(
+                ":1.1",
+                id,
+                OwnedActionResponse::Custom(format!("action-{}", id)),
+            );
+        }
+
+        for (id, waiter) in waiters {
+            match waiter.join().unwrap() {
+                Some(OwnedActionResponse::Custom(action)) => {
+                    assert_eq!(action, format!("action-{}", id));
+                }
+                _ => panic!("no action for {}", id),
+            }
+        }
+        assert!(lock(&dispatcher.routes)
+            .as_ref()
+            .unwrap()
+            .waiting
+            .is_empty());
+    }
+
+    #[test]
+    fn routes_by_server() {
+        let dispatcher = Arc::new(SignalDispatcher::new());
+        let waiter = wait(&dispatcher, ":1.1", 1);
+
+        dispatcher.dispatch(":1.2", 1, OwnedActionResponse::Custom("other".into()));
+        dispatcher.dispatch(":1.1", 1, OwnedActionResponse::Custom("ours".into()));
+
+        assert_eq!(
+            waiter.join().unwrap(),
+            Some(OwnedActionResponse::Custom("ours".into()))
+        );
+    }
+
+    #[test]
+    fn every_waiter_of_an_id_is_notified() {
+        let dispatcher = Arc::new(SignalDispatcher::new());
+        let first = wait(&dispatcher, ":1.1", 1);
+        let second = wait(&dispatcher, ":1.1", 1);
+
+        dispatcher.dispatch(":1.1", 2, OwnedActionResponse::Custom("unrelated".into()));
+        dispatcher.dispatch(
+            ":1.1",
+            1,
+            OwnedActionResponse::Closed(CloseReason::Dismissed),
+        );
+
+        for waiter in [first, second] {
+            assert!(matches!(
+                waiter.join().unwrap(),
+                Some(OwnedActionResponse::Closed(CloseReason::Dismissed))
+            ));
+        }
+    }
+
+    #[test]
+    fn early_signals_are_kept_for_late_waiters() {
+        let dispatcher = SignalDispatcher::new();
+        dispatcher.dispatch(":1.1", 1, OwnedActionResponse::Custom("first".into()));
+        dispatcher.dispatch(":1.1", 1, OwnedActionResponse::Custom("latest".into()));
+        dispatcher.dispatch(":1.2", 1, OwnedActionResponse::Custom("other".into()));
+
+        assert_eq!(
+            zbus::block_on(dispatcher.next(Some(":1.1".into()), 1)),
+            Some(OwnedActionResponse::Custom("latest".into()))
+        );
+        let mut routes = lock(&dispatcher.routes);
+        let routes = routes.as_mut().unwrap();
+        assert_eq!(routes.claim(Some(":1.1"), 1), None);
+        assert_eq!(
+            routes.claim(None, 1),
+            Some(OwnedActionResponse::Custom("other".into()))
+        );
+    }
+
+    #[test]
+    fn subscriptions_receive_every_signal() {
+        let dispatcher = SignalDispatcher::new();
+        dispatcher.dispatch(":1.1", 1, OwnedActionResponse::Custom("early".into()));
+        let signals = dispatcher.subscribe(Some(":1.1".into()), 1).unwrap();
+
+        dispatcher.dispatch(":1.1", 1, OwnedActionResponse::Custom("open".into()));
+        dispatcher.dispatch(
+            ":1.1",
+            1,
+            OwnedActionResponse::Closed(CloseReason::Dismissed),
+        );
+        for expected in [
+            OwnedActionResponse::Custom("early".into()),
+            OwnedActionResponse::Custom("open".into()),
+            OwnedActionResponse::Closed(CloseReason::Dismissed),
+        ] {
+            assert_eq!(signals.try_recv().ok(), Some(expected));
+        }
+        assert_eq!(registered(&dispatcher, 1), 1);
+
+        drop(signals);
+        dispatcher.dispatch(":1.1", 1, OwnedActionResponse::Custom("late".into()));
+        assert_eq!(registered(&dispatcher, 1), 0);
+    }
+
+    #[test]
+    fn abandoned_waiters_are_forgotten() {
+        let dispatcher = SignalDispatcher::new();
+        for id in 1..=8 {
+            assert!(dispatcher
+                .next(Some(":1.1".into()), id)
+                .now_or_never()
+                .is_none());
+        }
+        assert!((1..=7).all(|id| registered(&dispatcher, id) == 0));
+        assert_eq!(registered(&dispatcher, 8), 1);
+
+        let signals = dispatcher.subscribe(Some(":1.1".into()), 9).unwrap();
+        assert_eq!(registered(&dispatcher, 8), 0);
+        assert_eq!(registered(&dispatcher, 9), 1);
+
+        drop(signals);
+        assert!(dispatcher.next(None, 10).now_or_never().is_none());
+        assert_eq!(registered(&dispatcher, 9), 0);
+    }
+
+    #[test]
+    fn waiters_give_up_when_connection_is_gone() {
+        let dispatcher = Arc::new(SignalDispatcher::new());
+        let waiter = wait(&dispatcher, ":1.1", 1);
+
+        assert!(!dispatcher.is_closed());
+        lock(&dispatcher.routes).take();
+
+        assert!(dispatcher.is_closed());
+        assert!(waiter.join().unwrap().is_none());
+        assert!(zbus::block_on(dispatcher.next(None, 1)).is_none());
+    }
+}
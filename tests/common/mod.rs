@@ -0,0 +1,182 @@
+//! A minimal notification server that runs inside the test process, shared by the test binaries.
+//!
+//! Every test claims its own bus name via [`Notification::at_bus`](notify_rust::Notification::at_bus), so they can run in parallel.
+#![allow(dead_code)]
+
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use zbus::{blocking::Connection, interface, object_server::SignalContext};
+
+pub const PATH: &str = "/org/freedesktop/Notifications";
+
+/// What the mock server received via `Notify`.
+#[derive(Clone, Debug)]
+pub struct Received {
+    pub replaces_id: u32,
+    pub app_icon: String,
+    pub summary: String,
+    pub body: String,
+    pub actions: Vec<String>,
+    pub hints: Vec<String>,
+    pub expire_timeout: i32,
+}
+
+#[derive(Default)]
+pub struct MockNotifications {
+    pub capabilities: Vec<String>,
+    pub next_id: AtomicU32,
+    pub received: Arc<Mutex<Vec<Received>>>,
+    pub closed: Arc<Mutex<Vec<u32>>>,
+}
+
+#[interface(name = "org.freedesktop.Notifications")]
+impl MockNotifications {
+    #[allow(clippy::too_many_arguments)]
+    pub fn notify(
+        &self,
+        _app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: Vec<String>,
+        hints: std::collections::HashMap<String, zbus::zvariant::OwnedValue>,
+        expire_timeout: i32,
+    ) -> u32 {
+        let mut hints = hints.into_keys().collect::<Vec<_>>();
+        hints.sort();
+        let id = if replaces_id == 0 {
+            self.next_id.fetch_add(1, Ordering::SeqCst) + 1
+        } else {
+            replaces_id
+        };
+        self.received.lock().unwrap().push(Received {
+            replaces_id,
+            app_icon: app_icon.into(),
+            summary: summary.into(),
+            body: body.into(),
+            actions,
+            hints,
+            expire_timeout,
+        });
+        id
+    }
+
+    pub fn get_capabilities(&self) -> Vec<String> {
+        self.capabilities.clone()
+    }
+
+    pub fn close_notification(&self, id: u32) {
+        self.closed.lock().unwrap().push(id);
+    }
+
+    #[zbus(signal)]
+    pub async fn action_invoked(
+        ctx: &SignalContext<'_>,
+        id: u32,
+        action_key: &str,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    pub async fn notification_closed(
+        ctx: &SignalContext<'_>,
+        id: u32,
+        reason: u32,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    pub async fn activation_token(
+        ctx: &SignalContext<'_>,
+        id: u32,
+        activation_token: &str,
+    ) -> zbus::Result<()>;
+}
+
+pub struct MockServer {
+    pub connection: Connection,
+    pub received: Arc<Mutex<Vec<Received>>>,
+    pub closed: Arc<Mutex<Vec<u32>>>,
+}
+
+impl MockServer {
+    pub fn start(sub_bus: &str) -> Self {
+        Self::with_capabilities(
+            sub_bus,
+            &["actions", "body", "body-markup", "icon-static", "sound"],
+        )
+    }
+
+    pub fn with_capabilities(sub_bus: &str, capabilities: &[&str]) -> Self {
+        let server = MockNotifications {
+            capabilities: capabilities.iter().map(|&c| c.to_owned()).collect(),
+            ..MockNotifications::default()
+        };
+        let received = server.received.clone();
+        let closed = server.closed.clone();
+        let connection = zbus::blocking::connection::Builder::session()
+            .unwrap()
+            .name(format!("de.hoodie.Notification.{sub_bus}"))
+            .unwrap()
+            .serve_at(PATH, server)
+            .unwrap()
+            .build()
+            .unwrap();
+        MockServer {
+            connection,
+            received,
+            closed,
+        }
+    }
+
+    pub fn received(&self) -> Vec<Received> {
+        self.received.lock().unwrap().clone()
+    }
+
+    pub fn closed(&self) -> Vec<u32> {
+        self.closed.lock().unwrap().clone()
+    }
+
+    /// Emits `ActionInvoked` after a short delay, so the caller can start waiting for it first.
+    pub fn invoke_later(&self, id: u32, action_key: impl Into<String>) -> thread::JoinHandle<()> {
+        let connection = self.connection.clone();
+        let action_key = action_key.into();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            let iface = connection
+                .object_server()
+                .interface::<_, MockNotifications>(PATH)
+                .unwrap();
+            zbus::block_on(MockNotifications::action_invoked(
+                iface.signal_context(),
+                id,
+                &action_key,
+            ))
+            .unwrap();
+        })
+    }
+
+    /// Emits `NotificationClosed` after `delay`.
+    pub fn close_later(&self, id: u32, reason: u32, delay: Duration) -> thread::JoinHandle<()> {
+        let connection = self.connection.clone();
+        thread::spawn(move || {
+            thread::sleep(delay);
+            let iface = connection
+                .object_server()
+                .interface::<_, MockNotifications>(PATH)
+                .unwrap();
+            zbus::block_on(MockNotifications::notification_closed(
+                iface.signal_context(),
+                id,
+                reason,
+            ))
+            .unwrap();
+        })
+    }
+}
@@ -0,0 +1,42 @@
+#![cfg(all(
+    any(feature = "dbus", feature = "zbus"),
+    unix,
+    not(target_os = "macos")
+))]
+//! `NOTIFY_RUST_DRY_RUN` affects the whole process, so this runs in a test binary of its own.
+
+use std::sync::Mutex;
+
+use notify_rust::Notification;
+
+#[test]
+fn dry_run_sends_nothing() {
+    // any real send would fail
+    std::env::set_var(
+        "DBUS_SESSION_BUS_ADDRESS",
+        "unix:path=/nonexistent/notify-rust",
+    );
+    std::env::set_var("NOTIFY_RUST_DRY_RUN", "1");
+
+    static SENT: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    notify_rust::set_send_observer(Box::new(|notification: &Notification| {
+        SENT.lock().unwrap().push(notification.summary.clone());
+    }));
+
+    let mut handle = Notification::new().summary("Syncing").show().unwrap();
+    let id = handle.id();
+    assert_ne!(id, 0);
+    handle.summary("Synced");
+    handle.update();
+    assert_eq!(handle.id(), id);
+
+    let mut action = None;
+    handle.wait_for_action(|invoked| action = Some(invoked.to_owned()));
+    assert_eq!(action.as_deref(), Some("__closed"));
+
+    let light = Notification::new().summary("Light").show_light().unwrap();
+    assert_ne!(light.id(), id);
+    light.close().unwrap();
+
+    assert_eq!(*SENT.lock().unwrap(), ["Syncing", "Synced", "Light"]);
+}
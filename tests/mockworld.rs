@@ -0,0 +1,967 @@
+#![cfg(all(feature = "zbus", unix, not(target_os = "macos")))]
+#![allow(deprecated)]
+//! Tests against a minimal notification server that runs inside the test process.
+//!
+//! Every test claims its own bus name via [`Notification::at_bus`], so they can run in parallel.
+
+mod common;
+
+use std::{thread, time::Duration};
+
+use common::{MockNotifications, MockServer, PATH};
+
+use notify_rust::{
+    error::ErrorKind, ActionResponse, CloseReason, Hint, Notification, OwnedActionResponse,
+    ServerEvent,
+};
+use zbus::interface;
+
+/// A server that never gets around to replying.
+struct StuckNotifications;
+
+#[interface(name = "org.freedesktop.Notifications")]
+impl StuckNotifications {
+    #[allow(clippy::too_many_arguments)]
+    async fn notify(
+        &self,
+        _app_name: &str,
+        _replaces_id: u32,
+        _app_icon: &str,
+        _summary: &str,
+        _body: &str,
+        _actions: Vec<String>,
+        _hints: std::collections::HashMap<String, zbus::zvariant::OwnedValue>,
+        _expire_timeout: i32,
+    ) -> u32 {
+        std::future::pending().await
+    }
+
+    fn get_capabilities(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+#[test]
+fn updated_actions_fire_on_the_same_handle() {
+    let server = MockServer::start("update_actions");
+
+    let mut handle = Notification::at_bus("update_actions")
+        .summary("Incoming call")
+        .action("accept", "Accept")
+        .show()
+        .unwrap();
+    let id = handle.id();
+
+    handle.update_actions(&[("hang-up", "Hang up")]);
+    assert_eq!(handle.id(), id);
+
+    let received = server.received();
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[1].replaces_id, id);
+    assert_eq!(received[1].actions, ["hang-up", "Hang up"]);
+
+    let emitter = server.invoke_later(id, "hang-up");
+    let mut invoked = None;
+    handle.wait_for_action(|action| invoked = Some(action.to_owned()));
+    emitter.join().unwrap();
+
+    assert_eq!(invoked.as_deref(), Some("hang-up"));
+}
+
+#[test]
+fn handle_outlives_wait_for_action_ref() {
+    let server = MockServer::start("wait_ref");
+
+    let handle = Notification::at_bus("wait_ref")
+        .summary("Update available")
+        .action("install", "Install")
+        .show()
+        .unwrap();
+    let id = handle.id();
+
+    let emitter = server.invoke_later(id, "install");
+    let mut invoked = None;
+    handle.wait_for_action_ref(|response: &ActionResponse| {
+        invoked = response.as_custom().map(String::from);
+    });
+    emitter.join().unwrap();
+    assert_eq!(invoked.as_deref(), Some("install"));
+
+    handle.close();
+    // dbus-rs doesn't wait for the reply to `CloseNotification`
+    let started = std::time::Instant::now();
+    while server.closed().is_empty() && started.elapsed() < Duration::from_secs(2) {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(server.closed(), [id]);
+}
+
+#[async_std::test]
+async fn wait_for_action_ref_async_borrows() {
+    let server = MockServer::start("wait_ref_async");
+
+    let mut handle = Notification::at_bus("wait_ref_async")
+        .summary("Pick one")
+        .action("left", "Left")
+        .show_async()
+        .await
+        .unwrap();
+    let id = handle.id();
+
+    let emitter = server.invoke_later(id, "left");
+    let mut invoked = None;
+    handle
+        .wait_for_action_ref_async(|response: &ActionResponse| {
+            invoked = response.as_custom().map(String::from);
+        })
+        .await;
+    emitter.join().unwrap();
+    assert_eq!(invoked.as_deref(), Some("left"));
+
+    handle.summary("Picked left");
+    handle.update();
+    assert_eq!(server.received()[1].replaces_id, id);
+}
+
+#[test]
+fn update_goes_to_the_same_bus() {
+    let server = MockServer::start("update_bus");
+
+    let mut handle = Notification::at_bus("update_bus")
+        .summary("before")
+        .show()
+        .unwrap();
+    handle.summary("after");
+    handle.update();
+
+    let summaries = server
+        .received()
+        .into_iter()
+        .map(|received| received.summary)
+        .collect::<Vec<_>>();
+    assert_eq!(summaries, ["before", "after"]);
+}
+
+#[test]
+fn merge_by_summary_replaces_recent() {
+    let server = MockServer::start("merge_by_summary");
+
+    let first = Notification::at_bus("merge_by_summary")
+        .summary("merge_by_summary")
+        .body("Syncing…")
+        .merge_by_summary(Duration::from_secs(60))
+        .show()
+        .unwrap();
+    let second = Notification::at_bus("merge_by_summary")
+        .summary("merge_by_summary")
+        .body("Sync complete")
+        .merge_by_summary(Duration::from_secs(60))
+        .show()
+        .unwrap();
+    Notification::at_bus("merge_by_summary")
+        .summary("merge_by_summary")
+        .show()
+        .unwrap();
+
+    assert_eq!(first.id(), second.id());
+    let replaced = server
+        .received()
+        .into_iter()
+        .map(|received| received.replaces_id)
+        .collect::<Vec<_>>();
+    assert_eq!(replaced, [0, first.id(), 0]);
+}
+
+#[test]
+fn merge_by_summary_prefix_replaces_recent() {
+    let server = MockServer::start("merge_by_prefix");
+
+    let syncing = Notification::at_bus("merge_by_prefix")
+        .summary("Syncing…")
+        .merge_by_summary_prefix("Sync", Duration::from_secs(60))
+        .show()
+        .unwrap();
+    let complete = Notification::at_bus("merge_by_prefix")
+        .summary("Sync complete")
+        .merge_by_summary_prefix("Sync", Duration::from_secs(60))
+        .show()
+        .unwrap();
+
+    assert_eq!(syncing.id(), complete.id());
+    assert_eq!(server.received()[1].replaces_id, syncing.id());
+}
+
+#[test]
+fn concurrent_waits_get_their_own_action() {
+    let server = MockServer::start("concurrent_waits");
+
+    let waiters = (0..8)
+        .map(|_| {
+            let (shown, id) = std::sync::mpsc::channel();
+            let waiter = thread::spawn(move || {
+                let handle = Notification::at_bus("concurrent_waits")
+                    .summary("concurrent")
+                    .show()
+                    .unwrap();
+                shown.send(handle.id()).unwrap();
+                let mut invoked = None;
+                handle.wait_for_action(|action| invoked = Some(action.to_owned()));
+                invoked
+            });
+            (id.recv().unwrap(), waiter)
+        })
+        .collect::<Vec<_>>();
+
+    let emitters = waiters
+        .iter()
+        .rev()
+        .map(|(id, _)| server.invoke_later(*id, format!("action-{id}")))
+        .collect::<Vec<_>>();
+
+    for (id, waiter) in waiters {
+        assert_eq!(waiter.join().unwrap(), Some(format!("action-{id}")));
+    }
+    for emitter in emitters {
+        emitter.join().unwrap();
+    }
+}
+
+#[test]
+fn light_handle_closes() {
+    let server = MockServer::start("light_handle");
+
+    let handle = Notification::at_bus("light_handle")
+        .summary("light")
+        .show_light()
+        .unwrap();
+    let id = handle.id();
+    assert_eq!(server.received()[0].summary, "light");
+
+    handle.close().unwrap();
+    assert_eq!(server.closed(), [id]);
+}
+
+#[test]
+fn handles_survive_reset_connection() {
+    let server = MockServer::start("reset_connection");
+
+    let first = Notification::at_bus("reset_connection")
+        .summary("first")
+        .show()
+        .unwrap();
+    notify_rust::reset_connection();
+    let second = Notification::at_bus("reset_connection")
+        .summary("second")
+        .show()
+        .unwrap();
+    let (first_id, second_id) = (first.id(), second.id());
+    first.close();
+    second.close();
+
+    assert_ne!(first_id, second_id);
+    assert_eq!(server.received().len(), 2);
+    for _ in 0..100 {
+        if server.closed().len() == 2 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(server.closed(), [first_id, second_id]);
+}
+
+#[test]
+fn send_latency_is_measured() {
+    let _server = MockServer::start("send_latency");
+
+    let mut handle = Notification::at_bus("send_latency")
+        .summary("ping")
+        .show()
+        .unwrap();
+    assert!(handle.last_send_latency() > Duration::ZERO);
+    assert!(handle.last_send_latency() < Duration::from_secs(5));
+
+    handle.summary("pong");
+    handle.update();
+    assert!(handle.last_send_latency() > Duration::ZERO);
+}
+
+#[test]
+fn modify_resends_with_same_id() {
+    let server = MockServer::start("modify");
+
+    let mut handle = Notification::at_bus("modify")
+        .summary("New message")
+        .show()
+        .unwrap();
+    let id = handle.id();
+
+    handle.modify(|notification| {
+        notification.summary("Message read");
+    });
+
+    assert_eq!(handle.id(), id);
+    assert_eq!(handle.summary, "Message read");
+    let received = server.received();
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[1].replaces_id, id);
+    assert_eq!(received[1].summary, "Message read");
+}
+
+#[test]
+fn update_icon_resends_with_same_id() {
+    let server = MockServer::start("update_icon");
+
+    let mut handle = Notification::at_bus("update_icon")
+        .summary("Downloading")
+        .body("ubuntu.iso")
+        .icon("folder-download")
+        .show()
+        .unwrap();
+    let id = handle.id();
+
+    handle.update_icon("emblem-ok");
+
+    assert_eq!(handle.id(), id);
+    let received = server.received();
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0].app_icon, "folder-download");
+    assert_eq!(received[1].replaces_id, id);
+    assert_eq!(received[1].app_icon, "emblem-ok");
+    assert_eq!(received[1].summary, "Downloading");
+    assert_eq!(received[1].body, "ubuntu.iso");
+}
+
+#[test]
+fn complete_drops_progress() {
+    let server = MockServer::start("complete");
+
+    let mut handle = Notification::at_bus("complete")
+        .summary("Downloading")
+        .hint(Hint::CustomInt("value".into(), 40))
+        .hint(Hint::Category("transfer".into()))
+        .timeout(0)
+        .show()
+        .unwrap();
+    let id = handle.id();
+
+    handle.complete("Download finished", "ubuntu.iso", "emblem-ok");
+
+    let received = server.received();
+    assert_eq!(received[0].hints, ["category", "value"]);
+    assert_eq!(received[0].expire_timeout, 0);
+    assert_eq!(received[1].replaces_id, id);
+    assert_eq!(received[1].summary, "Download finished");
+    assert_eq!(received[1].body, "ubuntu.iso");
+    assert_eq!(received[1].hints, ["category"]);
+    assert_eq!(received[1].expire_timeout, -1);
+}
+
+#[test]
+fn update_checks_lengths() {
+    let server = MockServer::start("update_lengths");
+
+    let mut handle = Notification::at_bus("update_lengths")
+        .summary("Build log")
+        .body("starting")
+        .validate_lengths(64, 20)
+        .show()
+        .unwrap();
+    handle.body("line\u{1b}[31m 1\nline 2");
+    handle.update();
+
+    handle.body("a line that is far too long");
+    let refused = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| handle.update()));
+
+    assert!(refused.is_err());
+    let received = server.received();
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[1].body, "line[31m 1\nline 2");
+}
+
+#[test]
+fn timestamp_is_rendered_on_every_send() {
+    let server = MockServer::start("timestamp");
+
+    let mut handle = Notification::at_bus("timestamp")
+        .summary("Disk almost full")
+        .body("/var has 2% left")
+        .with_timestamp_format("[%Y]")
+        .show()
+        .unwrap();
+    handle.body("");
+    handle.update();
+
+    let received = server.received();
+    let year = &received[0].body[1..5];
+    assert!(
+        year.chars().all(|c| c.is_ascii_digit()),
+        "{:?}",
+        received[0]
+    );
+    assert_eq!(received[0].body, format!("[{}] /var has 2% left", year));
+    assert_eq!(received[1].body, format!("[{}]", year));
+    assert_eq!(handle.body, "");
+}
+
+#[test]
+fn persisted_id_is_replaced_next_run() {
+    let server = MockServer::start("persisted_id");
+    let path =
+        std::env::temp_dir().join(format!("notify-rust-mockworld-{}.id", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let run = || {
+        Notification::at_bus("persisted_id")
+            .summary("Backup running")
+            .replace_from_persisted(&path)
+            .persist_id_to(&path)
+            .show()
+            .unwrap()
+            .id()
+    };
+    let first = run();
+    let second = run();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(first, second);
+    let replaced = server
+        .received()
+        .into_iter()
+        .map(|received| received.replaces_id)
+        .collect::<Vec<_>>();
+    assert_eq!(replaced, [0, first]);
+}
+
+#[test]
+fn call_timeout_gives_up_on_stuck_server() {
+    let _server = zbus::blocking::connection::Builder::session()
+        .unwrap()
+        .name("de.hoodie.Notification.call_timeout")
+        .unwrap()
+        .serve_at(PATH, StuckNotifications)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    let error = Notification::at_bus("call_timeout")
+        .summary("stuck")
+        .with_call_timeout(Duration::from_millis(200))
+        .show()
+        .unwrap_err();
+
+    assert!(
+        matches!(error.kind(), ErrorKind::Timeout(timeout) if *timeout == Duration::from_millis(200))
+    );
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn wait_for_close_timeout_returns_reason() {
+    let server = MockServer::start("close_timeout_reason");
+
+    let handle = Notification::at_bus("close_timeout_reason")
+        .summary("closing")
+        .show()
+        .unwrap();
+    let id = handle.id();
+
+    // actions don't end the wait
+    let action = server.invoke_later(id, "default");
+    let close = server.close_later(id, 2, Duration::from_millis(600));
+    let reason = handle.wait_for_close_timeout(Duration::from_secs(10));
+    action.join().unwrap();
+    close.join().unwrap();
+
+    assert_eq!(reason, CloseReason::Dismissed);
+}
+
+#[test]
+fn on_close_sees_last_sent_notification() {
+    let server = MockServer::start("on_close_notification");
+
+    let mut handle = Notification::at_bus("on_close_notification")
+        .summary("Uploading")
+        .show()
+        .unwrap();
+    let id = handle.id();
+    handle.summary("Upload failed");
+    handle.update();
+
+    let close = server.close_later(id, 2, Duration::from_millis(200));
+    let closed = std::cell::RefCell::new(None);
+    handle.on_close(|reason: CloseReason, notification: &Notification| {
+        *closed.borrow_mut() = Some((reason, notification.summary.clone()));
+    });
+    close.join().unwrap();
+
+    assert_eq!(
+        closed.into_inner(),
+        Some((CloseReason::Dismissed, "Upload failed".to_owned()))
+    );
+}
+
+#[test]
+fn wait_for_close_timeout_gives_up() {
+    let _server = MockServer::start("close_timeout_silent");
+
+    let handle = Notification::at_bus("close_timeout_silent")
+        .summary("never closed")
+        .show()
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    let reason = handle.wait_for_close_timeout(Duration::from_millis(300));
+
+    assert_eq!(reason, CloseReason::Expired);
+    assert!(started.elapsed() >= Duration::from_millis(300));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn send_id_replaces_by_id() {
+    let server = MockServer::start("send_id");
+
+    let id = Notification::at_bus("send_id")
+        .summary("Downloading")
+        .send_id()
+        .unwrap();
+    let replaced = Notification::at_bus("send_id")
+        .summary("Download complete")
+        .id(id)
+        .send_id()
+        .unwrap();
+
+    assert_eq!(replaced, id);
+    let received = server.received();
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[1].replaces_id, id);
+}
+
+#[async_std::test]
+async fn send_id_async_returns_id() {
+    let server = MockServer::start("send_id_async");
+
+    let id = Notification::at_bus("send_id_async")
+        .summary("async")
+        .send_id_async()
+        .await
+        .unwrap();
+
+    assert_ne!(id, 0);
+    assert_eq!(server.received()[0].summary, "async");
+}
+
+#[test]
+fn cancelled_schedule_never_sends() {
+    let server = MockServer::start("schedule_cancel");
+
+    let scheduled = Notification::at_bus("schedule_cancel")
+        .summary("Connection lost")
+        .show_after(Duration::from_millis(200));
+    assert!(scheduled.cancel());
+    assert!(scheduled.cancel());
+    assert!(scheduled.wait().is_none());
+
+    thread::sleep(Duration::from_millis(300));
+    assert!(server.received().is_empty());
+}
+
+#[test]
+fn schedule_sends_after_delay() {
+    let server = MockServer::start("schedule_send");
+
+    let started = std::time::Instant::now();
+    let scheduled = Notification::at_bus("schedule_send")
+        .summary("Still offline")
+        .show_after(Duration::from_millis(200));
+    assert!(server.received().is_empty());
+
+    while server.received().is_empty() {
+        assert!(started.elapsed() < Duration::from_secs(5));
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(!scheduled.cancel());
+    let handle = scheduled.wait().unwrap().unwrap();
+
+    assert!(started.elapsed() >= Duration::from_millis(200));
+    assert_eq!(server.received()[0].summary, "Still offline");
+    assert_ne!(handle.id(), 0);
+}
+
+#[test]
+fn close_by_id_without_handle() {
+    let server = MockServer::start("close_by_id");
+
+    let id = Notification::at_bus("close_by_id")
+        .summary("Uploading")
+        .send_id()
+        .unwrap();
+    notify_rust::close_notification_at_bus(id, "close_by_id").unwrap();
+
+    assert_eq!(server.closed(), [id]);
+}
+
+#[test]
+fn close_by_tag_closes_the_whole_thread() {
+    let server = MockServer::start("close_by_tag");
+
+    // ids other tests' servers won't close
+    for (id, thread) in [
+        (940_001, "tag-chat"),
+        (940_002, "tag-chat"),
+        (940_003, "tag-other"),
+    ] {
+        Notification::at_bus("close_by_tag")
+            .summary("Alice")
+            .stack_tag(thread)
+            .id(id)
+            .send_id()
+            .unwrap();
+    }
+    assert_eq!(notify_rust::close_by_tag("tag-chat").unwrap(), 2);
+    assert_eq!(server.closed(), [940_001, 940_002]);
+    assert_eq!(notify_rust::close_by_tag("tag-chat").unwrap(), 0);
+}
+
+#[test]
+fn reconnecting_handle_survives_server_restart() {
+    let server = MockServer::start("reconnecting");
+    let mut handle = Notification::at_bus("reconnecting")
+        .summary("VPN connected")
+        .show_reconnecting()
+        .unwrap();
+    let id = handle.id();
+    drop(server);
+
+    // the new server doesn't know the id, but takes it as a replacement like any server would
+    let server = MockServer::start("reconnecting");
+    handle.summary("VPN reconnecting");
+    handle.update().unwrap();
+    assert_eq!(handle.id(), id);
+    let received = server.received();
+    assert_eq!(received[0].summary, "VPN reconnecting");
+    assert_eq!(received[0].replaces_id, id);
+
+    handle.close().unwrap();
+    assert_eq!(server.closed(), [id]);
+}
+
+#[test]
+fn select_action_closes_the_others() {
+    let server = MockServer::start("select_action");
+
+    let handles = ["Coffee", "Tea", "Water"]
+        .iter()
+        .map(|drink| {
+            Notification::at_bus("select_action")
+                .summary(drink)
+                .action("pick", "Pick")
+                .show()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let ids = handles.iter().map(|handle| handle.id()).collect::<Vec<_>>();
+    let invoking = server.invoke_later(ids[1], "pick");
+
+    let picked = notify_rust::select_action(handles);
+    invoking.join().unwrap();
+
+    match picked {
+        Some((1, OwnedActionResponse::Custom(action))) => assert_eq!(action, "pick"),
+        other => panic!("unexpected {:?}", other),
+    }
+    // dbus-rs doesn't wait for the reply to `CloseNotification`
+    let started = std::time::Instant::now();
+    while server.closed().len() < 2 && started.elapsed() < Duration::from_secs(2) {
+        thread::sleep(Duration::from_millis(10));
+    }
+    let mut closed = server.closed();
+    closed.sort_unstable();
+    assert_eq!(closed, [ids[0], ids[2]]);
+}
+
+#[async_std::test]
+async fn watch_server_sees_restart() {
+    use zbus::export::futures_util::StreamExt;
+
+    let mut events = notify_rust::watch_server_at_bus("watch_server")
+        .await
+        .unwrap();
+
+    let server = MockServer::start("watch_server");
+    assert_eq!(events.next().await, Some(ServerEvent::Started));
+    drop(server);
+    assert_eq!(events.next().await, Some(ServerEvent::Stopped));
+    let _server = MockServer::start("watch_server");
+    assert_eq!(events.next().await, Some(ServerEvent::Started));
+}
+
+#[async_std::test]
+async fn signal_stream_sees_every_id() {
+    use notify_rust::NotificationSignal;
+    use zbus::export::futures_util::StreamExt;
+
+    let server = MockServer::start("signal_stream");
+    let connection = zbus::Connection::session().await.unwrap();
+    let signals = notify_rust::signal_stream(&connection).await.unwrap();
+    // other tests' servers signal on the same bus
+    let mut signals = signals.filter(|signal| {
+        let id = signal.id();
+        std::future::ready((950_001..=950_002).contains(&id))
+    });
+
+    let iface = server
+        .connection
+        .object_server()
+        .interface::<_, MockNotifications>(PATH)
+        .unwrap();
+    let ctx = iface.signal_context();
+    MockNotifications::activation_token(ctx, 950_001, "token")
+        .await
+        .unwrap();
+    MockNotifications::action_invoked(ctx, 950_001, "default")
+        .await
+        .unwrap();
+    MockNotifications::notification_closed(ctx, 950_002, 2)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        signals.next().await,
+        Some(NotificationSignal::ActivationToken {
+            id: 950_001,
+            activation_token: "token".into()
+        })
+    );
+    assert_eq!(
+        signals.next().await,
+        Some(NotificationSignal::ActionInvoked {
+            id: 950_001,
+            action_key: "default".into()
+        })
+    );
+    assert_eq!(
+        signals.next().await,
+        Some(NotificationSignal::NotificationClosed {
+            id: 950_002,
+            reason: CloseReason::Dismissed
+        })
+    );
+}
+
+#[test]
+fn reply_default_only_with_capability() {
+    let plain = MockServer::with_capabilities("reply_plain", &["actions", "inline-reply"]);
+    let prefill = MockServer::with_capabilities(
+        "reply_prefill",
+        &["actions", "inline-reply", "x-kde-reply-default-text"],
+    );
+
+    for sub_bus in ["reply_plain", "reply_prefill"] {
+        Notification::at_bus(sub_bus)
+            .summary("Alice")
+            .inline_reply_with_default("Reply", "Write a reply…", "On my way!")
+            .send_id()
+            .unwrap();
+    }
+
+    let hints = |server: &MockServer| server.received()[0].hints.clone();
+    assert_eq!(hints(&plain), ["x-kde-reply-placeholder-text"]);
+    assert_eq!(
+        hints(&prefill),
+        ["x-kde-reply-default-text", "x-kde-reply-placeholder-text"]
+    );
+}
+
+#[test]
+fn reply_default_only_with_capability_on_update() {
+    let server = MockServer::with_capabilities("reply_update", &["actions", "inline-reply"]);
+
+    let mut handle = Notification::at_bus("reply_update")
+        .summary("Alice")
+        .show()
+        .unwrap();
+    handle.inline_reply_with_default("Reply", "Write a reply…", "On my way!");
+    handle.update();
+
+    let received = server.received();
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[1].hints, ["x-kde-reply-placeholder-text"]);
+}
+
+#[cfg(feature = "images")]
+#[test]
+fn icon_frames_only_with_icon_multi_on_update() {
+    let still = MockServer::with_capabilities("frames_still", &["icon-static"]);
+    let animated = MockServer::with_capabilities("frames_animated", &["icon-multi"]);
+
+    for sub_bus in ["frames_still", "frames_animated"] {
+        let mut handle = Notification::at_bus(sub_bus)
+            .summary("Uploading")
+            .show()
+            .unwrap();
+        let frame = || notify_rust::Image::from_rgb(1, 1, vec![0, 0, 0]).unwrap();
+        handle.animated_icon(vec![frame(), frame()]);
+        handle.update();
+    }
+
+    let hints = |server: &MockServer| server.received()[1].hints.clone();
+    assert_eq!(hints(&still), ["image_data"]);
+    assert_eq!(
+        hints(&animated),
+        ["image_data", "x-notify-rust-image-frames"]
+    );
+}
+
+#[test]
+fn action_fallback_only_without_actions_capability() {
+    let minimal = MockServer::with_capabilities("fallback_minimal", &["body"]);
+    let full = MockServer::start("fallback_full");
+
+    for bus in ["fallback_minimal", "fallback_full"] {
+        Notification::at_bus(bus)
+            .summary("Deploy to production?")
+            .body("3 commits")
+            .action("accept", "Deploy")
+            .with_action_fallback("Run `myapp accept` to confirm.")
+            .show_light()
+            .unwrap();
+    }
+
+    let received = &minimal.received()[0];
+    assert_eq!(received.body, "3 commits\nRun `myapp accept` to confirm.");
+    assert!(received.actions.is_empty());
+
+    let received = &full.received()[0];
+    assert_eq!(received.body, "3 commits");
+    assert_eq!(received.actions, ["accept", "Deploy"]);
+}
+
+#[test]
+fn action_fallback_applies_to_updated_actions() {
+    let server = MockServer::with_capabilities("fallback_update", &["body"]);
+
+    let mut handle = Notification::at_bus("fallback_update")
+        .summary("Incoming call")
+        .body("Alice")
+        .with_action_fallback("Run `myapp answer` to pick up.")
+        .show()
+        .unwrap();
+    handle.update_actions(&[("answer", "Answer")]);
+
+    let received = server.received();
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[1].body, "Alice\nRun `myapp answer` to pick up.");
+    assert!(received[1].actions.is_empty());
+    assert_eq!(handle.body, "Alice");
+}
+
+#[async_std::test]
+async fn action_fallback_fetches_capabilities_async() {
+    let server = MockServer::with_capabilities("fallback_async", &["body"]);
+
+    Notification::at_bus("fallback_async")
+        .summary("Deploy to production?")
+        .action("accept", "Deploy")
+        .with_action_fallback("Run `myapp accept` to confirm.")
+        .show_async()
+        .await
+        .unwrap();
+
+    let received = &server.received()[0];
+    assert_eq!(received.body, "Run `myapp accept` to confirm.");
+    assert!(received.actions.is_empty());
+}
+
+#[test]
+fn show_many_ids_sends_all_of_them() {
+    let server = MockServer::start("show_many_ids");
+    let notifications = (0..20)
+        .map(|i| {
+            Notification::at_bus("show_many_ids")
+                .summary(&format!("#{}", i))
+                .finalize()
+        })
+        .collect::<Vec<_>>();
+
+    let mut ids = notify_rust::show_many_ids(&notifications).unwrap();
+    assert_eq!(ids.len(), 20);
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), 20);
+
+    let mut summaries = server
+        .received()
+        .into_iter()
+        .map(|received| received.summary)
+        .collect::<Vec<_>>();
+    summaries.sort_unstable();
+    let mut expected = (0..20).map(|i| format!("#{}", i)).collect::<Vec<_>>();
+    expected.sort_unstable();
+    assert_eq!(summaries, expected);
+    assert!(notify_rust::show_many_ids(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn show_and_wait_returns_the_action() {
+    let server = MockServer::start("show_and_wait");
+    // the mock server hands out ids counting from 1
+    let emitter = server.invoke_later(1, "delete");
+    let response = Notification::at_bus("show_and_wait")
+        .summary("Delete 3 files?")
+        .action("delete", "Delete")
+        .show_and_wait()
+        .unwrap();
+    emitter.join().unwrap();
+    assert_eq!(response, OwnedActionResponse::Custom("delete".into()));
+
+    server.close_later(2, 2, Duration::from_millis(200));
+    let response = Notification::at_bus("show_and_wait")
+        .summary("Delete 3 files?")
+        .show_and_wait()
+        .unwrap();
+    assert_eq!(
+        response,
+        OwnedActionResponse::Closed(CloseReason::Dismissed)
+    );
+}
+
+#[test]
+fn scoped_notification_closes_at_scope_end() {
+    let server = MockServer::start("scoped");
+    let closed = |id: u32| server.closed().contains(&id);
+
+    fn process(fail: bool) -> Result<u32, u32> {
+        let mut progress = Notification::at_bus("scoped")
+            .summary("Processing")
+            .show_scoped()
+            .unwrap();
+        progress.body("step 1");
+        progress.update();
+        if fail {
+            return Err(progress.id());
+        }
+        Ok(progress.id())
+    }
+    let id = process(false).unwrap();
+    assert!(closed(id));
+    let failed = process(true).unwrap_err();
+    assert!(closed(failed));
+    assert_eq!(server.received().len(), 4);
+
+    let kept = Notification::at_bus("scoped")
+        .summary("Done")
+        .show_scoped()
+        .unwrap()
+        .into_handle();
+    let async_scoped = async_std::task::block_on(async {
+        Notification::at_bus("scoped")
+            .summary("Async")
+            .show_scoped()
+            .unwrap()
+            .id()
+    });
+    assert!(closed(async_scoped));
+    assert!(!server.closed().contains(&kept.id()));
+}
@@ -0,0 +1,35 @@
+#![cfg(all(feature = "zbus", unix, not(target_os = "macos")))]
+#![allow(deprecated)]
+//! The panic hook serves the whole process, so this runs in a test binary of its own.
+
+mod common;
+
+use std::{sync::Mutex, thread, time::Duration};
+
+use common::MockServer;
+use notify_rust::Notification;
+use zbus::blocking::Connection;
+
+#[test]
+fn panic_hook_gets_update_errors() {
+    let server = MockServer::start("panic_hook");
+    let mut handle = Notification::at_bus("panic_hook")
+        .summary("Syncing")
+        .show()
+        .unwrap();
+    drop(server);
+    let dbus = zbus::blocking::fdo::DBusProxy::new(&Connection::session().unwrap()).unwrap();
+    let name: zbus::names::BusName = "de.hoodie.Notification.panic_hook".try_into().unwrap();
+    while dbus.name_has_owner(name.clone()).unwrap() {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    static REPORTED: Mutex<Vec<thread::ThreadId>> = Mutex::new(Vec::new());
+    notify_rust::set_panic_hook(|_: &notify_rust::error::Error| {
+        REPORTED.lock().unwrap().push(thread::current().id());
+    });
+    handle.summary("Synced");
+    handle.update();
+
+    assert!(REPORTED.lock().unwrap().contains(&thread::current().id()));
+}
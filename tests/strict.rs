@@ -0,0 +1,77 @@
+#![cfg(all(feature = "zbus", unix, not(target_os = "macos")))]
+#![allow(deprecated)]
+//! Strict mode affects the whole process, so this runs in a test binary of its own.
+
+mod common;
+
+use std::sync::Mutex;
+
+use common::MockServer;
+use notify_rust::{error::ErrorKind, Hint, Notification};
+
+/// Held while a test has strict mode on.
+static STRICT: Mutex<()> = Mutex::new(());
+
+#[test]
+fn strict_capabilities_refuse_unsupported_actions() {
+    let server = MockServer::with_capabilities("strict_capabilities", &["body"]);
+
+    let _strict = STRICT.lock().unwrap();
+    notify_rust::set_strict_capabilities(true);
+    let refused = Notification::at_bus("strict_capabilities")
+        .summary("strict")
+        .action("default", "Open")
+        .show();
+    let accepted = Notification::at_bus("strict_capabilities")
+        .summary("strict")
+        .show();
+    notify_rust::set_strict_capabilities(false);
+
+    assert!(matches!(
+        refused.unwrap_err().kind(),
+        ErrorKind::UnsupportedFeature { feature: "actions" }
+    ));
+    accepted.unwrap();
+    assert_eq!(server.received().len(), 1);
+}
+
+#[test]
+fn strict_capabilities_check_updates() {
+    let server = MockServer::with_capabilities("strict_update", &["body"]);
+
+    let _strict = STRICT.lock().unwrap();
+    notify_rust::set_strict_capabilities(true);
+    let mut handle = Notification::at_bus("strict_update")
+        .summary("strict")
+        .show()
+        .unwrap();
+    handle.hint(Hint::SoundName("message-new-instant".into()));
+    let refused = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| handle.update()));
+    notify_rust::set_strict_capabilities(false);
+
+    assert!(refused.is_err());
+    assert_eq!(server.received().len(), 1);
+}
+
+#[test]
+fn strict_capabilities_are_fetched_anew_after_reset() {
+    let server = MockServer::with_capabilities("strict_reset", &["body"]);
+    let _strict = STRICT.lock().unwrap();
+    notify_rust::set_strict_capabilities(true);
+    let open = || {
+        Notification::at_bus("strict_reset")
+            .summary("strict")
+            .action("default", "Open")
+            .show()
+    };
+    assert!(open().is_err());
+
+    drop(server);
+    let server = MockServer::with_capabilities("strict_reset", &["actions"]);
+    notify_rust::reset_connection();
+    let shown = open();
+    notify_rust::set_strict_capabilities(false);
+
+    shown.unwrap();
+    assert_eq!(server.received().len(), 1);
+}
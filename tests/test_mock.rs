@@ -0,0 +1,84 @@
+#![cfg(all(
+    feature = "test-mock",
+    any(feature = "dbus", feature = "zbus"),
+    unix,
+    not(target_os = "macos")
+))]
+
+use notify_rust::{
+    ActionResponse, CloseReason, Notification, NotificationHandle, OwnedActionResponse,
+    TypedActionResponse,
+};
+
+fn call() -> Notification {
+    Notification::new()
+        .summary("Incoming call")
+        .action("accept", "Accept")
+        .action("decline", "Decline")
+        .finalize()
+}
+
+#[derive(Debug, PartialEq)]
+enum Answer {
+    Accept,
+    Decline,
+}
+
+impl std::str::FromStr for Answer {
+    type Err = String;
+    fn from_str(key: &str) -> Result<Self, String> {
+        match key {
+            "accept" => Ok(Answer::Accept),
+            "decline" => Ok(Answer::Decline),
+            _ => Err(format!("no answer {:?}", key)),
+        }
+    }
+}
+
+#[test]
+fn injected_action_is_invoked() {
+    let accept = || OwnedActionResponse::Custom("accept".into());
+
+    let mut invoked = None;
+    NotificationHandle::with_injected_action(call(), accept())
+        .wait_for_action(|action| invoked = Some(action.to_owned()));
+    assert_eq!(invoked.as_deref(), Some("accept"));
+
+    let handle = NotificationHandle::with_injected_action(call(), accept());
+    let mut response = None;
+    handle.wait_for_action_ref(|r: &ActionResponse| response = Some(r.to_owned_response()));
+    assert_eq!(response, Some(accept()));
+    assert!(matches!(
+        handle.wait_for_action_typed::<Answer>().unwrap(),
+        TypedActionResponse::Action(Answer::Accept)
+    ));
+    assert!(NotificationHandle::with_injected_action(
+        call(),
+        OwnedActionResponse::Custom("default".into())
+    )
+    .wait_for_action_typed::<Answer>()
+    .is_err());
+}
+
+#[test]
+fn injected_close_is_reported() {
+    let closed = || OwnedActionResponse::Closed(CloseReason::Expired);
+
+    let mut invoked = None;
+    NotificationHandle::with_injected_action(call(), closed())
+        .wait_for_action(|action| invoked = Some(action.to_owned()));
+    assert_eq!(invoked.as_deref(), Some("__closed"));
+
+    let reason = std::cell::Cell::new(None);
+    NotificationHandle::with_injected_action(call(), closed())
+        .on_close(|r: CloseReason| reason.set(Some(r)));
+    assert_eq!(reason.get(), Some(CloseReason::Expired));
+
+    let handle = NotificationHandle::with_injected_action(call(), closed());
+    assert_eq!(handle.summary, "Incoming call");
+    assert_ne!(handle.id(), 0);
+    assert_eq!(
+        handle.wait_for_close_timeout(std::time::Duration::from_secs(60)),
+        CloseReason::Expired
+    );
+}
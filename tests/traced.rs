@@ -0,0 +1,95 @@
+#![cfg(all(feature = "tracing", feature = "zbus", unix, not(target_os = "macos")))]
+#![allow(deprecated)]
+//! Spans go to the global subscriber of the process, so this runs in a test binary of its own.
+
+mod common;
+
+use std::{fmt::Write, sync::Mutex};
+
+use common::MockServer;
+use notify_rust::Notification;
+use tracing::{
+    field::{Field, Visit},
+    span, Subscriber,
+};
+use tracing_subscriber::{
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    Layer, Registry,
+};
+
+/// Every span with its fields, in the order they were created.
+static SPANS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Where a span is kept in [`SPANS`].
+struct Index(usize);
+
+struct Fields<'a>(&'a mut String);
+
+impl Visit for Fields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        write!(self.0, " {}={:?}", field.name(), value).unwrap();
+    }
+}
+
+struct Recorder;
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Recorder {
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let mut fields = attrs.metadata().name().to_owned();
+        attrs.record(&mut Fields(&mut fields));
+        let mut spans = SPANS.lock().unwrap();
+        ctx.span(id)
+            .unwrap()
+            .extensions_mut()
+            .insert(Index(spans.len()));
+        spans.push(fields);
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        let span = ctx.span(id).unwrap();
+        let Index(index) = *span.extensions().get::<Index>().unwrap();
+        values.record(&mut Fields(&mut SPANS.lock().unwrap()[index]));
+    }
+}
+
+/// The recorded spans that mention `needle`.
+fn spans_with(needle: &str) -> Vec<String> {
+    SPANS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|fields| fields.contains(needle))
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn spans_trace_send_and_wait() {
+    tracing::subscriber::set_global_default(Registry::default().with(Recorder)).unwrap();
+    let server = MockServer::start("traced");
+    let handle = Notification::at_bus("traced")
+        .summary("Traced")
+        .action("open", "Open")
+        .show()
+        .unwrap();
+    let emitter = server.invoke_later(handle.id(), "open");
+    handle.wait_for_action(|_| ());
+    emitter.join().unwrap();
+
+    let bus = "bus=de.hoodie.Notification.traced";
+    assert_eq!(
+        spans_with(bus),
+        [
+            format!("connect_and_send_notification {} summary_len=6 id=1", bus),
+            format!(
+                "send_notification_via_connection_at_bus replaces_id=0 {} summary_len=6",
+                bus
+            ),
+        ]
+    );
+    assert_eq!(
+        spans_with("wait_for_action_signal"),
+        ["wait_for_action_signal id=1 outcome=Some(Custom(\"open\"))"]
+    );
+}